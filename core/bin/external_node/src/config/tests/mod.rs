@@ -16,6 +16,7 @@ use zksync_config::{
         api::{MaxResponseSizeOverrides, Namespace},
         da_client::avail::AvailClientConfig,
         database::MerkleTreeMode,
+        networks::GatewayMode,
         object_store::ObjectStoreMode,
         observability::LogFormat,
     },
@@ -262,6 +263,8 @@ fn parsing_from_full_env() {
 
         EN_CONTRACTS_DIAMOND_PROXY_ADDR=0x0000000000000000000000000000000000010001
         EN_MAIN_NODE_RATE_LIMIT_RPS=150
+        EN_MAIN_NODE_REQUEST_TIMEOUT_MS=5000
+        EN_EXPECTED_PROTOCOL_VERSION=27
 
         EN_SNAPSHOTS_RECOVERY_ENABLED=true
         EN_SNAPSHOTS_RECOVERY_POSTGRES_MAX_CONCURRENCY=5
@@ -276,6 +279,7 @@ fn parsing_from_full_env() {
         EN_PRUNING_DATA_RETENTION_SEC=86400
 
         EN_GATEWAY_URL=https://127.0.0.1:3150/
+        EN_GATEWAY_MODE=url
         EN_BRIDGE_ADDRESSES_REFRESH_INTERVAL_SEC=300
         EN_TIMESTAMP_ASSERTER_MIN_TIME_TILL_END_SEC=90
         EN_CONSISTENCY_CHECKER_MAX_BATCHES_TO_RECHECK=5
@@ -489,12 +493,18 @@ fn test_parsing_general_config(source: impl ConfigSource + Clone) {
         config.main_node_rate_limit_rps,
         NonZeroUsize::new(150).unwrap()
     );
+    assert_eq!(
+        config.main_node_request_timeout,
+        Some(Duration::from_secs(5))
+    );
+    assert_eq!(config.expected_protocol_version, Some(27));
     assert_eq!(
         config.bridge_addresses_refresh_interval,
         Duration::from_secs(300)
     );
     assert_eq!(config.l1_chain_id, L1ChainId(8));
     assert_eq!(config.gateway_chain_id, Some(SLChainId(277)));
+    assert_eq!(config.gateway_mode, Some(GatewayMode::Url));
     assert_eq!(config.l2_chain_id, L2ChainId::from(270));
     assert_eq!(config.main_node_url.expose_str(), "https://127.0.0.1:3050/");
 
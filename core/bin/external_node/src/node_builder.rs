@@ -137,7 +137,8 @@ impl ExternalNodeBuilder {
             networks.main_node_url.clone(),
             networks.main_node_rate_limit_rps,
             networks.l2_chain_id,
-        );
+        )
+        .with_request_timeout(networks.main_node_request_timeout);
         self.node.add_layer(layer);
         Ok(self)
     }
@@ -545,6 +546,7 @@ impl ExternalNodeBuilder {
         self.node.add_layer(SettlementLayerData::new(ENConfig {
             chain_id: self.config.local.networks.l2_chain_id,
             gateway_rpc_url: self.config.local.secrets.l1.gateway_rpc_url.clone(),
+            gateway_mode: self.config.local.networks.gateway_mode,
         }));
         Ok(self)
     }
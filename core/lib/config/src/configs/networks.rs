@@ -1,8 +1,9 @@
 use std::{num::NonZeroUsize, time::Duration};
 
+use serde::{Deserialize, Serialize};
 use smart_config::{
     de::{Optional, Serde},
-    DescribeConfig, DeserializeConfig,
+    DescribeConfig, DeserializeConfig, ErrorWithOrigin,
 };
 use zksync_basic_types::{url::SensitiveUrl, Address, L1ChainId, L2ChainId, SLChainId};
 
@@ -15,8 +16,23 @@ pub struct SharedL1ContractsConfig {
     pub diamond_proxy_addr: Option<Address>,
 }
 
+/// Explicit mode of interacting with the gateway.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GatewayMode {
+    /// Gateway is never used, regardless of the configured gateway RPC URL or the default gateway URL
+    /// inferred from the bridgehub address.
+    Disabled,
+    /// Gateway is only accessed via the RPC URL configured in secrets; the default gateway URL is never inferred.
+    Url,
+}
+
 /// Temporary config for initializing external node, will be completely replaced by consensus config later.
 #[derive(Debug, Clone, PartialEq, DescribeConfig, DeserializeConfig)]
+#[config(validate(
+    Self::validate_request_timeout,
+    "`main_node_request_timeout` must be positive"
+))]
 pub struct NetworksConfig {
     /// Chain ID of the (L2) network that the node is a part of.
     #[config(with = Serde![int])]
@@ -27,12 +43,23 @@ pub struct NetworksConfig {
     /// Chain ID of the gateway network, if this network settles on one.
     #[config(with = Optional(Serde![int]))]
     pub gateway_chain_id: Option<SLChainId>,
+    /// Explicit gateway mode. If not set, the gateway RPC URL from secrets is used if it's specified;
+    /// otherwise, the default gateway URL is inferred from the bridgehub address, if possible.
+    #[config(with = Optional(Serde![str]))]
+    pub gateway_mode: Option<GatewayMode>,
     /// URL of an L2 peer node used to sync from.
     #[config(secret, with = Serde![str])]
     pub main_node_url: SensitiveUrl,
     /// Rate limiting configuration for the L2 peer node.
     #[config(default_t = NonZeroUsize::new(100).unwrap())]
     pub main_node_rate_limit_rps: NonZeroUsize,
+    /// Timeout for a single request to the L2 peer node. If not set, the client default is used.
+    pub main_node_request_timeout: Option<Duration>,
+    /// Minor protocol version that the L2 peer node is expected to advertise.
+    ///
+    /// **Important.** The value is only parsed for now; the node does not check it against the version
+    /// advertised by the L2 peer node yet.
+    pub expected_protocol_version: Option<u32>,
 
     #[config(default_t = Duration::from_secs(60))]
     pub bridge_addresses_refresh_interval: Duration,
@@ -45,9 +72,37 @@ impl NetworksConfig {
             l1_chain_id: L1ChainId(9),
             main_node_url: "http://localhost:3050/".parse().unwrap(),
             main_node_rate_limit_rps: 100.try_into().unwrap(),
+            main_node_request_timeout: None,
+            expected_protocol_version: None,
             bridge_addresses_refresh_interval: Duration::from_secs(60),
             gateway_chain_id: None,
+            gateway_mode: None,
+        }
+    }
+
+    /// Creates a config with the specified required params. Optional params are set to the same values
+    /// as if they were missing from config sources.
+    #[cfg(test)]
+    pub(crate) fn for_test(
+        main_node_url: SensitiveUrl,
+        l1_chain_id: L1ChainId,
+        l2_chain_id: L2ChainId,
+    ) -> Self {
+        Self {
+            l2_chain_id,
+            l1_chain_id,
+            main_node_url,
+            ..Self::for_tests()
+        }
+    }
+
+    fn validate_request_timeout(&self) -> Result<(), ErrorWithOrigin> {
+        if self.main_node_request_timeout == Some(Duration::ZERO) {
+            return Err(ErrorWithOrigin::custom(
+                "`main_node_request_timeout` must be positive",
+            ));
         }
+        Ok(())
     }
 }
 
@@ -62,8 +117,11 @@ mod tests {
             l2_chain_id: L2ChainId::from(271),
             l1_chain_id: L1ChainId(9),
             gateway_chain_id: Some(SLChainId(123)),
+            gateway_mode: Some(GatewayMode::Url),
             main_node_url: "http://127.0.0.1:3050/".parse().unwrap(),
             main_node_rate_limit_rps: NonZeroUsize::new(200).unwrap(),
+            main_node_request_timeout: Some(Duration::from_millis(2_500)),
+            expected_protocol_version: Some(28),
             bridge_addresses_refresh_interval: Duration::from_secs(15),
         }
     }
@@ -84,8 +142,11 @@ mod tests {
             EN_L1_CHAIN_ID=9
             EN_L2_CHAIN_ID=271
             EN_GATEWAY_CHAIN_ID=123
+            EN_GATEWAY_MODE=url
             EN_MAIN_NODE_URL=http://127.0.0.1:3050/
             EN_MAIN_NODE_RATE_LIMIT_RPS=200
+            EN_MAIN_NODE_REQUEST_TIMEOUT_MS=2500
+            EN_EXPECTED_PROTOCOL_VERSION=28
             EN_BRIDGE_ADDRESSES_REFRESH_INTERVAL="15s"
         "#;
         let env = Environment::from_dotenv("test.env", env)
@@ -103,10 +164,13 @@ mod tests {
         let yaml = r#"
             main_node_url: http://127.0.0.1:3050/
             main_node_rate_limit_rps: 200
+            main_node_request_timeout_ms: 2500
+            expected_protocol_version: 28
             gateway_url: null
             l2_chain_id: 271
             l1_chain_id: 9
             gateway_chain_id: 123
+            gateway_mode: url
             bridge_addresses_refresh_interval: '15s'
         "#;
         let yaml = Yaml::new("test.yml", serde_yaml::from_str(yaml).unwrap()).unwrap();
@@ -123,10 +187,13 @@ mod tests {
           external_node:
             main_node_url: http://127.0.0.1:3050/
             main_node_rate_limit_rps: 200
+            main_node_request_timeout_ms: 2500
+            expected_protocol_version: 28
             gateway_url: null
             l2_chain_id: 271
             l1_chain_id: 9
             gateway_chain_id: 123
+            gateway_mode: url
             bridge_addresses_refresh_interval: '15s'
         "#;
         let yaml = Yaml::new("test.yml", serde_yaml::from_str(yaml).unwrap()).unwrap();
@@ -136,4 +203,91 @@ mod tests {
         let config: NetworksConfig = repo.single().unwrap().parse().unwrap();
         assert_eq!(config, expected_config());
     }
+
+    #[test]
+    fn optional_params_default_to_none() {
+        let yaml = r#"
+            main_node_url: http://127.0.0.1:3050/
+            l2_chain_id: 271
+            l1_chain_id: 9
+        "#;
+        let yaml = Yaml::new("test.yml", serde_yaml::from_str(yaml).unwrap()).unwrap();
+
+        let schema = create_schema();
+        let repo = ConfigRepository::new(&schema).with(yaml);
+        let config: NetworksConfig = repo.single().unwrap().parse().unwrap();
+        assert_eq!(config.main_node_request_timeout, None);
+        assert_eq!(config.expected_protocol_version, None);
+        assert_eq!(config.gateway_mode, None);
+    }
+
+    #[test]
+    fn config_for_test_matches_parsed_config() {
+        let yaml = r#"
+            main_node_url: http://127.0.0.1:3050/
+            l2_chain_id: 271
+            l1_chain_id: 9
+        "#;
+        let yaml = Yaml::new("test.yml", serde_yaml::from_str(yaml).unwrap()).unwrap();
+
+        let schema = create_schema();
+        let repo = ConfigRepository::new(&schema).with(yaml);
+        let config: NetworksConfig = repo.single().unwrap().parse().unwrap();
+        let expected = NetworksConfig::for_test(
+            "http://127.0.0.1:3050/".parse().unwrap(),
+            L1ChainId(9),
+            L2ChainId::from(271),
+        );
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn parsing_gateway_mode() {
+        for (raw_mode, expected_mode) in [
+            ("disabled", GatewayMode::Disabled),
+            ("url", GatewayMode::Url),
+        ] {
+            let yaml = format!(
+                "main_node_url: http://127.0.0.1:3050/\n\
+                 l2_chain_id: 271\n\
+                 l1_chain_id: 9\n\
+                 gateway_mode: {raw_mode}"
+            );
+            let yaml = Yaml::new("test.yml", serde_yaml::from_str(&yaml).unwrap()).unwrap();
+            let schema = create_schema();
+            let repo = ConfigRepository::new(&schema).with(yaml);
+            let config: NetworksConfig = repo.single().unwrap().parse().unwrap();
+            assert_eq!(config.gateway_mode, Some(expected_mode));
+
+            // Check that the mode round-trips.
+            let serialized = serde_json::to_value(expected_mode).unwrap();
+            assert_eq!(serialized, raw_mode);
+            let deserialized: GatewayMode = serde_json::from_value(serialized).unwrap();
+            assert_eq!(deserialized, expected_mode);
+        }
+    }
+
+    #[test]
+    fn zero_request_timeout_is_rejected() {
+        let env = r#"
+            EN_L1_CHAIN_ID=9
+            EN_L2_CHAIN_ID=271
+            EN_MAIN_NODE_URL=http://127.0.0.1:3050/
+            EN_MAIN_NODE_REQUEST_TIMEOUT_MS=0
+        "#;
+        let env = Environment::from_dotenv("test.env", env)
+            .unwrap()
+            .strip_prefix("EN_");
+
+        let schema = create_schema();
+        let repo = ConfigRepository::new(&schema).with(env);
+        let err = repo
+            .single::<NetworksConfig>()
+            .unwrap()
+            .parse()
+            .unwrap_err();
+        assert_eq!(err.len(), 1, "{err}");
+        let err = err.first().inner().to_string();
+        assert!(err.contains("must be positive"), "{err}");
+    }
 }
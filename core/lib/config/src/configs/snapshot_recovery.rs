@@ -158,4 +158,19 @@ mod tests {
         let config: SnapshotRecoveryConfig = repo.single().unwrap().parse().unwrap();
         assert_eq!(config, expected_config());
     }
+
+    #[test]
+    fn object_store_is_optional() {
+        let yaml = r#"
+        snapshot_recovery:
+          enabled: true
+          l1_batch: 1234
+        "#;
+        let yaml = Yaml::new("test.yml", serde_yaml::from_str(yaml).unwrap()).unwrap();
+        let schema = create_schema();
+        let repo = ConfigRepository::new(&schema).with(yaml);
+        let config: SnapshotRecoveryConfig = repo.single().unwrap().parse().unwrap();
+        assert!(config.enabled);
+        assert_eq!(config.object_store, None);
+    }
 }
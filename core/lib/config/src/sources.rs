@@ -71,16 +71,48 @@ impl ConfigFilePaths {
             .with_context(|| format!("failed digesting YAML map from {path:?}"))
     }
 
+    /// Parses YAML config obtained from a non-file location (e.g., fetched over network).
+    /// `origin` is used to refer to the config in errors.
+    pub fn parse_yaml(origin: &str, contents: &str) -> anyhow::Result<Yaml> {
+        let raw: serde_yaml::Mapping = serde_yaml::from_str(contents)
+            .with_context(|| format!("failed reading YAML map from {origin}"))?;
+        Yaml::new(origin, raw).with_context(|| format!("failed digesting YAML map from {origin}"))
+    }
+
     /// **Important.** This method is blocking.
     pub fn into_config_sources<'a>(
         self,
         env_prefix: impl Into<Option<&'a str>>,
     ) -> anyhow::Result<ConfigSources> {
-        let env_prefix = env_prefix.into();
+        let general = self.general.as_deref().map(Self::read_yaml).transpose()?;
+        self.build_sources(general, env_prefix.into())
+    }
+
+    /// Same as [`Self::into_config_sources()`], but uses the provided `general` config instead of reading it
+    /// from a file. [`Self::general`] must not be set.
+    ///
+    /// **Important.** This method is blocking.
+    pub fn into_config_sources_with_general<'a>(
+        self,
+        general: Yaml,
+        env_prefix: impl Into<Option<&'a str>>,
+    ) -> anyhow::Result<ConfigSources> {
+        anyhow::ensure!(
+            self.general.is_none(),
+            "general config is provided both as a file and as a parsed config"
+        );
+        self.build_sources(Some(general), env_prefix.into())
+    }
+
+    fn build_sources(
+        self,
+        general: Option<Yaml>,
+        env_prefix: Option<&str>,
+    ) -> anyhow::Result<ConfigSources> {
         let mut sources = smart_config::ConfigSources::default();
 
-        if let Some(path) = &self.general {
-            sources.push(Self::read_yaml(path)?);
+        if let Some(general) = general {
+            sources.push(general);
         }
         if let Some(path) = &self.secrets {
             sources.push(Self::read_yaml(path)?);
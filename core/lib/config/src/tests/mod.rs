@@ -36,6 +36,26 @@ fn pre_smart_config_files_can_be_parsed() {
     repo.parse::<Wallets>().unwrap();
 }
 
+#[test]
+fn general_config_can_be_provided_as_parsed_yaml() {
+    let config_dir = Path::new("./src/tests/pre_smart_config");
+    let contents = std::fs::read_to_string(config_dir.join("general.yaml")).unwrap();
+    let general = ConfigFilePaths::parse_yaml("http://localhost/general.yaml", &contents).unwrap();
+    let paths = ConfigFilePaths {
+        secrets: Some(config_dir.join("secrets.yaml")),
+        ..ConfigFilePaths::default()
+    };
+    let config_sources = paths
+        .into_config_sources_with_general(general, None)
+        .unwrap();
+    let schema = full_config_schema();
+    let mut repo = config_sources.build_repository(&schema);
+    assert_general_config(repo.parse().unwrap());
+    assert_secrets(repo.parse().unwrap());
+
+    ConfigFilePaths::parse_yaml("http://localhost/general.yaml", "- not a map").unwrap_err();
+}
+
 // These checks aren't intended to be exhaustive; they mostly check parsing completeness.
 fn assert_general_config(general: GeneralConfig) {
     assert_eq!(general.api_config.unwrap().web3_json_rpc.http_port, 3050);
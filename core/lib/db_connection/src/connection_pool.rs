@@ -32,6 +32,8 @@ pub struct ConnectionPoolBuilder<DB: DbMarker> {
     acquire_timeout: Duration,
     acquire_retries: usize,
     statement_timeout: Option<Duration>,
+    max_lifetime: Option<Duration>,
+    idle_timeout: Option<Duration>,
     _db: PhantomData<DB>,
 }
 
@@ -44,6 +46,8 @@ impl<DB: DbMarker> fmt::Debug for ConnectionPoolBuilder<DB> {
             .field("acquire_timeout", &self.acquire_timeout)
             .field("acquire_retries", &self.acquire_retries)
             .field("statement_timeout", &self.statement_timeout)
+            .field("max_lifetime", &self.max_lifetime)
+            .field("idle_timeout", &self.idle_timeout)
             .field("db", &any::type_name::<DB>())
             .finish()
     }
@@ -75,6 +79,20 @@ impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
         self
     }
 
+    /// Sets the maximum lifetime of individual connections in the pool; connections are closed and reopened
+    /// once they reach this age. If not specified, the `sqlx` default (30 minutes) is used.
+    pub fn set_max_lifetime(&mut self, lifetime: Option<Duration>) -> &mut Self {
+        self.max_lifetime = lifetime;
+        self
+    }
+
+    /// Sets the maximum time a connection can stay idle in the pool before it is closed.
+    /// If not specified, the `sqlx` default (10 minutes) is used.
+    pub fn set_idle_timeout(&mut self, timeout: Option<Duration>) -> &mut Self {
+        self.idle_timeout = timeout;
+        self
+    }
+
     /// Returns the maximum number of connections that can be allocated by the pool.
     pub fn max_size(&self) -> u32 {
         self.max_size
@@ -82,9 +100,15 @@ impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
 
     /// Builds a connection pool from this builder.
     pub async fn build(&self) -> anyhow::Result<ConnectionPool<DB>> {
-        let options = PgPoolOptions::new()
+        let mut options = PgPoolOptions::new()
             .max_connections(self.max_size)
             .acquire_timeout(self.acquire_timeout);
+        if let Some(lifetime) = self.max_lifetime {
+            options = options.max_lifetime(lifetime);
+        }
+        if let Some(timeout) = self.idle_timeout {
+            options = options.idle_timeout(timeout);
+        }
         let mut connect_options: PgConnectOptions = self
             .database_url
             .expose_str()
@@ -117,6 +141,8 @@ impl<DB: DbMarker> ConnectionPoolBuilder<DB> {
             acquire_timeout: self.acquire_timeout,
             acquire_retries: self.acquire_retries,
             statement_timeout: self.statement_timeout,
+            max_lifetime: self.max_lifetime,
+            idle_timeout: self.idle_timeout,
             _db: PhantomData,
         };
         singleton_builder.build().await
@@ -354,6 +380,8 @@ impl<DB: DbMarker> ConnectionPool<DB> {
             acquire_timeout: Duration::from_secs(30), // Default value used by `sqlx`
             acquire_retries: 2,
             statement_timeout: None,
+            max_lifetime: None,
+            idle_timeout: None,
             _db: PhantomData,
         }
     }
@@ -0,0 +1,127 @@
+//! Debugging tool that loads ancestors for the specified tree instructions from a Merkle tree stored in RocksDB,
+//! the same way they are loaded when updating the tree.
+
+use std::{fs, path::PathBuf};
+
+use anyhow::Context as _;
+use clap::Parser;
+use zksync_merkle_tree::{
+    unstable::Node, Key, MerkleTree, RocksDBWrapper, TreeInstruction, ValueHash,
+};
+
+/// CLI for loading ancestors of keys touched by tree instructions.
+#[derive(Debug, Parser)]
+struct Cli {
+    /// Path to the Merkle tree RocksDB directory.
+    #[arg(long = "db")]
+    db_path: PathBuf,
+    /// Tree version to load ancestors for. If not specified, the latest version is used.
+    #[arg(long)]
+    version: Option<u64>,
+    /// Path to a file with tree instructions, one per line. A write instruction is specified as
+    /// `write <key> <leaf_index> <value>`, and a read instruction as `read <key>`; keys and values are hex-encoded
+    /// 32-byte words. Empty lines and lines starting with `#` are skipped.
+    #[arg(long = "instructions")]
+    instructions_path: PathBuf,
+}
+
+fn parse_instruction(line: &str) -> anyhow::Result<TreeInstruction> {
+    fn parse_word(raw: &str) -> anyhow::Result<Key> {
+        let hex = raw.strip_prefix("0x").unwrap_or(raw);
+        Key::from_str_radix(hex, 16).with_context(|| format!("invalid 32-byte word `{raw}`"))
+    }
+
+    let parts: Vec<_> = line.split_whitespace().collect();
+    Ok(match parts.as_slice() {
+        ["read", key] => TreeInstruction::Read(parse_word(key)?),
+        ["write", key, leaf_index, value] => {
+            let leaf_index = leaf_index
+                .parse()
+                .with_context(|| format!("invalid leaf index `{leaf_index}`"))?;
+            let mut value_bytes = [0_u8; 32];
+            parse_word(value)?.to_big_endian(&mut value_bytes);
+            TreeInstruction::write(parse_word(key)?, leaf_index, ValueHash::from(value_bytes))
+        }
+        _ => {
+            anyhow::bail!("expected `write <key> <leaf_index> <value>` or `read <key>` instruction")
+        }
+    })
+}
+
+impl Cli {
+    fn parse_instructions(&self) -> anyhow::Result<Vec<TreeInstruction>> {
+        let raw_instructions = fs::read_to_string(&self.instructions_path).with_context(|| {
+            format!(
+                "failed reading instructions from {:?}",
+                self.instructions_path
+            )
+        })?;
+        raw_instructions
+            .lines()
+            .enumerate()
+            .map(|(i, line)| (i + 1, line.trim()))
+            .filter(|(_, line)| !line.is_empty() && !line.starts_with('#'))
+            .map(|(line_number, line)| {
+                parse_instruction(line)
+                    .with_context(|| format!("invalid instruction `{line}` at line {line_number}"))
+            })
+            .collect()
+    }
+
+    fn run(self) -> anyhow::Result<()> {
+        let instructions = self.parse_instructions()?;
+        anyhow::ensure!(
+            !instructions.is_empty(),
+            "no instructions specified in {:?}",
+            self.instructions_path
+        );
+        anyhow::ensure!(
+            self.db_path.is_dir(),
+            "Merkle tree RocksDB directory {:?} does not exist",
+            self.db_path
+        );
+
+        let db =
+            RocksDBWrapper::new(&self.db_path).context("failed opening Merkle tree RocksDB")?;
+        let tree = MerkleTree::new(db).context("cannot initialize Merkle tree")?;
+        let version = match self.version {
+            Some(version) => version,
+            None => tree.latest_version().context("Merkle tree is empty")?,
+        };
+
+        let keys: Vec<_> = instructions.iter().map(TreeInstruction::key).collect();
+        let loaded = tree.load_ancestors(version, &keys)?;
+        println!(
+            "Loaded ancestors for {} instructions at version {version} using {} DB reads",
+            instructions.len(),
+            loaded.db_reads
+        );
+        for (instruction, ancestor) in instructions.iter().zip(&loaded.ancestors) {
+            match instruction {
+                TreeInstruction::Read(key) => println!("read {key:064x}"),
+                TreeInstruction::Write(entry) => println!(
+                    "write {:064x} (leaf index {}, value {:?})",
+                    entry.key, entry.leaf_index, entry.value
+                ),
+            }
+            println!("  ancestor at depth {}", ancestor.nibble_count);
+            match &ancestor.node {
+                None => println!("  (empty tree)"),
+                Some(Node::Leaf(leaf)) => {
+                    let relation = if leaf.full_key == ancestor.key {
+                        "same key"
+                    } else {
+                        "other key"
+                    };
+                    println!("  leaf ({relation}): {leaf:?}");
+                }
+                Some(Node::Internal(node)) => println!("  internal node: {node:?}"),
+            }
+        }
+        Ok(())
+    }
+}
+
+fn main() -> anyhow::Result<()> {
+    Cli::parse().run()
+}
@@ -1,6 +1,6 @@
 //! Error types.
 
-use std::{error, fmt, str::Utf8Error};
+use std::{error, fmt, io, str::Utf8Error};
 
 use crate::types::NodeKey;
 
@@ -24,6 +24,22 @@ pub enum DeserializeErrorKind {
     InvalidChildKind,
     #[error("data left after deserialization")]
     Leftovers,
+    /// An internal node specifies multiple children at the same nibble.
+    #[error("duplicate child at nibble {0:x} in an internal tree node")]
+    DuplicateChild(u8),
+    /// A child reference in an internal node has a version greater than the node itself.
+    #[error(
+        "child at nibble {nibble:x} has version {child_version}, which is greater than \
+         the internal node version {node_version}"
+    )]
+    FutureChildVersion {
+        /// Nibble of the child reference.
+        nibble: u8,
+        /// Version of the child reference.
+        child_version: u64,
+        /// Version of the internal node.
+        node_version: u64,
+    },
 
     /// Missing required tag in the tree manifest.
     #[error("missing required tag `{0}` in tree manifest")]
@@ -40,6 +56,39 @@ pub enum DeserializeErrorKind {
         #[source]
         err: Box<dyn error::Error + Send + Sync>,
     },
+    /// Value hash length declared in the tree manifest differs from the length of hashes produced by the tree hasher.
+    #[error(
+        "value hash length {actual} declared in tree manifest differs from the length {expected} \
+         of hashes produced by the tree hasher"
+    )]
+    HashLengthMismatch {
+        /// Hash length supported by the tree hasher.
+        expected: usize,
+        /// Hash length declared in the manifest.
+        actual: usize,
+    },
+    /// Version of the latest tree root is inconsistent with the number of versions declared in the tree manifest,
+    /// which indicates a torn write.
+    #[error(
+        "latest tree root has version {root_version}, which is {} the number of versions {version_count} \
+         declared in tree manifest",
+        if *root_version >= *version_count { "ahead of" } else { "behind" }
+    )]
+    VersionMismatch {
+        /// Number of versions declared in the manifest.
+        version_count: u64,
+        /// Version of the latest tree root.
+        root_version: u64,
+    },
+    /// Malformed custom tag in the tree manifest.
+    #[error("malformed custom tag `{name}` in tree manifest: {err}")]
+    MalformedCustomTag {
+        /// Custom tag name (without the `custom.` prefix).
+        name: String,
+        /// Error that has occurred parsing the tag.
+        #[source]
+        err: Box<dyn error::Error + Send + Sync>,
+    },
 }
 
 impl DeserializeErrorKind {
@@ -137,6 +186,25 @@ impl fmt::Display for DeserializeError {
 
 impl error::Error for DeserializeError {}
 
+/// Error serializing a tree node into a sink that can fail (e.g., a fixed-size slice or an [`io::Write`] implementation).
+#[derive(Debug, thiserror::Error)]
+#[non_exhaustive]
+pub enum SerializeError {
+    /// Output slice is too small to hold the serialized node.
+    #[error(
+        "output buffer is too small: {required} bytes required, but only {available} available"
+    )]
+    InsufficientSpace {
+        /// Serialized node size.
+        required: usize,
+        /// Output slice size.
+        available: usize,
+    },
+    /// I/O error writing to the sink.
+    #[error("failed writing serialized node: {0}")]
+    Io(#[from] io::Error),
+}
+
 /// Error accessing a specific tree version.
 #[derive(Debug)]
 pub struct NoVersionError {
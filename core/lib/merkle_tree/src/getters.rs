@@ -8,6 +8,26 @@ use crate::{
     Database, HashTree, Key, MerkleTree, NoVersionError, PruneDatabase, ValueHash,
 };
 
+/// Ancestors loaded by [`MerkleTree::load_ancestors()`].
+#[derive(Debug, Clone)]
+pub struct LoadedAncestors {
+    /// Deepest ancestor for each requested key, in the same order as keys were requested.
+    pub ancestors: Vec<KeyAncestor>,
+    /// Number of database reads performed to load ancestors.
+    pub db_reads: u64,
+}
+
+/// Deepest node present in the tree on the path to a certain key.
+#[derive(Debug, Clone)]
+pub struct KeyAncestor {
+    /// Requested key.
+    pub key: Key,
+    /// Number of nibbles in the ancestor node key; 0 corresponds to the root node.
+    pub nibble_count: usize,
+    /// Ancestor node. `None` if the tree is empty.
+    pub node: Option<Node>,
+}
+
 impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
     /// Reads entries with the specified keys from the tree. The entries are returned in the same order
     /// as requested. If a certain key is not present in the tree, the corresponding returned entry
@@ -25,6 +45,49 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
         load_and_transform_entries(&self.db, version, leaf_keys, extract_entry)
     }
 
+    /// Loads the deepest nodes present in the tree on the paths to the specified keys, the same way
+    /// they are loaded when updating the tree. This is mostly useful for debugging.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tree `version` is missing.
+    pub fn load_ancestors(
+        &self,
+        version: u64,
+        leaf_keys: &[Key],
+    ) -> Result<LoadedAncestors, NoVersionError> {
+        let _profiling_guard = self
+            .db
+            .start_profiling(ProfiledTreeOperation::LoadAncestors);
+        let root = self.db.root(version).ok_or_else(|| {
+            let manifest = self.db.manifest().unwrap_or_default();
+            NoVersionError {
+                missing_version: version,
+                version_count: manifest.version_count,
+            }
+        })?;
+        let sorted_keys = SortedKeys::new(leaf_keys.iter().copied());
+        let mut patch_set = WorkingPatchSet::new(version, root);
+        let LoadAncestorsResult {
+            longest_prefixes,
+            db_reads,
+        } = patch_set.load_ancestors(&sorted_keys, &self.db);
+
+        let ancestors = leaf_keys
+            .iter()
+            .zip(&longest_prefixes)
+            .map(|(&key, longest_prefix)| KeyAncestor {
+                key,
+                nibble_count: longest_prefix.nibble_count(),
+                node: patch_set.get(longest_prefix).cloned(),
+            })
+            .collect();
+        Ok(LoadedAncestors {
+            ancestors,
+            db_reads,
+        })
+    }
+
     /// Reads entries together with Merkle proofs with the specified keys from the tree. The entries are returned
     /// in the same order as requested.
     ///
@@ -115,9 +178,38 @@ impl<DB: PruneDatabase, H: HashTree> MerkleTreeRecovery<DB, H> {
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
+
     use super::*;
     use crate::PatchSet;
 
+    #[test]
+    fn loading_ancestors() {
+        let mut tree = MerkleTree::new(PatchSet::default()).unwrap();
+        let keys = [Key::from(1), Key::from(2) << 252, Key::from(3) << 252];
+        let entries = (1..)
+            .zip(keys)
+            .map(|(leaf_index, key)| TreeEntry::new(key, leaf_index, ValueHash::repeat_byte(1)))
+            .collect();
+        tree.extend(entries).unwrap();
+        let missing_key = Key::from(4) << 252;
+
+        let loaded = tree.load_ancestors(0, &[keys[1], missing_key]).unwrap();
+        assert_eq!(loaded.ancestors.len(), 2);
+        // The key is a direct child of the root.
+        let ancestor = &loaded.ancestors[0];
+        assert_eq!(ancestor.key, keys[1]);
+        assert_eq!(ancestor.nibble_count, 1);
+        assert_matches!(&ancestor.node, Some(Node::Leaf(leaf)) if leaf.full_key == keys[1]);
+        // The root has no child at the first nibble of the missing key.
+        let ancestor = &loaded.ancestors[1];
+        assert_eq!(ancestor.nibble_count, 0);
+        assert_matches!(&ancestor.node, Some(Node::Internal(_)));
+
+        let err = tree.load_ancestors(1, &keys).unwrap_err();
+        assert_eq!(err.missing_version, 1);
+    }
+
     #[test]
     fn entries_in_empty_tree() {
         let mut tree = MerkleTree::new(PatchSet::default()).unwrap();
@@ -50,6 +50,7 @@ use zksync_crypto_primitives::hasher::blake2::Blake2Hasher;
 
 pub use crate::{
     errors::NoVersionError,
+    getters::{KeyAncestor, LoadedAncestors},
     hasher::{HashTree, TreeRangeDigest},
     pruning::{MerkleTreePruner, MerkleTreePrunerHandle},
     storage::{
@@ -82,8 +83,14 @@ mod utils;
 #[doc(hidden)]
 pub mod unstable {
     pub use crate::{
-        errors::DeserializeError,
-        types::{Manifest, Node, NodeKey, ProfiledTreeOperation, RawNode, Root},
+        errors::{DeserializeError, SerializeError},
+        storage::{
+            verify_tree_nodes, verify_version_consistency, DefaultEncoder, NodeAnomaly,
+            NodeEncoder, VerifyReport,
+        },
+        types::{
+            InternalNode, LeafNode, Manifest, Node, NodeKey, ProfiledTreeOperation, RawNode, Root,
+        },
     };
 }
 
@@ -187,6 +194,19 @@ impl<DB: Database, H: HashTree> MerkleTree<DB, H> {
         self.db.manifest()?.version_count.checked_sub(1)
     }
 
+    /// Returns the minor protocol version supported by the node that has created the tree, or `None`
+    /// if the tree was created before the version was recorded.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the recorded version is malformed.
+    pub fn protocol_version(&self) -> Result<Option<u16>, DeserializeError> {
+        let Some(manifest) = self.db.manifest() else {
+            return Ok(None);
+        };
+        manifest.protocol_version()
+    }
+
     /// Returns the root hash for the latest version of the tree.
     pub fn latest_root_hash(&self) -> ValueHash {
         let root_hash = self
@@ -3,7 +3,7 @@
 use std::{any::Any, ops};
 
 use crate::{
-    errors::DeserializeError,
+    errors::{DeserializeError, DeserializeErrorKind, ErrorContext},
     storage::patch::PatchSet,
     types::{Manifest, Node, NodeKey, ProfiledTreeOperation, Root},
 };
@@ -479,6 +479,28 @@ impl PruneDatabase for PatchSet {
     }
 }
 
+/// Checks that the version of the latest tree root (i.e., the version in its [`NodeKey`]) agrees with
+/// the number of versions declared in the tree `manifest`. A mismatch indicates a torn write,
+/// e.g. if the root was persisted, but the manifest was not (or vice versa).
+///
+/// # Errors
+///
+/// Returns an error if the root version is ahead of or behind the manifest.
+pub fn verify_version_consistency(
+    manifest: &Manifest,
+    latest_root_version: u64,
+) -> Result<(), DeserializeError> {
+    if manifest.version_count.checked_sub(1) == Some(latest_root_version) {
+        Ok(())
+    } else {
+        let err = DeserializeErrorKind::VersionMismatch {
+            version_count: manifest.version_count,
+            root_version: latest_root_version,
+        };
+        Err(err.with_context(ErrorContext::Manifest))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use assert_matches::assert_matches;
@@ -492,6 +514,40 @@ mod tests {
         types::{InternalNode, Nibbles},
     };
 
+    #[test]
+    fn verifying_version_consistency() {
+        let manifest = Manifest::new(10, &());
+        let root_key = Nibbles::EMPTY.with_version(9);
+        verify_version_consistency(&manifest, root_key.version).unwrap();
+
+        let root_key = Nibbles::EMPTY.with_version(10);
+        let err = verify_version_consistency(&manifest, root_key.version)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "latest tree root has version 10, which is ahead of the number of versions 10"
+            ),
+            "{err}"
+        );
+        assert!(err.starts_with("[in tree manifest]"), "{err}");
+
+        let root_key = Nibbles::EMPTY.with_version(7);
+        let err = verify_version_consistency(&manifest, root_key.version)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "latest tree root has version 7, which is behind the number of versions 10"
+            ),
+            "{err}"
+        );
+
+        // An empty tree cannot have roots.
+        let manifest = Manifest::new(0, &());
+        verify_version_consistency(&manifest, 0).unwrap_err();
+    }
+
     #[test]
     fn patch_set_with_update() {
         let manifest = Manifest::new(10, &());
@@ -1,10 +1,14 @@
 //! Storage-related logic.
 
 pub use self::{
-    database::{Database, NodeKeys, Patched, PruneDatabase, PrunePatchSet},
+    database::{
+        verify_version_consistency, Database, NodeKeys, Patched, PruneDatabase, PrunePatchSet,
+    },
     parallel::PersistenceThreadHandle,
     patch::PatchSet,
     rocksdb::{MerkleTreeColumnFamily, RocksDBWrapper},
+    serialization::{DefaultEncoder, NodeEncoder},
+    verify::{verify_tree_nodes, NodeAnomaly, VerifyReport},
 };
 pub(crate) use self::{
     parallel::MaybeParallel,
@@ -20,6 +24,7 @@ use crate::{
 };
 
 mod database;
+mod object_store;
 mod parallel;
 mod patch;
 mod proofs;
@@ -27,6 +32,7 @@ mod rocksdb;
 mod serialization;
 #[cfg(test)]
 mod tests;
+mod verify;
 
 /// Tree operation: either inserting a new version or updating an existing one (the latter is only
 /// used during tree recovery).
@@ -272,6 +278,12 @@ impl<'a, DB: Database + ?Sized> Storage<'a, DB> {
             Root::Empty
         };
 
+        let mut updater = TreeUpdater::new(version, root);
+        if !create_new_version {
+            // All loaded nodes are rewritten on update, so we track which of them are actually changed.
+            updater.patch_set.track_content_hashes();
+        }
+
         Self {
             db,
             hasher,
@@ -282,7 +294,7 @@ impl<'a, DB: Database + ?Sized> Storage<'a, DB> {
             } else {
                 Operation::Update
             },
-            updater: TreeUpdater::new(version, root),
+            updater,
         }
     }
 
@@ -0,0 +1,74 @@
+//! Storing tree manifest in an object store.
+
+use anyhow::Context as _;
+use zksync_object_store::{Bucket, ObjectStore, ObjectStoreError};
+
+use crate::{errors::ErrorContext, types::Manifest};
+
+impl Manifest {
+    /// Serializes this manifest and stores it in the [`Bucket::MerkleTree`] bucket of the object store
+    /// under the specified `key`. Uses the same encoding as the RocksDB storage.
+    ///
+    /// # Errors
+    ///
+    /// Propagates object store I/O errors.
+    pub async fn store(&self, store: &dyn ObjectStore, key: &str) -> anyhow::Result<()> {
+        let mut buffer = vec![];
+        self.serialize(&mut buffer);
+        store
+            .put_raw(Bucket::MerkleTree, key, buffer)
+            .await
+            .with_context(|| format!("failed storing tree manifest `{key}`"))
+    }
+
+    /// Loads a manifest previously stored with [`Self::store()`]. Returns `Ok(None)` if there is no manifest
+    /// with the specified `key`.
+    ///
+    /// # Errors
+    ///
+    /// Propagates object store I/O errors and errors deserializing the manifest.
+    pub async fn load(store: &dyn ObjectStore, key: &str) -> anyhow::Result<Option<Self>> {
+        let raw_manifest = match store.get_raw(Bucket::MerkleTree, key).await {
+            Ok(raw) => raw,
+            Err(ObjectStoreError::KeyNotFound(_)) => return Ok(None),
+            Err(err) => {
+                return Err(err).with_context(|| format!("failed loading tree manifest `{key}`"));
+            }
+        };
+        let manifest = Self::deserialize(&raw_manifest)
+            .map_err(|err| err.with_context(ErrorContext::Manifest))
+            .with_context(|| format!("failed deserializing tree manifest `{key}`"))?;
+        Ok(Some(manifest))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_crypto_primitives::hasher::blake2::Blake2Hasher;
+    use zksync_object_store::MockObjectStore;
+
+    use super::*;
+    use crate::types::TreeTags;
+
+    #[tokio::test]
+    async fn manifest_roundtrip_via_object_store() {
+        let store = MockObjectStore::arc();
+        assert_eq!(Manifest::load(&*store, "manifest").await.unwrap(), None);
+
+        let mut tags = TreeTags::new(&Blake2Hasher);
+        tags.custom.insert("test".to_owned(), "value".to_owned());
+        let manifest = Manifest {
+            version_count: 42,
+            tags: Some(tags),
+        };
+        manifest.store(&*store, "manifest").await.unwrap();
+        let loaded = Manifest::load(&*store, "manifest").await.unwrap();
+        assert_eq!(loaded, Some(manifest));
+
+        store
+            .put_raw(Bucket::MerkleTree, "mangled", vec![42, 0xff])
+            .await
+            .unwrap();
+        Manifest::load(&*store, "mangled").await.unwrap_err();
+    }
+}
@@ -175,6 +175,9 @@ impl PatchSet {
 struct WorkingNode {
     inner: Node,
     prev_version: Option<u64>,
+    /// Content hash of the node as it was loaded from the DB. Only computed if content hash tracking
+    /// is enabled for the patch set.
+    content_hash: Option<ValueHash>,
 }
 
 impl WorkingNode {
@@ -182,8 +185,20 @@ impl WorkingNode {
         Self {
             inner,
             prev_version,
+            content_hash: None,
         }
     }
+
+    fn with_content_hash(mut self) -> Self {
+        self.content_hash = Some(self.inner.content_hash());
+        self
+    }
+
+    /// Checks whether the node is unchanged compared to its DB state. Always returns `false`
+    /// if the content hash wasn't computed when loading the node.
+    fn is_unchanged(&self) -> bool {
+        self.content_hash == Some(self.inner.content_hash())
+    }
 }
 
 /// Result of ancestors loading.
@@ -204,6 +219,8 @@ pub(crate) struct WorkingPatchSet {
     // `depth == nibble_count * 4`) so that we can compute hashes for all changed nodes
     // in a single traversal in `Self::finalize()`.
     changes_by_nibble_count: Vec<HashMap<NibblesBytes, WorkingNode>>,
+    /// Whether to compute content hashes for nodes loaded from the DB.
+    track_content_hashes: bool,
 }
 
 impl WorkingPatchSet {
@@ -219,9 +236,17 @@ impl WorkingPatchSet {
         Self {
             root_version,
             changes_by_nibble_count,
+            track_content_hashes: false,
         }
     }
 
+    /// Enables computing content hashes for nodes loaded from the DB. This allows to skip
+    /// nodes that were not changed when finalizing an update patch. Should be called before
+    /// loading any nodes.
+    pub fn track_content_hashes(&mut self) {
+        self.track_content_hashes = true;
+    }
+
     pub fn root_version(&self) -> u64 {
         self.root_version
     }
@@ -279,7 +304,10 @@ impl WorkingPatchSet {
     fn push_level_from_db<'a>(&mut self, level: impl Iterator<Item = (&'a NodeKey, Node)>) {
         let level = level
             .map(|(key, node)| {
-                let node = WorkingNode::new(node, Some(key.version));
+                let mut node = WorkingNode::new(node, Some(key.version));
+                if self.track_content_hashes {
+                    node = node.with_content_hash();
+                }
                 (*key.nibbles.bytes(), node)
             })
             .collect();
@@ -313,6 +341,7 @@ impl WorkingPatchSet {
         let mut parts = [(); SUBTREE_COUNT].map(|()| Self {
             root_version: self.root_version,
             changes_by_nibble_count: vec![HashMap::new(); self.changes_by_nibble_count.len()],
+            track_content_hashes: self.track_content_hashes,
         });
 
         let levels = self.changes_by_nibble_count.into_iter().enumerate();
@@ -465,10 +494,13 @@ impl WorkingPatchSet {
                         stale_keys.push(nibbles.with_version(prev_version));
                     }
                 }
-                if was_replaced || matches!(operation, Operation::Update) {
-                    // All nodes in the patch set are updated for the update operation, regardless
+                if was_replaced {
+                    patched_nodes.insert(nibbles.with_version(node_version), node.inner);
+                } else if matches!(operation, Operation::Update) && !node.is_unchanged() {
+                    // All changed nodes in the patch set are updated for the update operation, regardless
                     // of the version change. For insert operations, we only should update nodes
-                    // with the changed version.
+                    // with the changed version. Unchanged nodes loaded from the DB don't need
+                    // to be written since they are already persisted under the same key.
                     patched_nodes.insert(nibbles.with_version(node_version), node.inner);
                 }
             }
@@ -125,6 +125,26 @@ impl RocksDBWrapper {
         Ok(Self::from(RocksDB::new(path)?))
     }
 
+    /// Returns sorted keys of custom tags in the tree manifest that are not present in `known_custom_keys`.
+    /// Such tags do not prevent using the tree (e.g., they may be written by a newer node version),
+    /// but may be useful to log for diagnostics.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the manifest cannot be deserialized.
+    pub fn unknown_custom_tags(
+        &self,
+        known_custom_keys: &HashSet<&str>,
+    ) -> Result<Vec<String>, DeserializeError> {
+        let Some(raw_manifest) = self.raw_node(Self::MANIFEST_KEY) else {
+            return Ok(vec![]);
+        };
+        let (_, unknown_custom_keys) =
+            Manifest::deserialize_with_known_custom_tags(&raw_manifest, Some(known_custom_keys))
+                .map_err(|err| err.with_context(ErrorContext::Manifest))?;
+        Ok(unknown_custom_keys)
+    }
+
     /// Sets the chunk size for multi-get operations. The requested keys will be split
     /// into chunks of this size and requested in parallel using `rayon`. Setting chunk size
     /// to a large value (e.g., `usize::MAX`) will effectively disable parallelism.
@@ -142,7 +162,7 @@ impl RocksDBWrapper {
         self.multi_get_chunk_size = chunk_size;
     }
 
-    fn raw_node(&self, key: &[u8]) -> Option<Vec<u8>> {
+    pub(super) fn raw_node(&self, key: &[u8]) -> Option<Vec<u8>> {
         self.db
             .get_cf(MerkleTreeColumnFamily::Tree, key)
             .expect("Failed reading from RocksDB")
@@ -182,7 +202,10 @@ impl RocksDBWrapper {
         let node = if is_leaf {
             LeafNode::deserialize(raw_node, false).map(Node::Leaf)
         } else {
-            InternalNode::deserialize(raw_node, false).map(Node::Internal)
+            InternalNode::deserialize(raw_node, false).and_then(|node| {
+                node.verify_child_versions(key.version)?;
+                Ok(Node::Internal(node))
+            })
         };
         node.map_err(|err| {
             err.with_context(if is_leaf {
@@ -193,6 +216,14 @@ impl RocksDBWrapper {
         })
     }
 
+    /// Returns keys of all stored tree nodes, including roots, in the DB order.
+    pub(super) fn node_keys(&self) -> impl Iterator<Item = NodeKey> + '_ {
+        self.db
+            .prefix_iterator_cf(MerkleTreeColumnFamily::Tree, &[])
+            .filter(|(raw_key, _)| raw_key.len() > 8) // filter out the manifest and other non-node keys
+            .map(|(raw_key, _)| NodeKey::from_db_key(&raw_key))
+    }
+
     pub(crate) fn all_keys_for_version(
         &self,
         version: u64,
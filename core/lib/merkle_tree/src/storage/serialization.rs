@@ -1,9 +1,12 @@
 //! Serialization of node types in the database.
 
-use std::{collections::HashMap, str};
+use std::{
+    collections::{HashMap, HashSet},
+    io, str,
+};
 
 use crate::{
-    errors::{DeserializeError, DeserializeErrorKind, ErrorContext},
+    errors::{DeserializeError, DeserializeErrorKind, ErrorContext, SerializeError},
     repair::StaleKeysRepairData,
     types::{
         ChildRef, InternalNode, Key, LeafNode, Manifest, Node, RawNode, Root, TreeTags, ValueHash,
@@ -11,9 +14,74 @@ use crate::{
     },
 };
 
-/// Estimate for the byte size of LEB128-encoded `u64` values. 3 bytes fits values
-/// up to `2 ** (3 * 7) = 2_097_152` (exclusive).
-const LEB128_SIZE_ESTIMATE: usize = 3;
+/// Returns the byte size of the LEB128 encoding of `value`.
+fn leb128_len(value: u64) -> usize {
+    let significant_bits = (u64::BITS - value.leading_zeros()) as usize;
+    significant_bits.div_ceil(7).max(1)
+}
+
+/// Serializes into a fixed-size slice, checking its size beforehand so that no partial output is produced.
+fn serialize_into_slice(
+    serialized_len: usize,
+    buffer: &mut [u8],
+    write: impl FnOnce(&mut &mut [u8]) -> io::Result<()>,
+) -> Result<usize, SerializeError> {
+    if buffer.len() < serialized_len {
+        return Err(SerializeError::InsufficientSpace {
+            required: serialized_len,
+            available: buffer.len(),
+        });
+    }
+    write(&mut &mut buffer[..serialized_len])?;
+    Ok(serialized_len)
+}
+
+/// Encoder of tree nodes into raw bytes persisted by a [`Database`](crate::Database).
+///
+/// Encoders append bytes to a caller-provided buffer, so that a single buffer can be reused
+/// across nodes. Storage backends may implement this trait to add custom framing
+/// (e.g., a length prefix or an envelope) on top of [`DefaultEncoder`].
+pub trait NodeEncoder {
+    /// Encodes a leaf node.
+    fn encode_leaf(&self, leaf: &LeafNode, buffer: &mut Vec<u8>);
+    /// Encodes an internal node.
+    fn encode_internal(&self, node: &InternalNode, buffer: &mut Vec<u8>);
+    /// Encodes a tree root.
+    fn encode_root(&self, root: &Root, buffer: &mut Vec<u8>);
+    /// Encodes a tree manifest.
+    fn encode_manifest(&self, manifest: &Manifest, buffer: &mut Vec<u8>);
+}
+
+/// Default [`NodeEncoder`] producing the format used by [`RocksDBWrapper`](crate::RocksDBWrapper).
+#[derive(Debug, Clone, Copy, Default)]
+pub struct DefaultEncoder;
+
+impl NodeEncoder for DefaultEncoder {
+    // `unwrap()`s below are safe; writing to a `Vec<u8>` always succeeds
+
+    fn encode_leaf(&self, leaf: &LeafNode, buffer: &mut Vec<u8>) {
+        buffer.reserve(leaf.serialized_len());
+        leaf.write(buffer).unwrap();
+    }
+
+    fn encode_internal(&self, node: &InternalNode, buffer: &mut Vec<u8>) {
+        buffer.reserve(node.serialized_len());
+        node.write(buffer).unwrap();
+    }
+
+    fn encode_root(&self, root: &Root, buffer: &mut Vec<u8>) {
+        buffer.reserve(root.serialized_len());
+        root.write(buffer).unwrap();
+    }
+
+    fn encode_manifest(&self, manifest: &Manifest, buffer: &mut Vec<u8>) {
+        buffer.reserve(manifest.serialized_len());
+        leb128::write::unsigned(buffer, manifest.version_count).unwrap();
+        if let Some(tags) = &manifest.tags {
+            tags.serialize(buffer);
+        }
+    }
+}
 
 impl LeafNode {
     pub(super) fn deserialize(bytes: &[u8], strict: bool) -> Result<Self, DeserializeError> {
@@ -39,12 +107,39 @@ impl LeafNode {
     }
 
     pub(super) fn serialize(&self, buffer: &mut Vec<u8>) {
-        buffer.reserve(KEY_SIZE + HASH_SIZE + LEB128_SIZE_ESTIMATE);
+        DefaultEncoder.encode_leaf(self, buffer);
+    }
+
+    fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
         let mut key_bytes = [0_u8; KEY_SIZE];
         self.full_key.to_big_endian(&mut key_bytes);
-        buffer.extend_from_slice(&key_bytes);
-        buffer.extend_from_slice(self.value_hash.as_ref());
-        leb128::write::unsigned(buffer, self.leaf_index).unwrap();
+        writer.write_all(&key_bytes)?;
+        writer.write_all(self.value_hash.as_bytes())?;
+        leb128::write::unsigned(writer, self.leaf_index)?;
+        Ok(())
+    }
+
+    /// Serializes this leaf into the provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Propagates I/O errors returned by `writer`.
+    pub fn serialize_to_writer(&self, writer: &mut impl io::Write) -> Result<(), SerializeError> {
+        self.write(writer).map_err(Into::into)
+    }
+
+    /// Serializes this leaf into the start of `buffer`, returning the number of written bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buffer` is shorter than [`Self::serialized_len()`]; `buffer` is not modified in this case.
+    pub fn serialize_into(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        serialize_into_slice(self.serialized_len(), buffer, |writer| self.write(writer))
+    }
+
+    /// Returns the exact byte size of this leaf once serialized, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        KEY_SIZE + HASH_SIZE + leb128_len(self.leaf_index)
     }
 }
 
@@ -70,9 +165,6 @@ impl ChildKind {
 }
 
 impl ChildRef {
-    /// Estimated capacity to serialize a `ChildRef`.
-    const ESTIMATED_CAPACITY: usize = LEB128_SIZE_ESTIMATE + HASH_SIZE;
-
     fn deserialize(buffer: &mut &[u8], is_leaf: bool) -> Result<Self, DeserializeError> {
         if buffer.len() < HASH_SIZE {
             let err = DeserializeErrorKind::UnexpectedEof;
@@ -92,12 +184,15 @@ impl ChildRef {
         })
     }
 
-    fn serialize(&self, buffer: &mut Vec<u8>) {
-        buffer.extend_from_slice(self.hash.as_bytes());
-        leb128::write::unsigned(buffer, self.version).unwrap();
-        // ^ `unwrap()` is safe; writing to a `Vec<u8>` always succeeds
+    fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        writer.write_all(self.hash.as_bytes())?;
+        leb128::write::unsigned(writer, self.version)?;
+        // `self.is_leaf` is not serialized here, but rather in `InternalNode::write()`
+        Ok(())
+    }
 
-        // `self.is_leaf` is not serialized here, but rather in `InternalNode::serialize()`
+    fn serialized_len(&self) -> usize {
+        HASH_SIZE + leb128_len(self.version)
     }
 
     fn kind(&self) -> ChildKind {
@@ -111,6 +206,18 @@ impl ChildRef {
 
 impl InternalNode {
     pub(super) fn deserialize(bytes: &[u8], strict: bool) -> Result<Self, DeserializeError> {
+        let mut this = Self::default();
+        this.deserialize_into(bytes, strict)?;
+        Ok(this)
+    }
+
+    /// Deserializes a node into this node, replacing all its children. Unlike [`Self::deserialize()`],
+    /// this reuses the allocated child storage. On error, this node is left in a valid, but unspecified state.
+    pub(crate) fn deserialize_into(
+        &mut self,
+        bytes: &[u8],
+        strict: bool,
+    ) -> Result<(), DeserializeError> {
         if bytes.len() < 4 {
             let err = DeserializeErrorKind::UnexpectedEof;
             return Err(err.with_context(ErrorContext::ChildrenMask));
@@ -124,17 +231,17 @@ impl InternalNode {
         // This works because both non-empty `ChildKind`s have exactly one bit set
         // in their binary representation.
         let child_count = bitmap.count_ones();
-        let mut this = Self::with_capacity(child_count as usize);
+        self.clear_with_capacity(child_count as usize);
         for i in 0..Self::CHILD_COUNT {
             match ChildKind::deserialize(bitmap & ChildKind::MASK)? {
                 ChildKind::None => { /* skip */ }
                 ChildKind::Internal => {
                     let child_ref = ChildRef::deserialize(&mut bytes, false)?;
-                    this.insert_child_ref(i, child_ref);
+                    self.insert_deserialized_child(i, child_ref)?;
                 }
                 ChildKind::Leaf => {
                     let child_ref = ChildRef::deserialize(&mut bytes, true)?;
-                    this.insert_child_ref(i, child_ref);
+                    self.insert_deserialized_child(i, child_ref)?;
                 }
             }
             bitmap >>= 2;
@@ -142,30 +249,91 @@ impl InternalNode {
         if strict && !bytes.is_empty() {
             return Err(DeserializeErrorKind::Leftovers.into());
         }
-        Ok(this)
+        Ok(())
+    }
+
+    /// Inserts a deserialized child reference, checking that the nibble isn't occupied. With the current format,
+    /// each nibble is read at most once, but the check guards against silent overwrites if the format evolves.
+    fn insert_deserialized_child(
+        &mut self,
+        nibble: u8,
+        child_ref: ChildRef,
+    ) -> Result<(), DeserializeError> {
+        if self.child_ref(nibble).is_some() {
+            return Err(DeserializeErrorKind::DuplicateChild(nibble).into());
+        }
+        self.insert_child_ref(nibble, child_ref);
+        Ok(())
+    }
+
+    /// Checks that no child reference has a version greater than `node_version` (the version of this node).
+    /// Such child references cannot be produced by tree updates and signal data corruption.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error for the first child reference with a version greater than `node_version`.
+    pub fn verify_child_versions(&self, node_version: u64) -> Result<(), DeserializeError> {
+        for (nibble, child_ref) in self.children_sorted() {
+            if child_ref.version > node_version {
+                let err = DeserializeErrorKind::FutureChildVersion {
+                    nibble,
+                    child_version: child_ref.version,
+                    node_version,
+                };
+                return Err(err.into());
+            }
+        }
+        Ok(())
     }
 
     pub(super) fn serialize(&self, buffer: &mut Vec<u8>) {
+        DefaultEncoder.encode_internal(self, buffer);
+    }
+
+    fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
         // Creates a bitmap specifying children existence and type (internal node or leaf).
         // Each child occupies 2 bits in the bitmap (i.e., the entire bitmap is 32 bits),
         // with ordering from least significant bits to most significant ones.
         // `0b00` means no child, while bitmap chunks for existing children are determined by
         // `ChildKind`.
         let mut bitmap = 0_u32;
-        let mut child_count = 0;
         for (i, child_ref) in self.children() {
             let offset = 2 * u32::from(i);
             bitmap |= (child_ref.kind() as u32) << offset;
-            child_count += 1;
         }
-
-        let additional_capacity = 4 + ChildRef::ESTIMATED_CAPACITY * child_count;
-        buffer.reserve(additional_capacity);
-        buffer.extend_from_slice(&bitmap.to_le_bytes());
+        writer.write_all(&bitmap.to_le_bytes())?;
 
         for child_ref in self.child_refs() {
-            child_ref.serialize(buffer);
+            child_ref.write(writer)?;
         }
+        Ok(())
+    }
+
+    /// Serializes this node into the provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Propagates I/O errors returned by `writer`.
+    pub fn serialize_to_writer(&self, writer: &mut impl io::Write) -> Result<(), SerializeError> {
+        self.write(writer).map_err(Into::into)
+    }
+
+    /// Serializes this node into the start of `buffer`, returning the number of written bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buffer` is shorter than [`Self::serialized_len()`]; `buffer` is not modified in this case.
+    pub fn serialize_into(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        serialize_into_slice(self.serialized_len(), buffer, |writer| self.write(writer))
+    }
+
+    /// Returns the exact byte size of this node once serialized, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        // 4 bytes for the children bitmap
+        4 + self
+            .child_refs()
+            .map(ChildRef::serialized_len)
+            .sum::<usize>()
     }
 }
 
@@ -218,13 +386,46 @@ impl Root {
     }
 
     pub(super) fn serialize(&self, buffer: &mut Vec<u8>) {
+        DefaultEncoder.encode_root(self, buffer);
+    }
+
+    fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
         match self {
             Self::Empty => {
-                leb128::write::unsigned(buffer, 0 /* leaf_count */).unwrap();
+                leb128::write::unsigned(writer, 0 /* leaf_count */)?;
             }
             Self::Filled { leaf_count, node } => {
-                leb128::write::unsigned(buffer, (*leaf_count).into()).unwrap();
-                node.serialize(buffer);
+                leb128::write::unsigned(writer, (*leaf_count).into())?;
+                node.write(writer)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Serializes this root into the provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Propagates I/O errors returned by `writer`.
+    pub fn serialize_to_writer(&self, writer: &mut impl io::Write) -> Result<(), SerializeError> {
+        self.write(writer).map_err(Into::into)
+    }
+
+    /// Serializes this root into the start of `buffer`, returning the number of written bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buffer` is shorter than [`Self::serialized_len()`]; `buffer` is not modified in this case.
+    pub fn serialize_into(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        serialize_into_slice(self.serialized_len(), buffer, |writer| self.write(writer))
+    }
+
+    /// Returns the exact byte size of this root once serialized, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        match self {
+            Self::Empty => leb128_len(0),
+            Self::Filled { leaf_count, node } => {
+                leb128_len(leaf_count.get()) + node.serialized_len()
             }
         }
     }
@@ -237,19 +438,59 @@ impl Node {
             Self::Leaf(leaf) => leaf.serialize(buffer),
         }
     }
+
+    fn write(&self, writer: &mut impl io::Write) -> io::Result<()> {
+        match self {
+            Self::Internal(node) => node.write(writer),
+            Self::Leaf(leaf) => leaf.write(writer),
+        }
+    }
+
+    /// Serializes this node into the provided writer.
+    ///
+    /// # Errors
+    ///
+    /// Propagates I/O errors returned by `writer`.
+    pub fn serialize_to_writer(&self, writer: &mut impl io::Write) -> Result<(), SerializeError> {
+        self.write(writer).map_err(Into::into)
+    }
+
+    /// Serializes this node into the start of `buffer`, returning the number of written bytes.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `buffer` is shorter than [`Self::serialized_len()`]; `buffer` is not modified in this case.
+    pub fn serialize_into(&self, buffer: &mut [u8]) -> Result<usize, SerializeError> {
+        serialize_into_slice(self.serialized_len(), buffer, |writer| self.write(writer))
+    }
+
+    /// Returns the exact byte size of this node once serialized, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        match self {
+            Self::Internal(node) => node.serialized_len(),
+            Self::Leaf(leaf) => leaf.serialized_len(),
+        }
+    }
 }
 
 impl TreeTags {
     /// Tags are serialized as a length-prefixed list of `(&str, &str)` tuples, where each
     /// `&str` is length-prefixed as well. All lengths are encoded using LEB128.
     /// Custom tag keys are prefixed with `custom.` to ensure they don't intersect with standard tags.
-    fn deserialize(bytes: &mut &[u8]) -> Result<Self, DeserializeError> {
+    ///
+    /// If `known_custom_keys` are provided, custom tags with other keys are still accepted, but their keys
+    /// are returned (sorted) alongside the tags.
+    fn deserialize(
+        bytes: &mut &[u8],
+        known_custom_keys: Option<&HashSet<&str>>,
+    ) -> Result<(Self, Vec<String>), DeserializeError> {
         let tag_count = leb128::read::unsigned(bytes).map_err(DeserializeErrorKind::Leb128)?;
         let mut architecture = None;
         let mut hasher = None;
         let mut depth = None;
         let mut is_recovering = false;
         let mut custom = HashMap::new();
+        let mut unknown_custom_keys = vec![];
 
         for _ in 0..tag_count {
             let key = Self::deserialize_str(bytes)?;
@@ -277,6 +518,9 @@ impl TreeTags {
                 }
                 key => {
                     if let Some(custom_key) = key.strip_prefix("custom.") {
+                        if known_custom_keys.is_some_and(|known| !known.contains(custom_key)) {
+                            unknown_custom_keys.push(custom_key.to_owned());
+                        }
                         custom.insert(custom_key.to_owned(), value.to_owned());
                     } else {
                         return Err(DeserializeErrorKind::UnknownTag(key.to_owned()).into());
@@ -284,13 +528,15 @@ impl TreeTags {
                 }
             }
         }
-        Ok(Self {
+        let tags = Self {
             architecture: architecture.ok_or(DeserializeErrorKind::MissingTag("architecture"))?,
             hasher: hasher.ok_or(DeserializeErrorKind::MissingTag("hasher"))?,
             depth: depth.ok_or(DeserializeErrorKind::MissingTag("depth"))?,
             is_recovering,
             custom,
-        })
+        };
+        unknown_custom_keys.sort_unstable();
+        Ok((tags, unknown_custom_keys))
     }
 
     fn deserialize_str<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, DeserializeErrorKind> {
@@ -310,9 +556,16 @@ impl TreeTags {
         bytes.extend_from_slice(s.as_bytes());
     }
 
+    fn serialized_str_len(len: usize) -> usize {
+        leb128_len(len as u64) + len
+    }
+
+    fn entry_count(&self) -> u64 {
+        3 + u64::from(self.is_recovering) + self.custom.len() as u64
+    }
+
     fn serialize(&self, buffer: &mut Vec<u8>) {
-        let entry_count = 3 + u64::from(self.is_recovering) + self.custom.len() as u64;
-        leb128::write::unsigned(buffer, entry_count).unwrap();
+        leb128::write::unsigned(buffer, self.entry_count()).unwrap();
 
         Self::serialize_str(buffer, "architecture");
         Self::serialize_str(buffer, &self.architecture);
@@ -330,29 +583,62 @@ impl TreeTags {
             Self::serialize_str(buffer, value);
         }
     }
+
+    fn serialized_len(&self) -> usize {
+        let mut len = leb128_len(self.entry_count())
+            + Self::serialized_str_len("architecture".len())
+            + Self::serialized_str_len(self.architecture.len())
+            + Self::serialized_str_len("depth".len())
+            + Self::serialized_str_len(self.depth.to_string().len())
+            + Self::serialized_str_len("hasher".len())
+            + Self::serialized_str_len(self.hasher.len());
+        if self.is_recovering {
+            len += Self::serialized_str_len("is_recovering".len())
+                + Self::serialized_str_len("true".len());
+        }
+        for (custom_key, value) in &self.custom {
+            len += Self::serialized_str_len("custom.".len() + custom_key.len())
+                + Self::serialized_str_len(value.len());
+        }
+        len
+    }
 }
 
 impl Manifest {
-    pub(super) fn deserialize(mut bytes: &[u8]) -> Result<Self, DeserializeError> {
+    pub(super) fn deserialize(bytes: &[u8]) -> Result<Self, DeserializeError> {
+        Self::deserialize_with_known_custom_tags(bytes, None).map(|(manifest, _)| manifest)
+    }
+
+    /// Deserializes a manifest, additionally returning keys of custom tags not present in `known_custom_keys`.
+    /// Unknown custom tags are not an error; e.g., they may be written by a newer node version.
+    /// If `known_custom_keys` is `None`, custom tags are not checked, and the returned list is always empty.
+    pub(super) fn deserialize_with_known_custom_tags(
+        mut bytes: &[u8],
+        known_custom_keys: Option<&HashSet<&str>>,
+    ) -> Result<(Self, Vec<String>), DeserializeError> {
         let version_count =
             leb128::read::unsigned(&mut bytes).map_err(DeserializeErrorKind::Leb128)?;
-        let tags = if bytes.is_empty() {
-            None
+        let (tags, unknown_custom_keys) = if bytes.is_empty() {
+            (None, vec![])
         } else {
-            Some(TreeTags::deserialize(&mut bytes)?)
+            let (tags, unknown_custom_keys) = TreeTags::deserialize(&mut bytes, known_custom_keys)?;
+            (Some(tags), unknown_custom_keys)
         };
 
-        Ok(Self {
+        let manifest = Self {
             version_count,
             tags,
-        })
+        };
+        Ok((manifest, unknown_custom_keys))
     }
 
     pub(super) fn serialize(&self, buffer: &mut Vec<u8>) {
-        leb128::write::unsigned(buffer, self.version_count).unwrap();
-        if let Some(tags) = &self.tags {
-            tags.serialize(buffer);
-        }
+        DefaultEncoder.encode_manifest(self, buffer);
+    }
+
+    /// Returns the exact byte size of this manifest once serialized, without serializing it.
+    pub fn serialized_len(&self) -> usize {
+        leb128_len(self.version_count) + self.tags.as_ref().map_or(0, TreeTags::serialized_len)
     }
 }
 
@@ -370,14 +656,22 @@ impl StaleKeysRepairData {
 
 #[cfg(test)]
 mod tests {
-    use zksync_types::H256;
+    use assert_matches::assert_matches;
+    use zksync_types::{ProtocolVersionId, H256};
 
     use super::*;
     use crate::types::TreeEntry;
 
     #[test]
     fn serializing_manifest() {
-        let manifest = Manifest::new(42, &());
+        let mut manifest = Manifest::new(42, &());
+        // The protocol version tag is tested separately.
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .remove(TreeTags::PROTOCOL_VERSION_TAG);
         let mut buffer = vec![];
         manifest.serialize(&mut buffer);
         assert_eq!(buffer[0], 42); // version count
@@ -395,6 +689,12 @@ mod tests {
     #[test]
     fn serializing_manifest_with_recovery_flag() {
         let mut manifest = Manifest::new(42, &());
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .remove(TreeTags::PROTOCOL_VERSION_TAG);
         manifest.tags.as_mut().unwrap().is_recovering = true;
         let mut buffer = vec![];
         manifest.serialize(&mut buffer);
@@ -444,6 +744,146 @@ mod tests {
         assert_eq!(manifest_copy, manifest);
     }
 
+    #[test]
+    fn reporting_unknown_custom_tags() {
+        let mut manifest = Manifest::new(42, &());
+        manifest.tags.as_mut().unwrap().custom = HashMap::from([
+            (TreeTags::HASH_LEN_TAG.to_owned(), "32".to_owned()),
+            ("from.newer.node".to_owned(), "1".to_owned()),
+            ("another".to_owned(), "true".to_owned()),
+        ]);
+        let mut buffer = vec![];
+        manifest.serialize(&mut buffer);
+
+        let known_custom_keys = HashSet::from([TreeTags::HASH_LEN_TAG]);
+        let (manifest_copy, unknown_keys) =
+            Manifest::deserialize_with_known_custom_tags(&buffer, Some(&known_custom_keys))
+                .unwrap();
+        assert_eq!(manifest_copy, manifest);
+        assert_eq!(unknown_keys, ["another", "from.newer.node"]);
+
+        let (manifest_copy, unknown_keys) =
+            Manifest::deserialize_with_known_custom_tags(&buffer, None).unwrap();
+        assert_eq!(manifest_copy, manifest);
+        assert!(unknown_keys.is_empty());
+
+        let (_, unknown_keys) =
+            Manifest::deserialize_with_known_custom_tags(&[42], Some(&known_custom_keys)).unwrap();
+        assert!(unknown_keys.is_empty());
+    }
+
+    #[test]
+    fn removing_custom_tags() {
+        let mut manifest = Manifest::new(42, &());
+        manifest.tags.as_mut().unwrap().custom = HashMap::from([
+            ("test".to_owned(), "1".to_owned()),
+            ("obsolete".to_owned(), "true".to_owned()),
+        ]);
+        let mut custom_tags: Vec<_> = manifest.custom_tags().collect();
+        custom_tags.sort_unstable();
+        assert_eq!(custom_tags, [("obsolete", "true"), ("test", "1")]);
+
+        assert!(manifest.remove_custom_tag("obsolete"));
+        assert!(!manifest.remove_custom_tag("obsolete"));
+        assert!(!manifest.remove_custom_tag("missing"));
+        // Standard tags cannot be removed.
+        assert!(!manifest.remove_custom_tag("hasher"));
+        assert!(!manifest.remove_custom_tag("architecture"));
+        assert_eq!(manifest.custom_tags().collect::<Vec<_>>(), [("test", "1")]);
+
+        let mut buffer = vec![];
+        manifest.serialize(&mut buffer);
+        let manifest_copy = Manifest::deserialize(&buffer).unwrap();
+        assert_eq!(manifest_copy, manifest);
+        let tags = manifest_copy.tags.unwrap();
+        assert_eq!(tags.hasher, "no_op256");
+        assert_eq!(tags.architecture, TreeTags::ARCHITECTURE);
+        assert_eq!(
+            tags.custom,
+            HashMap::from([("test".to_owned(), "1".to_owned())])
+        );
+
+        let mut manifest_without_tags = Manifest::default();
+        assert_eq!(manifest_without_tags.custom_tags().count(), 0);
+        assert!(!manifest_without_tags.remove_custom_tag("test"));
+    }
+
+    #[test]
+    fn serializing_manifest_with_protocol_version() {
+        let mut manifest = Manifest::new(42, &());
+        assert_eq!(
+            manifest.protocol_version().unwrap(),
+            Some(ProtocolVersionId::latest() as u16)
+        );
+        manifest.tags.as_mut().unwrap().custom =
+            HashMap::from([(TreeTags::PROTOCOL_VERSION_TAG.to_owned(), "28".to_owned())]);
+        let mut buffer = vec![];
+        manifest.serialize(&mut buffer);
+        assert_eq!(buffer[0], 42); // version count
+        assert_eq!(buffer[1], 4); // number of tags (3 standard + 1 custom)
+        assert_eq!(
+            buffer[2..],
+            *b"\x0Carchitecture\x06AR16MT\x05depth\x03256\x06hasher\x08no_op256\x17custom.protocol_version\x0228"
+        );
+
+        let manifest_copy = Manifest::deserialize(&buffer).unwrap();
+        assert_eq!(manifest_copy, manifest);
+        assert_eq!(manifest_copy.protocol_version().unwrap(), Some(28));
+    }
+
+    #[test]
+    fn manifest_with_protocol_version_is_readable_by_old_nodes() {
+        let manifest = Manifest::new(42, &());
+        let mut buffer = vec![];
+        manifest.serialize(&mut buffer);
+
+        // Nodes unaware of the protocol version tag reject unknown standard tags, but accept unknown custom ones.
+        let known_custom_keys = HashSet::from([TreeTags::HASH_LEN_TAG]);
+        let (manifest_copy, unknown_custom_keys) =
+            Manifest::deserialize_with_known_custom_tags(&buffer, Some(&known_custom_keys))
+                .unwrap();
+        assert_eq!(manifest_copy, manifest);
+        assert_eq!(unknown_custom_keys, [TreeTags::PROTOCOL_VERSION_TAG]);
+        manifest_copy
+            .tags
+            .unwrap()
+            .ensure_consistency(&(), false)
+            .unwrap();
+    }
+
+    #[test]
+    fn deserializing_manifest_without_protocol_version() {
+        let mut manifest = Manifest::new(42, &());
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .remove(TreeTags::PROTOCOL_VERSION_TAG);
+        let mut buffer = vec![];
+        manifest.serialize(&mut buffer);
+        assert_eq!(buffer[1], 3); // number of tags
+
+        let manifest_copy = Manifest::deserialize(&buffer).unwrap();
+        assert_eq!(manifest_copy, manifest);
+        assert_eq!(manifest_copy.protocol_version().unwrap(), None);
+    }
+
+    #[test]
+    fn malformed_protocol_version_tag() {
+        let mut buffer = vec![42, 4];
+        buffer.extend_from_slice(
+            b"\x0Carchitecture\x06AR16MT\x05depth\x03256\x06hasher\x08no_op256\x17custom.protocol_version\x02v1",
+        );
+        // The tag is only parsed on access, so the manifest itself is still readable.
+        let manifest = Manifest::deserialize(&buffer).unwrap();
+        let err = manifest.protocol_version().unwrap_err().to_string();
+        assert!(
+            err.contains("malformed custom tag `protocol_version`"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn manifest_serialization_errors() {
         let manifest = Manifest::new(42, &());
@@ -523,6 +963,84 @@ mod tests {
         assert_eq!(node_copy, node);
     }
 
+    #[test]
+    fn deserializing_internal_node_into_existing_node() {
+        let node = create_internal_node();
+        let mut buffer = vec![];
+        node.serialize(&mut buffer);
+
+        let mut target = InternalNode::default();
+        for nibble in [0, 1, 5, 0xb, 0xf] {
+            target.insert_child_ref(nibble, ChildRef::leaf(7));
+        }
+        target.child_ref_mut(5).unwrap().hash = H256([5; 32]);
+        target.deserialize_into(&buffer, true).unwrap();
+        assert_eq!(target, node);
+        assert_eq!(target.child_count(), 2);
+        assert!(target.child_ref(5).is_none());
+
+        // Deserializing into the node again must not accumulate children.
+        target.deserialize_into(&buffer, true).unwrap();
+        assert_eq!(target, node);
+
+        let mut other_buffer = vec![];
+        let mut other_node = InternalNode::default();
+        other_node.insert_child_ref(3, ChildRef::internal(1));
+        other_node.serialize(&mut other_buffer);
+        target.deserialize_into(&other_buffer, true).unwrap();
+        assert_eq!(target, other_node);
+    }
+
+    #[test]
+    fn duplicate_child_is_rejected_on_deserialization() {
+        let mut node = create_internal_node();
+        node.insert_deserialized_child(5, ChildRef::leaf(1))
+            .unwrap();
+        assert_eq!(node.child_count(), 3);
+
+        let err = node
+            .insert_deserialized_child(0xb, ChildRef::internal(1))
+            .unwrap_err();
+        assert!(
+            err.to_string().contains("duplicate child at nibble b"),
+            "{err}"
+        );
+        // The existing child must not be overwritten.
+        assert_eq!(*node.child_ref(0xb).unwrap(), {
+            let mut child_ref = ChildRef::leaf(2);
+            child_ref.hash = H256([11; 32]);
+            child_ref
+        });
+    }
+
+    #[test]
+    fn enumerating_children_in_nibble_order() {
+        let node = create_internal_node();
+        let nibbles: Vec<_> = node.children_sorted().map(|(nibble, _)| nibble).collect();
+        assert_eq!(nibbles, [1, 0xb]);
+
+        let mut node = InternalNode::default();
+        for nibble in [0xf, 3, 0, 7] {
+            node.insert_child_ref(nibble, ChildRef::leaf(1));
+        }
+        let nibbles: Vec<_> = node.children_sorted().map(|(nibble, _)| nibble).collect();
+        assert_eq!(nibbles, [0, 3, 7, 0xf]);
+    }
+
+    #[test]
+    fn verifying_child_versions() {
+        let node = create_internal_node(); // has children with versions 3 and 2
+        node.verify_child_versions(3).unwrap();
+        node.verify_child_versions(10).unwrap();
+
+        let err = node.verify_child_versions(2).unwrap_err();
+        let err = err.to_string();
+        assert!(
+            err.contains("child at nibble 1 has version 3, which is greater than the internal node version 2"),
+            "{err}"
+        );
+    }
+
     #[test]
     fn serializing_empty_root() {
         let root = Root::Empty;
@@ -557,4 +1075,298 @@ mod tests {
         let root_copy = Root::deserialize(&buffer, true).unwrap();
         assert_eq!(root_copy, root);
     }
+
+    #[test]
+    fn leb128_len_is_exact() {
+        for value in [
+            0,
+            1,
+            127,
+            128,
+            16_383,
+            16_384,
+            u64::from(u32::MAX),
+            u64::MAX,
+        ] {
+            let mut buffer = vec![];
+            leb128::write::unsigned(&mut buffer, value).unwrap();
+            assert_eq!(leb128_len(value), buffer.len(), "{value}");
+        }
+    }
+
+    #[test]
+    fn serialized_len_matches_serialization() {
+        let leaves = [
+            LeafNode::new(TreeEntry::new(513.into(), 42, H256([4; 32]))),
+            LeafNode::new(TreeEntry::new(513.into(), 1 << 20, H256([4; 32]))),
+        ];
+        for leaf in leaves {
+            let mut buffer = vec![];
+            leaf.serialize(&mut buffer);
+            assert_eq!(leaf.serialized_len(), buffer.len());
+
+            let node = Node::from(leaf);
+            assert_eq!(node.serialized_len(), buffer.len());
+            let root = Root::new(1, node);
+            let mut buffer = vec![];
+            root.serialize(&mut buffer);
+            assert_eq!(root.serialized_len(), buffer.len());
+        }
+
+        let mut node = create_internal_node();
+        node.insert_child_ref(0xf, ChildRef::internal(u64::from(u32::MAX)));
+        let mut buffer = vec![];
+        node.serialize(&mut buffer);
+        assert_eq!(node.serialized_len(), buffer.len());
+        let root = Root::new(1_000, node.into());
+        let mut buffer = vec![];
+        root.serialize(&mut buffer);
+        assert_eq!(root.serialized_len(), buffer.len());
+
+        let mut buffer = vec![];
+        Root::Empty.serialize(&mut buffer);
+        assert_eq!(Root::Empty.serialized_len(), buffer.len());
+    }
+
+    #[test]
+    fn serializing_into_slice() {
+        let leaf = LeafNode::new(TreeEntry::new(513.into(), 42, H256([4; 32])));
+        let mut expected = vec![];
+        leaf.serialize(&mut expected);
+        let mut buffer = [0xff_u8; 80];
+        assert_eq!(leaf.serialize_into(&mut buffer).unwrap(), expected.len());
+        assert_eq!(buffer[..expected.len()], expected);
+        assert!(buffer[expected.len()..].iter().all(|&byte| byte == 0xff));
+
+        let root = Root::new(2, create_internal_node().into());
+        let mut expected = vec![];
+        root.serialize(&mut expected);
+        let mut buffer = vec![0; expected.len()];
+        assert_eq!(root.serialize_into(&mut buffer).unwrap(), expected.len());
+        assert_eq!(buffer, expected);
+
+        let mut writer = vec![];
+        root.serialize_to_writer(&mut writer).unwrap();
+        assert_eq!(writer, expected);
+    }
+
+    #[test]
+    fn serializing_into_too_small_slice() {
+        let leaf = LeafNode::new(TreeEntry::new(513.into(), 42, H256([4; 32])));
+        let mut buffer = [0_u8; 64];
+        let err = leaf.serialize_into(&mut buffer).unwrap_err();
+        assert_matches!(
+            err,
+            SerializeError::InsufficientSpace {
+                required: 65,
+                available: 64
+            }
+        );
+        assert_eq!(buffer, [0; 64]);
+
+        let node = Node::from(create_internal_node());
+        let mut buffer = [0_u8; 4];
+        let err = node.serialize_into(&mut buffer).unwrap_err();
+        assert_matches!(
+            err,
+            SerializeError::InsufficientSpace {
+                required: 70,
+                available: 4
+            }
+        );
+        assert_eq!(buffer, [0; 4]);
+
+        let mut writer = [0_u8; 16];
+        let err = node.serialize_to_writer(&mut &mut writer[..]).unwrap_err();
+        assert_matches!(err, SerializeError::Io(err) if err.kind() == io::ErrorKind::WriteZero);
+    }
+
+    /// Guards against accidental growth of the persisted format. If an encoding change is intentional,
+    /// the expected sizes should be updated together with the change.
+    #[test]
+    fn serialized_sizes_are_stable() {
+        fn check(name: &str, buffer: &[u8], serialized_len: usize, expected_len: usize) {
+            assert_eq!(buffer.len(), expected_len, "unexpected size for {name}");
+            assert_eq!(
+                serialized_len, expected_len,
+                "unexpected `serialized_len()` for {name}"
+            );
+        }
+
+        fn check_node(name: &str, node: &Node, expected_len: usize) {
+            let mut buffer = vec![];
+            node.serialize(&mut buffer);
+            check(name, &buffer, node.serialized_len(), expected_len);
+        }
+
+        fn check_root(name: &str, root: &Root, expected_len: usize) {
+            let mut buffer = vec![];
+            root.serialize(&mut buffer);
+            check(name, &buffer, root.serialized_len(), expected_len);
+        }
+
+        fn check_manifest(name: &str, manifest: &Manifest, expected_len: usize) {
+            let mut buffer = vec![];
+            manifest.serialize(&mut buffer);
+            check(name, &buffer, manifest.serialized_len(), expected_len);
+        }
+
+        let leaf = LeafNode::new(TreeEntry::new(513.into(), 42, H256([4; 32])));
+        check_node("leaf", &leaf.into(), 65);
+        let leaf_with_large_index =
+            LeafNode::new(TreeEntry::new(513.into(), 1 << 20, H256([4; 32])));
+        check_node("leaf with large index", &leaf_with_large_index.into(), 67);
+
+        let mut narrow_node = InternalNode::default();
+        narrow_node.insert_child_ref(0, ChildRef::leaf(1));
+        check_node("internal node with 1 child", &narrow_node.into(), 37);
+        check_node(
+            "internal node with 2 children",
+            &create_internal_node().into(),
+            70,
+        );
+        let mut full_node = InternalNode::default();
+        for nibble in 0..16 {
+            full_node.insert_child_ref(nibble, ChildRef::internal(3));
+        }
+        check_node(
+            "internal node with 16 children",
+            &full_node.clone().into(),
+            532,
+        );
+        full_node.insert_child_ref(0xf, ChildRef::internal(u64::from(u32::MAX)));
+        check_node(
+            "internal node with 16 children and large version",
+            &full_node.into(),
+            536,
+        );
+
+        check_root("empty root", &Root::Empty, 1);
+        check_root("root with leaf", &Root::new(1, leaf.into()), 66);
+        check_root(
+            "root with internal node",
+            &Root::new(1_000, create_internal_node().into()),
+            72,
+        );
+
+        check_manifest("manifest without tags", &Manifest::default(), 1);
+        let mut manifest = Manifest::new(42, &());
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .remove(TreeTags::PROTOCOL_VERSION_TAG);
+        check_manifest("manifest", &manifest, 48);
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .insert(TreeTags::PROTOCOL_VERSION_TAG.to_owned(), "28".to_owned());
+        check_manifest("manifest with protocol version", &manifest, 75);
+        manifest.tags.as_mut().unwrap().is_recovering = true;
+        check_manifest("manifest with recovery flag", &manifest, 94);
+        manifest.tags.as_mut().unwrap().custom =
+            HashMap::from([("test".to_owned(), "1".to_owned())]);
+        check_manifest("manifest with custom tag", &manifest, 81);
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .insert("other".to_owned(), "value".to_owned());
+        check_manifest("manifest with 2 custom tags", &manifest, 100);
+    }
+
+    /// Checks that `DefaultEncoder` produces exactly the same bytes as the persisted format.
+    #[test]
+    fn default_encoder_is_byte_identical_to_persisted_format() {
+        let leaf = LeafNode::new(TreeEntry::new(513.into(), 42, H256([4; 32])));
+        let mut buffer = vec![];
+        DefaultEncoder.encode_leaf(&leaf, &mut buffer);
+        let mut expected = vec![0; 30];
+        expected.extend_from_slice(&[2, 1]);
+        expected.extend_from_slice(&[4; 32]);
+        expected.push(42);
+        assert_eq!(buffer, expected);
+
+        let node = create_internal_node();
+        let mut buffer = vec![];
+        DefaultEncoder.encode_internal(&node, &mut buffer);
+        let mut expected_node = vec![4, 0, 128, 0];
+        expected_node.extend_from_slice(&[1; 32]);
+        expected_node.push(3);
+        expected_node.extend_from_slice(&[11; 32]);
+        expected_node.push(2);
+        assert_eq!(buffer, expected_node);
+
+        let mut buffer = vec![];
+        DefaultEncoder.encode_root(&Root::Empty, &mut buffer);
+        assert_eq!(buffer, [0]);
+        let mut buffer = vec![];
+        DefaultEncoder.encode_root(&Root::new(2, node.into()), &mut buffer);
+        assert_eq!(buffer[0], 2);
+        assert_eq!(buffer[1..], expected_node);
+
+        let mut manifest = Manifest::new(42, &());
+        manifest
+            .tags
+            .as_mut()
+            .unwrap()
+            .custom
+            .remove(TreeTags::PROTOCOL_VERSION_TAG);
+        let mut buffer = vec![];
+        DefaultEncoder.encode_manifest(&manifest, &mut buffer);
+        let mut expected = vec![42, 3];
+        expected
+            .extend_from_slice(b"\x0Carchitecture\x06AR16MT\x05depth\x03256\x06hasher\x08no_op256");
+        assert_eq!(buffer, expected);
+    }
+
+    /// Encoder prefixing each encoded node with its length.
+    #[derive(Debug)]
+    struct LengthPrefixedEncoder;
+
+    impl LengthPrefixedEncoder {
+        fn encode_with_prefix(buffer: &mut Vec<u8>, encode: impl FnOnce(&mut Vec<u8>)) {
+            let start = buffer.len();
+            encode(buffer);
+            let len = u32::try_from(buffer.len() - start).unwrap();
+            buffer.splice(start..start, len.to_le_bytes());
+        }
+    }
+
+    impl NodeEncoder for LengthPrefixedEncoder {
+        fn encode_leaf(&self, leaf: &LeafNode, buffer: &mut Vec<u8>) {
+            Self::encode_with_prefix(buffer, |buffer| DefaultEncoder.encode_leaf(leaf, buffer));
+        }
+
+        fn encode_internal(&self, node: &InternalNode, buffer: &mut Vec<u8>) {
+            Self::encode_with_prefix(buffer, |buffer| {
+                DefaultEncoder.encode_internal(node, buffer);
+            });
+        }
+
+        fn encode_root(&self, root: &Root, buffer: &mut Vec<u8>) {
+            Self::encode_with_prefix(buffer, |buffer| DefaultEncoder.encode_root(root, buffer));
+        }
+
+        fn encode_manifest(&self, manifest: &Manifest, buffer: &mut Vec<u8>) {
+            Self::encode_with_prefix(buffer, |buffer| {
+                DefaultEncoder.encode_manifest(manifest, buffer);
+            });
+        }
+    }
+
+    #[test]
+    fn custom_encoder_wrapping_default_one() {
+        let node = create_internal_node();
+        let mut buffer = vec![];
+        LengthPrefixedEncoder.encode_internal(&node, &mut buffer);
+        assert_eq!(buffer[..4], 70_u32.to_le_bytes());
+
+        let node_copy = InternalNode::deserialize(&buffer[4..], true).unwrap();
+        assert_eq!(node_copy, node);
+    }
 }
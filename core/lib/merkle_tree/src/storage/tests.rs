@@ -533,6 +533,41 @@ fn recovery_flattens_node_versions() {
     }
 }
 
+#[test]
+fn unchanged_nodes_are_not_rewritten_during_recovery() {
+    let recovery_version = 100;
+    let recovery_entries: Vec<_> = (0_u64..10)
+        .map(|i| TreeEntry {
+            key: Key::from(i) << 252, // the first key nibbles are distinct
+            value: ValueHash::zero(),
+            leaf_index: i + 1,
+        })
+        .collect();
+    let mut db = PatchSet::default();
+    let patch = Storage::new(&db, &(), recovery_version, false)
+        .extend_during_random_recovery(recovery_entries.clone());
+    db.apply_patch(patch).unwrap();
+
+    // Re-apply some of the entries without changes.
+    let patch = Storage::new(&db, &(), recovery_version, false)
+        .extend_during_random_recovery(recovery_entries[..3].to_vec());
+    let patch = &patch.patches_by_version[&recovery_version];
+    assert!(patch.nodes.is_empty(), "{:?}", patch.nodes);
+
+    // Change one of the entries; only the corresponding leaf should be rewritten.
+    let mut changed_entries = recovery_entries[..3].to_vec();
+    changed_entries[1].value = ValueHash::repeat_byte(1);
+    let patch = Storage::new(&db, &(), recovery_version, false)
+        .extend_during_random_recovery(changed_entries);
+    let patch = &patch.patches_by_version[&recovery_version];
+    assert_eq!(patch.nodes.len(), 1, "{:?}", patch.nodes);
+    let expected_key = Nibbles::single(1).with_version(recovery_version);
+    let Node::Leaf(leaf) = &patch.nodes[&expected_key] else {
+        panic!("Unexpected nodes: {:?}", patch.nodes);
+    };
+    assert_eq!(leaf.value_hash, ValueHash::repeat_byte(1));
+}
+
 #[test_casing(7, [256, 4, 5, 20, 69, 127, 128])]
 fn recovery_with_node_hierarchy(chunk_size: usize) {
     let recovery_version = 100;
@@ -0,0 +1,262 @@
+//! Offline integrity check for all tree nodes stored in RocksDB.
+
+use std::collections::HashMap;
+
+use crate::{
+    errors::{DeserializeError, ErrorContext},
+    storage::{Database, RocksDBWrapper},
+    types::{InternalNode, Key, LeafNode, Nibbles, Node, NodeKey, Root},
+};
+
+/// Structural anomaly found by [`verify_tree_nodes()`].
+#[derive(Debug, Clone, PartialEq, thiserror::Error)]
+#[non_exhaustive]
+pub enum NodeAnomaly {
+    /// Node or root cannot be deserialized.
+    #[error("failed deserializing node at {key}: {message}")]
+    Deserialize {
+        /// Key of the node.
+        key: NodeKey,
+        /// Deserialization error message.
+        message: String,
+    },
+    /// Node referenced by its parent is missing.
+    #[error(
+        "missing {node_str} at {key}",
+        node_str = if *is_leaf { "leaf" } else { "internal node" }
+    )]
+    MissingNode {
+        /// Key of the missing node.
+        key: NodeKey,
+        /// Whether the node is referenced as a leaf.
+        is_leaf: bool,
+    },
+    /// Internal node has no children.
+    #[error("internal node at {key} does not have children")]
+    EmptyInternalNode {
+        /// Key of the node.
+        key: NodeKey,
+    },
+    /// Internal node references a child with a greater version than the node itself.
+    #[error("internal node at {key} references child at `{nibble:x}` with future version {child_version}")]
+    FutureChildVersion {
+        /// Key of the parent node.
+        key: NodeKey,
+        /// Nibble of the child reference.
+        nibble: u8,
+        /// Version of the child reference.
+        child_version: u64,
+    },
+    /// Internal node is placed at the terminal tree level, so it cannot have children.
+    #[error("internal node at terminal tree level {key}")]
+    TerminalInternalNode {
+        /// Key of the node.
+        key: NodeKey,
+    },
+    /// Leaf full key doesn't start with the leaf node key.
+    #[error(
+        "leaf at {key} specifies its full key as {full_key}, which doesn't start with the node key"
+    )]
+    FullKeyMismatch {
+        /// Key of the leaf node.
+        key: NodeKey,
+        /// Full key specified in the leaf.
+        full_key: Key,
+    },
+    /// Number of leaves reachable from a tree root differs from the leaf count specified in the root.
+    #[error("root at version {version} specifies that tree has {expected} leaves, but it actually has {actual}")]
+    LeafCountMismatch {
+        /// Version of the root.
+        version: u64,
+        /// Leaf count specified in the root.
+        expected: u64,
+        /// Number of leaves reachable from the root.
+        actual: u64,
+    },
+}
+
+/// Report produced by [`verify_tree_nodes()`].
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    /// Number of tree roots checked. Roots of pruned versions are not present in the database and are not counted.
+    pub checked_roots: usize,
+    /// Number of distinct non-root nodes reachable from the checked roots.
+    pub checked_nodes: usize,
+    /// Number of stored non-root nodes that are not reachable from any root. Such nodes are not necessarily
+    /// a sign of corruption; e.g., they are left after truncating tree versions until the versions are overwritten.
+    pub unreachable_nodes: usize,
+    /// All found anomalies.
+    pub anomalies: Vec<NodeAnomaly>,
+}
+
+impl VerifyReport {
+    /// Checks whether no anomalies were found.
+    pub fn is_clean(&self) -> bool {
+        self.anomalies.is_empty()
+    }
+}
+
+/// Checks all nodes stored in the tree database for structural invariants, collecting all anomalies into a report.
+///
+/// Nodes are checked by traversing the tree from the root of each stored version; each node shared among
+/// versions is checked once. The following invariants are checked:
+///
+/// - All nodes referenced by their parents exist and can be deserialized.
+/// - Internal nodes are non-empty and don't reference children with versions greater than the node version.
+/// - Leaves are placed at the node key prefixing their full key.
+/// - The number of leaves reachable from each root equals the leaf count specified in the root.
+///
+/// Unlike [`MerkleTree::verify_consistency()`](crate::MerkleTree::verify_consistency()), node hashes are not checked,
+/// which makes the check significantly cheaper.
+///
+/// # Errors
+///
+/// Returns an error if the tree manifest cannot be deserialized. Errors deserializing tree nodes
+/// are reported as anomalies.
+pub fn verify_tree_nodes(db: &RocksDBWrapper) -> Result<VerifyReport, DeserializeError> {
+    let Some(manifest) = db.try_manifest()? else {
+        return Ok(VerifyReport::default());
+    };
+
+    let mut verifier = NodeVerifier {
+        db,
+        leaf_counts: HashMap::new(),
+        report: VerifyReport::default(),
+    };
+    for version in 0..manifest.version_count {
+        verifier.check_root(version);
+    }
+
+    let mut report = verifier.report;
+    let leaf_counts = verifier.leaf_counts;
+    report.checked_nodes = leaf_counts.len();
+    report.unreachable_nodes = db
+        .node_keys()
+        .filter(|key| !key.is_empty() && !leaf_counts.contains_key(key))
+        .count();
+    Ok(report)
+}
+
+#[derive(Debug)]
+struct NodeVerifier<'a> {
+    db: &'a RocksDBWrapper,
+    /// Leaf counts for checked nodes, or `None` if a node subtree is broken.
+    leaf_counts: HashMap<NodeKey, Option<u64>>,
+    report: VerifyReport,
+}
+
+impl NodeVerifier<'_> {
+    fn check_root(&mut self, version: u64) {
+        let key = NodeKey::empty(version);
+        let root = match self.db.try_root(version) {
+            Ok(Some(root)) => root,
+            Ok(None) => return, // The version was pruned
+            Err(err) => {
+                self.report.anomalies.push(NodeAnomaly::Deserialize {
+                    key,
+                    message: err.to_string(),
+                });
+                return;
+            }
+        };
+        self.report.checked_roots += 1;
+
+        let Root::Filled { leaf_count, node } = root else {
+            return;
+        };
+        let actual = match &node {
+            Node::Leaf(leaf) => self.check_leaf(key, leaf),
+            Node::Internal(node) => self.check_internal_node(key, node),
+        };
+        if let Some(actual) = actual {
+            let expected = leaf_count.get();
+            if actual != expected {
+                self.report.anomalies.push(NodeAnomaly::LeafCountMismatch {
+                    version,
+                    expected,
+                    actual,
+                });
+            }
+        }
+    }
+
+    fn check_leaf(&mut self, key: NodeKey, leaf: &LeafNode) -> Option<u64> {
+        let full_key_nibbles = Nibbles::new(&leaf.full_key, key.nibbles.nibble_count());
+        if full_key_nibbles != key.nibbles {
+            self.report.anomalies.push(NodeAnomaly::FullKeyMismatch {
+                key,
+                full_key: leaf.full_key,
+            });
+            return None;
+        }
+        Some(1)
+    }
+
+    fn check_internal_node(&mut self, key: NodeKey, node: &InternalNode) -> Option<u64> {
+        if node.child_count() == 0 {
+            self.report
+                .anomalies
+                .push(NodeAnomaly::EmptyInternalNode { key });
+            return None;
+        }
+
+        let mut leaf_count = Some(0_u64);
+        for (nibble, child_ref) in node.children_sorted() {
+            if child_ref.version > key.version {
+                self.report.anomalies.push(NodeAnomaly::FutureChildVersion {
+                    key,
+                    nibble,
+                    child_version: child_ref.version,
+                });
+                leaf_count = None;
+                continue;
+            }
+            let Some(child_nibbles) = key.nibbles.push(nibble) else {
+                self.report
+                    .anomalies
+                    .push(NodeAnomaly::TerminalInternalNode { key });
+                return None;
+            };
+            let child_key = child_nibbles.with_version(child_ref.version);
+            let child_leaf_count = self.check_node(child_key, child_ref.is_leaf);
+            leaf_count = leaf_count.zip(child_leaf_count).map(|(x, y)| x + y);
+        }
+        leaf_count
+    }
+
+    fn check_node(&mut self, key: NodeKey, is_leaf: bool) -> Option<u64> {
+        if let Some(&leaf_count) = self.leaf_counts.get(&key) {
+            return leaf_count;
+        }
+
+        let leaf_count = match self.db.raw_node(&key.to_db_key()) {
+            None => {
+                self.report
+                    .anomalies
+                    .push(NodeAnomaly::MissingNode { key, is_leaf });
+                None
+            }
+            Some(raw_node) if is_leaf => match LeafNode::deserialize(&raw_node, false) {
+                Ok(leaf) => self.check_leaf(key, &leaf),
+                Err(err) => {
+                    self.push_deserialize_error(key, err.with_context(ErrorContext::Leaf(key)))
+                }
+            },
+            Some(raw_node) => match InternalNode::deserialize(&raw_node, false) {
+                Ok(node) => self.check_internal_node(key, &node),
+                Err(err) => self
+                    .push_deserialize_error(key, err.with_context(ErrorContext::InternalNode(key))),
+            },
+        };
+        self.leaf_counts.insert(key, leaf_count);
+        leaf_count
+    }
+
+    fn push_deserialize_error(&mut self, key: NodeKey, err: DeserializeError) -> Option<u64> {
+        self.report.anomalies.push(NodeAnomaly::Deserialize {
+            key,
+            message: err.to_string(),
+        });
+        None
+    }
+}
@@ -2,11 +2,14 @@
 //! some of these types are declared as public and can be even exported using the `unstable` module.
 //! Still, logically these types are private, so adding them to new public APIs etc. is a logical error.
 
-use std::{collections::HashMap, fmt, num::NonZeroU64, str::FromStr};
+use std::{collections::HashMap, error, fmt, num::NonZeroU64, str::FromStr};
 
 use anyhow::Context;
+use zksync_crypto_primitives::hasher::{blake2::Blake2Hasher, Hasher};
+use zksync_types::ProtocolVersionId;
 
 use crate::{
+    errors::{DeserializeError, DeserializeErrorKind},
     hasher::{HashTree, InternalNodeCache},
     types::{Key, TreeEntry, ValueHash},
     utils::SmallMap,
@@ -33,6 +36,13 @@ pub(crate) struct TreeTags {
 
 impl TreeTags {
     pub const ARCHITECTURE: &'static str = "AR16MT";
+    /// Custom tag declaring the byte length of value hashes stored in leaves. If not set, hashes are assumed
+    /// to have [`HASH_SIZE`] length.
+    pub const HASH_LEN_TAG: &'static str = "hash_len";
+    /// Custom tag recording the minor protocol version supported by the node that has created the tree.
+    /// Not set for trees created before this tag was introduced. This is a custom rather than a standard tag
+    /// so that nodes not aware of it can still read the manifest (e.g., after a rollback).
+    pub const PROTOCOL_VERSION_TAG: &'static str = "protocol_version";
 
     pub fn new(hasher: &dyn HashTree) -> Self {
         Self {
@@ -40,7 +50,10 @@ impl TreeTags {
             hasher: hasher.name().to_owned(),
             depth: TREE_DEPTH,
             is_recovering: false,
-            custom: HashMap::new(),
+            custom: HashMap::from([(
+                Self::PROTOCOL_VERSION_TAG.to_owned(),
+                (ProtocolVersionId::latest() as u16).to_string(),
+            )]),
         }
     }
 
@@ -67,6 +80,16 @@ impl TreeTags {
             hasher.name(),
             self.hasher
         );
+        let hash_len = self.hash_len()?;
+        if hash_len != HASH_SIZE {
+            return Err(
+                DeserializeError::from(DeserializeErrorKind::HashLengthMismatch {
+                    expected: HASH_SIZE,
+                    actual: hash_len,
+                })
+                .into(),
+            );
+        }
 
         if expecting_recovery {
             anyhow::ensure!(
@@ -81,6 +104,43 @@ impl TreeTags {
         }
         Ok(())
     }
+
+    /// Returns the byte length of value hashes stored in tree leaves.
+    pub fn hash_len(&self) -> Result<usize, DeserializeError> {
+        let Some(hash_len) = self.custom_u64(Self::HASH_LEN_TAG)? else {
+            return Ok(HASH_SIZE);
+        };
+        // Lengths not fitting into `usize` cannot match the expected length anyway.
+        Ok(usize::try_from(hash_len).unwrap_or(usize::MAX))
+    }
+
+    /// Parses a custom tag as `u64`. Returns `Ok(None)` if the tag is not set.
+    pub fn custom_u64(&self, key: &str) -> Result<Option<u64>, DeserializeError> {
+        self.parse_custom(key)
+    }
+
+    /// Parses a custom tag as `bool`. Returns `Ok(None)` if the tag is not set.
+    pub fn custom_bool(&self, key: &str) -> Result<Option<bool>, DeserializeError> {
+        self.parse_custom(key)
+    }
+
+    fn parse_custom<T>(&self, key: &str) -> Result<Option<T>, DeserializeError>
+    where
+        T: FromStr,
+        T::Err: error::Error + Send + Sync + 'static,
+    {
+        let Some(value) = self.custom.get(key) else {
+            return Ok(None);
+        };
+        let parsed =
+            value
+                .parse()
+                .map_err(|err: T::Err| DeserializeErrorKind::MalformedCustomTag {
+                    name: key.to_owned(),
+                    err: err.into(),
+                })?;
+        Ok(Some(parsed))
+    }
 }
 
 /// Version-independent information about the tree.
@@ -102,6 +162,30 @@ impl Manifest {
         }
     }
 
+    /// Returns the minor protocol version supported by the node that has created the tree, if it was recorded.
+    pub fn protocol_version(&self) -> Result<Option<u16>, DeserializeError> {
+        let Some(tags) = &self.tags else {
+            return Ok(None);
+        };
+        tags.parse_custom(TreeTags::PROTOCOL_VERSION_TAG)
+    }
+
+    /// Iterates over custom tags as `(key, value)` pairs in no particular order. Standard tags are not included.
+    pub fn custom_tags(&self) -> impl Iterator<Item = (&str, &str)> + '_ {
+        self.tags
+            .iter()
+            .flat_map(|tags| &tags.custom)
+            .map(|(key, value)| (key.as_str(), value.as_str()))
+    }
+
+    /// Removes a custom tag with the specified key. Standard tags cannot be removed using this method.
+    /// Returns `true` if the tag was present.
+    pub fn remove_custom_tag(&mut self, key: &str) -> bool {
+        self.tags
+            .as_mut()
+            .is_some_and(|tags| tags.custom.remove(key).is_some())
+    }
+
     #[cfg(test)]
     pub(crate) fn new(version_count: u64, hasher: &dyn HashTree) -> Self {
         Self {
@@ -384,8 +468,11 @@ impl NodeKey {
 #[derive(Debug, Clone, Copy)]
 #[cfg_attr(test, derive(PartialEq, Eq))]
 pub struct LeafNode {
+    /// Full key of the leaf.
     pub full_key: Key,
+    /// Hash of the value stored in the leaf.
     pub value_hash: ValueHash,
+    /// 1-based index of the leaf assigned on insertion.
     pub leaf_index: u64,
 }
 
@@ -460,7 +547,7 @@ impl PartialEq for InternalNode {
 impl fmt::Debug for InternalNode {
     fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
         let mut map = formatter.debug_map();
-        for (nibble, child_ref) in self.children() {
+        for (nibble, child_ref) in self.children_sorted() {
             let nibble = format!("{nibble:x}");
             map.entry(&nibble, child_ref);
         }
@@ -472,17 +559,16 @@ impl InternalNode {
     /// Number of children in an internal node (= tree radix).
     pub(crate) const CHILD_COUNT: u8 = 16;
 
-    pub(crate) fn with_capacity(capacity: usize) -> Self {
-        Self {
-            cache: None,
-            children: SmallMap::with_capacity(capacity),
-        }
-    }
-
     pub(crate) fn child_count(&self) -> usize {
         self.children.len()
     }
 
+    /// Removes all children and the cache from this node, retaining the allocated child storage.
+    pub(crate) fn clear_with_capacity(&mut self, capacity: usize) {
+        self.children.clear_and_reserve(capacity);
+        self.cache = None;
+    }
+
     pub(crate) fn cache_mut(&mut self) -> Option<&mut InternalNodeCache> {
         self.cache.as_deref_mut()
     }
@@ -492,10 +578,18 @@ impl InternalNode {
         self.cache.get_or_insert(cache)
     }
 
+    /// Iterates over children of this node together with their nibbles.
     pub fn children(&self) -> impl Iterator<Item = (u8, &ChildRef)> + '_ {
         self.children.iter()
     }
 
+    /// Iterates over children of this node together with their nibbles. Unlike [`Self::children()`],
+    /// children are guaranteed to be yielded in the ascending nibble order.
+    pub(crate) fn children_sorted(&self) -> impl Iterator<Item = (u8, &ChildRef)> + '_ {
+        // `SmallMap` stores values in the order of their keys
+        self.children.iter()
+    }
+
     pub(crate) fn child_refs(&self) -> impl Iterator<Item = &ChildRef> + '_ {
         self.children.values()
     }
@@ -541,6 +635,37 @@ pub enum Node {
     Leaf(LeafNode),
 }
 
+impl Node {
+    /// Returns a hash of the node contents that does not depend on the node serialization format.
+    /// Equal nodes always have equal content hashes, so the hash can be used to cheaply check
+    /// whether a node has changed.
+    ///
+    /// This is **not** the Merkle tree hash of the node; e.g., it covers child versions.
+    pub(crate) fn content_hash(&self) -> ValueHash {
+        let mut buffer = Vec::with_capacity(1 + 16 * (1 + HASH_SIZE + 8 + 1));
+        match self {
+            Self::Internal(node) => {
+                buffer.push(0);
+                for (nibble, child_ref) in node.children_sorted() {
+                    buffer.push(nibble);
+                    buffer.extend_from_slice(child_ref.hash.as_bytes());
+                    buffer.extend_from_slice(&child_ref.version.to_le_bytes());
+                    buffer.push(u8::from(child_ref.is_leaf));
+                }
+            }
+            Self::Leaf(leaf) => {
+                buffer.push(1);
+                let mut full_key = [0_u8; KEY_SIZE];
+                leaf.full_key.to_big_endian(&mut full_key);
+                buffer.extend_from_slice(&full_key);
+                buffer.extend_from_slice(leaf.value_hash.as_bytes());
+                buffer.extend_from_slice(&leaf.leaf_index.to_le_bytes());
+            }
+        }
+        Blake2Hasher.hash_bytes(&buffer)
+    }
+}
+
 impl From<LeafNode> for Node {
     fn from(leaf: LeafNode) -> Self {
         Self::Leaf(leaf)
@@ -588,12 +713,27 @@ impl Root {
         }
     }
 
-    pub(crate) fn leaf_count(&self) -> u64 {
+    /// Checks whether this is a root of an empty tree.
+    pub fn is_empty(&self) -> bool {
+        matches!(self, Self::Empty)
+    }
+
+    /// Returns the number of leaves in the tree. This information is stored in the root,
+    /// so it doesn't require traversing the tree.
+    pub fn leaf_count(&self) -> u64 {
         match self {
             Self::Empty => 0,
             Self::Filled { leaf_count, .. } => (*leaf_count).into(),
         }
     }
+
+    /// Returns the root node, or `None` for an empty tree.
+    pub fn root_node(&self) -> Option<&Node> {
+        match self {
+            Self::Empty => None,
+            Self::Filled { node, .. } => Some(node),
+        }
+    }
 }
 
 /// Stale [`NodeKey`] with information about when it was replaced.
@@ -645,6 +785,7 @@ impl ProfiledTreeOperation {
 
 #[cfg(test)]
 mod tests {
+    use assert_matches::assert_matches;
     use zksync_types::U256;
 
     use super::*;
@@ -653,6 +794,76 @@ mod tests {
     // `0x_dead_beef_0000_0000_.._0000.`
     const TEST_KEY: Key = U256([0, 0, 0, 0x_dead_beef_0000_0000]);
 
+    #[test]
+    fn parsing_custom_tags() {
+        let mut tags = TreeTags::new(&Blake2Hasher);
+        tags.custom = HashMap::from([
+            ("count".to_owned(), "42".to_owned()),
+            ("enabled".to_owned(), "true".to_owned()),
+            ("malformed".to_owned(), "-1".to_owned()),
+        ]);
+
+        assert_eq!(tags.custom_u64("count").unwrap(), Some(42));
+        assert_eq!(tags.custom_bool("enabled").unwrap(), Some(true));
+        assert_eq!(tags.custom_u64("missing").unwrap(), None);
+        assert_eq!(tags.custom_bool("missing").unwrap(), None);
+
+        let err = tags.custom_u64("malformed").unwrap_err().to_string();
+        assert!(err.contains("malformed custom tag `malformed`"), "{err}");
+        let err = tags.custom_bool("count").unwrap_err().to_string();
+        assert!(err.contains("malformed custom tag `count`"), "{err}");
+    }
+
+    #[test]
+    fn checking_hash_length() {
+        let mut tags = TreeTags::new(&Blake2Hasher);
+        assert_eq!(tags.hash_len().unwrap(), HASH_SIZE);
+        tags.ensure_consistency(&Blake2Hasher, false).unwrap();
+
+        tags.custom
+            .insert(TreeTags::HASH_LEN_TAG.to_owned(), "32".to_owned());
+        tags.ensure_consistency(&Blake2Hasher, false).unwrap();
+
+        tags.custom
+            .insert(TreeTags::HASH_LEN_TAG.to_owned(), "20".to_owned());
+        assert_eq!(tags.hash_len().unwrap(), 20);
+        let err = tags
+            .ensure_consistency(&Blake2Hasher, false)
+            .unwrap_err()
+            .to_string();
+        assert!(
+            err.contains(
+                "value hash length 20 declared in tree manifest differs from the length 32"
+            ),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn root_accessors() {
+        let root = Root::Empty;
+        assert!(root.is_empty());
+        assert_eq!(root.leaf_count(), 0);
+        assert!(root.root_node().is_none());
+
+        let leaf = LeafNode::new(TreeEntry::new(TEST_KEY, 1, ValueHash::repeat_byte(1)));
+        let root = Root::new(1, leaf.into());
+        assert!(!root.is_empty());
+        assert_eq!(root.leaf_count(), 1);
+        assert_matches!(root.root_node(), Some(Node::Leaf(leaf)) if leaf.full_key == TEST_KEY);
+
+        let mut node = InternalNode::default();
+        node.insert_child_ref(0, ChildRef::leaf(1));
+        node.insert_child_ref(0xd, ChildRef::leaf(1));
+        let root = Root::new(2, node.into());
+        assert!(!root.is_empty());
+        assert_eq!(root.leaf_count(), 2);
+        assert_matches!(
+            root.root_node(),
+            Some(Node::Internal(node)) if node.child_count() == 2
+        );
+    }
+
     #[test]
     fn accessing_nibbles_in_key() {
         let start_nibbles = [0xd, 0xe, 0xa, 0xd, 0xb, 0xe, 0xe, 0xf];
@@ -778,4 +989,68 @@ mod tests {
         assert_ne!(nibbles, other_nibbles);
         assert!(nibbles > other_nibbles);
     }
+
+    fn test_internal_node() -> InternalNode {
+        let mut node = InternalNode::default();
+        node.insert_child_ref(0, ChildRef::leaf(1));
+        node.insert_child_ref(
+            0xb,
+            ChildRef {
+                hash: ValueHash::repeat_byte(0xb),
+                version: 2,
+                is_leaf: false,
+            },
+        );
+        node
+    }
+
+    #[test]
+    fn content_hash_for_equal_nodes() {
+        let node = Node::from(test_internal_node());
+        assert_eq!(node.content_hash(), node.clone().content_hash());
+        assert_eq!(
+            node.content_hash(),
+            Node::from(test_internal_node()).content_hash()
+        );
+
+        let leaf = Node::from(LeafNode::new(TreeEntry::new(
+            TEST_KEY,
+            1,
+            ValueHash::repeat_byte(1),
+        )));
+        assert_eq!(leaf.content_hash(), leaf.clone().content_hash());
+    }
+
+    #[test]
+    fn content_hash_for_different_nodes() {
+        let node = Node::from(test_internal_node());
+        let node_hash = node.content_hash();
+
+        let mut changed_node = test_internal_node();
+        changed_node.child_ref_mut(0xb).unwrap().hash = ValueHash::zero();
+        assert_ne!(Node::from(changed_node).content_hash(), node_hash);
+        let mut changed_node = test_internal_node();
+        changed_node.child_ref_mut(0xb).unwrap().version = 3;
+        assert_ne!(Node::from(changed_node).content_hash(), node_hash);
+        let mut changed_node = test_internal_node();
+        changed_node.child_ref_mut(0).unwrap().is_leaf = false;
+        assert_ne!(Node::from(changed_node).content_hash(), node_hash);
+        let mut changed_node = test_internal_node();
+        changed_node.insert_child_ref(1, ChildRef::leaf(1));
+        assert_ne!(Node::from(changed_node).content_hash(), node_hash);
+
+        let leaf = LeafNode::new(TreeEntry::new(TEST_KEY, 1, ValueHash::repeat_byte(1)));
+        let leaf_hash = Node::from(leaf).content_hash();
+        assert_ne!(leaf_hash, node_hash);
+
+        let mut changed_leaf = leaf;
+        changed_leaf.full_key = U256::one();
+        assert_ne!(Node::from(changed_leaf).content_hash(), leaf_hash);
+        let mut changed_leaf = leaf;
+        changed_leaf.value_hash = ValueHash::zero();
+        assert_ne!(Node::from(changed_leaf).content_hash(), leaf_hash);
+        let mut changed_leaf = leaf;
+        changed_leaf.leaf_index = 2;
+        assert_ne!(Node::from(changed_leaf).content_hash(), leaf_hash);
+    }
 }
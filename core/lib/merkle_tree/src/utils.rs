@@ -28,6 +28,7 @@ impl<V> Default for SmallMap<V> {
 impl<V> SmallMap<V> {
     const CAPACITY: u8 = 16;
 
+    #[cfg(test)]
     pub fn with_capacity(capacity: usize) -> Self {
         assert!(
             capacity <= usize::from(Self::CAPACITY),
@@ -43,6 +44,18 @@ impl<V> SmallMap<V> {
         self.bitmap.count_ones() as usize
     }
 
+    /// Removes all values from the map, retaining the allocated capacity, and reserves capacity
+    /// for at least `capacity` values.
+    pub fn clear_and_reserve(&mut self, capacity: usize) {
+        assert!(
+            capacity <= usize::from(Self::CAPACITY),
+            "capacity is too large"
+        );
+        self.bitmap = 0;
+        self.values.clear();
+        self.values.reserve(capacity);
+    }
+
     pub fn get(&self, index: u8) -> Option<&V> {
         assert!(index < Self::CAPACITY, "index is too large");
 
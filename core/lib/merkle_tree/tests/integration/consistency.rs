@@ -3,7 +3,10 @@
 
 use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
 use tempfile::TempDir;
-use zksync_merkle_tree::{MerkleTree, MerkleTreeColumnFamily, RocksDBWrapper};
+use zksync_merkle_tree::{
+    unstable::{verify_tree_nodes, NodeAnomaly},
+    MerkleTree, MerkleTreeColumnFamily, RocksDBWrapper,
+};
 
 use crate::common::generate_key_value_pairs;
 
@@ -66,3 +69,53 @@ fn five_thousand_angry_monkeys_vs_merkle_tree() {
         raw_db.write(reverse_batch).unwrap();
     }
 }
+
+#[test]
+fn verifying_tree_nodes() {
+    let dir = TempDir::new().expect("failed creating temporary dir for RocksDB");
+    let mut db = RocksDBWrapper::new(dir.path()).unwrap();
+    let mut tree = MerkleTree::new(&mut db).unwrap();
+    let kvs = generate_key_value_pairs(0..100);
+    for chunk in kvs.chunks(25) {
+        tree.extend(chunk.to_vec()).unwrap();
+    }
+
+    let report = verify_tree_nodes(&db).unwrap();
+    assert!(report.is_clean(), "{:?}", report.anomalies);
+    assert_eq!(report.checked_roots, 4);
+    assert!(report.checked_nodes > 100);
+    assert_eq!(report.unreachable_nodes, 0);
+
+    let mut raw_db = db.into_inner();
+    let cf = MerkleTreeColumnFamily::Tree;
+    // Pick a non-root node from the 0-th version of the tree.
+    let (key, _) = raw_db
+        .prefix_iterator_cf(cf, &[0; 8])
+        .find(|(key, _)| key.len() > 9)
+        .unwrap();
+
+    let mut batch = raw_db.new_write_batch();
+    batch.put_cf(cf, &key, &[]);
+    raw_db.write(batch).unwrap();
+    let db = RocksDBWrapper::from(raw_db);
+    let report = verify_tree_nodes(&db).unwrap();
+    assert_eq!(report.anomalies.len(), 1, "{:?}", report.anomalies);
+    assert!(
+        matches!(&report.anomalies[0], NodeAnomaly::Deserialize { .. }),
+        "{:?}",
+        report.anomalies
+    );
+
+    let mut raw_db = db.into_inner();
+    let mut batch = raw_db.new_write_batch();
+    batch.delete_cf(cf, &key);
+    raw_db.write(batch).unwrap();
+    let db = RocksDBWrapper::from(raw_db);
+    let report = verify_tree_nodes(&db).unwrap();
+    assert_eq!(report.anomalies.len(), 1, "{:?}", report.anomalies);
+    assert!(
+        matches!(&report.anomalies[0], NodeAnomaly::MissingNode { .. }),
+        "{:?}",
+        report.anomalies
+    );
+}
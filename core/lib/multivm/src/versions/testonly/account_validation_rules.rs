@@ -1,10 +1,12 @@
 use assert_matches::assert_matches;
 use zksync_test_contracts::{Account, TestContract};
-use zksync_types::{address_to_h256, fee::Fee, AccountTreeId, Address, StorageKey, H256};
+use zksync_types::{address_to_h256, fee::Fee, l2::L2Tx, AccountTreeId, Address, StorageKey, H256};
 
 use super::{
-    default_system_env, get_empty_storage, require_eip712::make_aa_transaction,
-    tester::VmTesterBuilder, ContractToDeploy, TestedVm, TestedVmForValidation,
+    default_system_env, get_empty_storage,
+    require_eip712::make_aa_transaction,
+    tester::{VmTester, VmTesterBuilder},
+    ContractToDeploy, TestedVm, TestedVmForValidation,
 };
 use crate::interface::{
     tracer::ViolatedValidationRule, ExecutionResult, Halt, InspectExecutionMode, SystemEnv,
@@ -14,7 +16,7 @@ use crate::interface::{
 /// Corresponds to test cases in the `ValidationRuleBreaker` contract.
 #[derive(Debug, Clone, Copy)]
 #[repr(u32)]
-enum TestCase {
+pub(crate) enum TestCase {
     Baseline = 0,
     ReadBootloaderBalance = 1,
     CallEoa = 2,
@@ -26,8 +28,13 @@ enum TestCase {
     DisallowedReadFromLayeredMapping = 8,
     DisallowedReadFromMappingWithOffset = 9,
     ReadFromMappingWithLargeOffset = 10,
+    StaticCallToMockToken = 11,
+    CallToMockToken = 12,
 }
 
+/// Address of the `MockToken` contract deployed for test cases reading storage of another contract.
+pub(crate) const MOCK_TOKEN_ADDRESS: Address = Address::repeat_byte(0x23);
+
 /// Checks that every limitation imposed on account validation results in an appropriate error.
 /// The actual misbehavior cases are found in "validation-rule-breaker.sol".
 pub(crate) fn test_account_validation_rules<VM: TestedVm + TestedVmForValidation>() {
@@ -106,6 +113,15 @@ fn test_rule<VM: TestedVmForValidation>(
     validation_gas_limit: u32,
     test_case: TestCase,
 ) -> (VmExecutionResultAndLogs, Option<ViolatedValidationRule>) {
+    let (mut vm, tx) = prepare_rule_test::<VM>(validation_gas_limit, test_case);
+    vm.vm.run_validation(tx, 55)
+}
+
+/// Prepares a VM with the `ValidationRuleBreaker` account set up for `test_case`, and a transaction from this account.
+pub(crate) fn prepare_rule_test<VM: TestedVm>(
+    validation_gas_limit: u32,
+    test_case: TestCase,
+) -> (VmTester<VM>, L2Tx) {
     let aa_address = Address::repeat_byte(0x10);
     let beneficiary_address = Address::repeat_byte(0x20);
 
@@ -130,8 +146,10 @@ fn test_rule<VM: TestedVmForValidation>(
             | TestCase::DisallowedReadFromLayeredMapping
             | TestCase::DisallowedReadFromMappingWithOffset
             | TestCase::ReadFromMappingWithLargeOffset
+            | TestCase::StaticCallToMockToken
+            | TestCase::CallToMockToken
     ) {
-        let token_address = Address::repeat_byte(0x23);
+        let token_address = MOCK_TOKEN_ADDRESS;
         let bytecode = TestContract::validation_test_mock_token().bytecode.to_vec();
         contracts.push(ContractToDeploy::new(bytecode, token_address));
         // Set the mock token address in the AA contract.
@@ -154,7 +172,7 @@ fn test_rule<VM: TestedVmForValidation>(
 
     let private_account = &mut vm.rich_accounts[0];
     let tx = make_aa_transaction(aa_address, beneficiary_address, private_account, None);
-    vm.vm.run_validation(tx, 55)
+    (vm, tx)
 }
 
 const OUT_OF_GAS_CASES: [TestCase; 3] = [
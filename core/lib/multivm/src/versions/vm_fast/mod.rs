@@ -3,8 +3,9 @@ pub use zksync_vm2::interface;
 pub(crate) use self::version::FastVmVersion;
 pub use self::{
     tracers::{
-        CallTracer, FastValidationTracer, FullValidationTracer, StorageInvocationsTracer,
-        ValidationTracer,
+        derived_validation_slots, eip7562_banned_precompiles, CallTracer, FastValidationTracer,
+        FullValidationTracer, SlotPredicate, StaticCallPolicy, StorageInvocationsTracer,
+        ValidationTracer, ViolationCallback, ViolationContext, ViolationLocation,
     },
     vm::Vm,
 };
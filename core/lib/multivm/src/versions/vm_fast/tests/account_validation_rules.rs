@@ -1,15 +1,31 @@
-use zksync_types::Address;
+use std::collections::HashSet;
+
+use assert_matches::assert_matches;
+use zksync_types::{
+    address_to_u256, h256_to_u256, storage_key_for_eth_balance,
+    storage_key_for_standard_token_balance, AccountTreeId, Address, KECCAK256_PRECOMPILE_ADDRESS,
+    U256,
+};
+use zksync_vm2::interface::Opcode;
 
 use super::TestedFastVm;
 use crate::{
+    interface::{
+        tracer::ViolatedValidationRule, ExecutionResult, Halt, InspectExecutionMode, VmInterface,
+        VmRevertReason,
+    },
     versions::testonly::{
         account_validation_rules::{
-            test_account_validation_rules, test_validation_out_of_gas_with_fast_tracer,
-            test_validation_out_of_gas_with_full_tracer,
+            prepare_rule_test, test_account_validation_rules,
+            test_validation_out_of_gas_with_fast_tracer,
+            test_validation_out_of_gas_with_full_tracer, TestCase, MOCK_TOKEN_ADDRESS,
         },
-        inspect_oneshot_dump, load_vm_dump, mock_validation_params,
+        inspect_oneshot_dump, load_vm_dump, mock_validation_params, validation_params,
+    },
+    vm_fast::{
+        self, derived_validation_slots, eip7562_banned_precompiles, FastValidationTracer,
+        FullValidationTracer, SlotPredicate, StaticCallPolicy, ValidationTracer,
     },
-    vm_fast::{self, FastValidationTracer, FullValidationTracer},
 };
 
 #[test]
@@ -42,5 +58,451 @@ fn adjacent_storage_slots_are_allowed() {
     let res = inspect_oneshot_dump::<vm_fast::Vm<_, _, _>>(vm_dump, &mut tracers);
     let violated_rule = tracers.1.validation_error();
     assert!(violated_rule.is_none(), "{violated_rule:?}");
+    assert!(tracers.1.validation_entered());
     assert!(!res.result.is_failed(), "{:?}", res.result);
 }
+
+#[test]
+fn validated_account_is_known_after_entering_validation() {
+    let vm_dump = load_vm_dump("validation_adjacent_storage_slots");
+    let tx = &vm_dump.l2_blocks[0].txs[0];
+    let validation_params = mock_validation_params(tx, &[]);
+    let mut tracer = FullValidationTracer::new(validation_params, 1);
+    assert_eq!(tracer.validated_account(), None);
+
+    tracer.account_validation_entered(u32::MAX, 0);
+    assert_eq!(tracer.validated_account(), Some(tx.initiator_account()));
+    tracer.validation_exited();
+    assert_eq!(tracer.validated_account(), Some(tx.initiator_account()));
+}
+
+#[test]
+fn validation_entered_flag() {
+    let mut tracer = FullValidationTracer::default();
+    assert!(!tracer.validation_entered());
+    assert!(tracer.validation_error().is_none());
+
+    tracer.account_validation_entered(u32::MAX, 0);
+    assert!(tracer.validation_entered());
+    tracer.validation_exited();
+    // The flag is not reset after validation has exited.
+    assert!(tracer.validation_entered());
+    assert_eq!(tracer.unmatched_validation_exits(), 0);
+}
+
+#[test]
+fn validation_exit_without_entry_is_recorded() {
+    let mut tracer = FullValidationTracer::default();
+    assert_eq!(tracer.validation_exited(), None);
+    assert_eq!(tracer.unmatched_validation_exits(), 1);
+    assert!(!tracer.validation_entered());
+
+    // Properly paired hooks don't change the count, but a repeated exit does.
+    tracer.account_validation_entered(u32::MAX, 0);
+    tracer.validation_exited();
+    assert_eq!(tracer.unmatched_validation_exits(), 1);
+    tracer.validation_exited();
+    assert_eq!(tracer.unmatched_validation_exits(), 2);
+}
+
+#[test]
+fn custom_policy_can_ban_opcodes() {
+    let vm_dump = load_vm_dump("validation_adjacent_storage_slots");
+    let tx = &vm_dump.l2_blocks[0].txs[0];
+    let accessed_tokens = [
+        "0x185db4e63ff8afdc67b2e44acaa837c104eb22bc",
+        "0x272814b0125380dc65a63570abf903d0a434b597",
+    ]
+    .map(|s| s.parse::<Address>().unwrap());
+    let validation_params = mock_validation_params(tx, &accessed_tokens);
+    // Storage reads are allowed by the built-in rules (see the test above), so any violation comes from the policy.
+    let tracer = FullValidationTracer::new(validation_params, 1).with_violation_callback(|ctx| {
+        (ctx.opcode() == Opcode::StorageRead).then_some(
+            ViolatedValidationRule::TouchedDisallowedStorageSlots(ctx.address(), 0.into()),
+        )
+    });
+    let mut tracers = ((), tracer);
+
+    inspect_oneshot_dump::<vm_fast::Vm<_, _, _>>(vm_dump, &mut tracers);
+    let violated_rule = tracers.1.validation_error();
+    assert!(
+        matches!(
+            violated_rule,
+            Some(ViolatedValidationRule::TouchedDisallowedStorageSlots(_, slot)) if slot.is_zero()
+        ),
+        "{violated_rule:?}"
+    );
+}
+
+fn count_validation_instructions(stop_at: Option<u64>) -> FullValidationTracer {
+    let (mut vm, tx) =
+        prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(u32::MAX, TestCase::Baseline);
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let mut tracer = FullValidationTracer::new(validation_params, 55);
+    if let Some(stop_at) = stop_at {
+        // Flags a violation on the `stop_at`-th validation instruction, which stops the VM right after it.
+        let mut seen_instructions = 0;
+        tracer = tracer.with_violation_callback(move |_| {
+            seen_instructions += 1;
+            (seen_instructions == stop_at)
+                .then_some(ViolatedValidationRule::TouchedDisallowedContext)
+        });
+    }
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    tracers.1
+}
+
+#[test]
+fn validation_instructions_are_counted() {
+    const STOP_AT: u64 = 100;
+
+    let tracer = count_validation_instructions(None);
+    assert_matches!(tracer.validation_error(), None);
+    assert!(tracer.validation_instruction_count() > STOP_AT);
+
+    // Validation is stopped after a known number of instructions.
+    let tracer = count_validation_instructions(Some(STOP_AT));
+    assert_matches!(
+        tracer.validation_error(),
+        Some(ViolatedValidationRule::TouchedDisallowedContext)
+    );
+    assert_eq!(tracer.validation_instruction_count(), STOP_AT);
+}
+
+#[test]
+fn storage_accesses_are_limited() {
+    let vm_dump = load_vm_dump("validation_adjacent_storage_slots");
+    let tx = &vm_dump.l2_blocks[0].txs[0];
+    let accessed_tokens = [
+        "0x185db4e63ff8afdc67b2e44acaa837c104eb22bc",
+        "0x272814b0125380dc65a63570abf903d0a434b597",
+    ]
+    .map(|s| s.parse::<Address>().unwrap());
+    let validation_params = mock_validation_params(tx, &accessed_tokens);
+
+    // Determine the number of storage accesses made by validation without a limit.
+    let tracer = FullValidationTracer::new(validation_params.clone(), 1);
+    let mut tracers = ((), tracer);
+    let res = inspect_oneshot_dump::<vm_fast::Vm<_, _, _>>(vm_dump.clone(), &mut tracers);
+    assert!(!res.result.is_failed(), "{:?}", res.result);
+    assert_matches!(tracers.1.validation_error(), None);
+    let access_count = tracers.1.storage_access_count();
+    assert!(access_count > 1, "{access_count}");
+
+    // The exact limit is fine.
+    let tracer = FullValidationTracer::new(validation_params.clone(), 1)
+        .with_max_storage_accesses(Some(access_count));
+    let mut tracers = ((), tracer);
+    inspect_oneshot_dump::<vm_fast::Vm<_, _, _>>(vm_dump.clone(), &mut tracers);
+    assert_matches!(tracers.1.validation_error(), None);
+
+    let limit = access_count - 1;
+    let tracer =
+        FullValidationTracer::new(validation_params, 1).with_max_storage_accesses(Some(limit));
+    let mut tracers = ((), tracer);
+    inspect_oneshot_dump::<vm_fast::Vm<_, _, _>>(vm_dump, &mut tracers);
+    assert_eq!(
+        tracers.1.validation_error(),
+        Some(ViolatedValidationRule::TooManyStorageAccesses(limit))
+    );
+    assert_eq!(tracers.1.storage_access_count(), access_count);
+}
+
+#[test]
+fn violation_location_is_recorded() {
+    let (mut vm, tx) = prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(
+        u32::MAX,
+        TestCase::StaticCallToMockToken,
+    );
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let tracer = FullValidationTracer::new(validation_params, 55);
+    assert_eq!(tracer.violation_location(), None);
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+
+    assert_matches!(
+        tracers.1.validation_error(),
+        Some(ViolatedValidationRule::TouchedDisallowedStorageSlots(address, _))
+            if address == MOCK_TOKEN_ADDRESS
+    );
+    let location = tracers.1.violation_location().unwrap();
+    assert_eq!(location.address, MOCK_TOKEN_ADDRESS);
+    // The storage read cannot be the first instruction of the contract (at the very least, it needs to dispatch on calldata).
+    let pc = location.pc.unwrap();
+    assert!(pc > 0, "{location:?}");
+}
+
+#[test]
+fn violation_location_is_not_recorded_without_violation() {
+    let (mut vm, tx) =
+        prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(u32::MAX, TestCase::Baseline);
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let tracer = FullValidationTracer::new(validation_params, 55);
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    assert_matches!(tracers.1.validation_error(), None);
+    assert_eq!(tracers.1.violation_location(), None);
+}
+
+fn run_full_tracer(validation_gas_limit: u32, test_case: TestCase) -> FullValidationTracer {
+    let (mut vm, tx) = prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(
+        validation_gas_limit,
+        test_case,
+    );
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let tracer = FullValidationTracer::new(validation_params, 55);
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    tracers.1
+}
+
+#[test]
+fn halt_reason_for_validation_out_of_gas() {
+    let tracer = run_full_tracer(300_000, TestCase::PlainOutOfGas);
+    assert_matches!(
+        tracer.validation_error(),
+        Some(ViolatedValidationRule::TookTooManyComputationalGas(_))
+    );
+    assert_eq!(tracer.into_halt_reason(), Some(Halt::ValidationOutOfGas));
+}
+
+#[test]
+fn halt_reason_for_storage_rule_violation() {
+    let tracer = run_full_tracer(u32::MAX, TestCase::StaticCallToMockToken);
+    let violated_rule = tracer.validation_error().unwrap();
+    assert_matches!(
+        violated_rule,
+        ViolatedValidationRule::TouchedDisallowedStorageSlots(address, _)
+            if address == MOCK_TOKEN_ADDRESS
+    );
+    assert_matches!(
+        tracer.into_halt_reason(),
+        Some(Halt::ValidationFailed(VmRevertReason::General { msg, .. }))
+            if msg == violated_rule.to_string()
+    );
+
+    let tracer = run_full_tracer(u32::MAX, TestCase::Baseline);
+    assert_eq!(tracer.into_halt_reason(), None);
+}
+
+fn test_rule_with_banned_precompiles(
+    test_case: TestCase,
+    banned_precompiles: Option<HashSet<Address>>,
+) -> Option<ViolatedValidationRule> {
+    let (mut vm, tx) =
+        prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(u32::MAX, test_case);
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let mut tracer = FullValidationTracer::new(validation_params, 55);
+    if let Some(banned_precompiles) = banned_precompiles {
+        tracer = tracer.with_banned_precompiles(banned_precompiles);
+    }
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    tracers.1.validation_error()
+}
+
+#[test]
+fn calling_allowed_precompile() {
+    // Reading from mappings calls the keccak256 precompile, which is not banned by default.
+    assert!(!eip7562_banned_precompiles().contains(&KECCAK256_PRECOMPILE_ADDRESS));
+    let violated_rule = test_rule_with_banned_precompiles(TestCase::ReadFromMappings, None);
+    assert_matches!(violated_rule, None);
+}
+
+#[test]
+fn calling_banned_precompile() {
+    let banned_precompiles = HashSet::from([KECCAK256_PRECOMPILE_ADDRESS]);
+    let violated_rule =
+        test_rule_with_banned_precompiles(TestCase::ReadFromMappings, Some(banned_precompiles));
+    assert_matches!(
+        violated_rule,
+        Some(ViolatedValidationRule::CalledBannedPrecompile(address))
+            if address == KECCAK256_PRECOMPILE_ADDRESS
+    );
+}
+
+#[test]
+fn default_banned_precompiles() {
+    let banned = eip7562_banned_precompiles();
+    assert_eq!(banned.len(), 8);
+    for allowed in 1..=9 {
+        assert!(!banned.contains(&Address::from_low_u64_be(allowed)));
+    }
+    assert!(banned.contains(&Address::from_low_u64_be(0x0a)));
+}
+
+fn test_rule_with_static_call_policy(
+    test_case: TestCase,
+    static_call_policy: Option<StaticCallPolicy>,
+) -> (ExecutionResult, Option<ViolatedValidationRule>) {
+    let (mut vm, tx) =
+        prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(u32::MAX, test_case);
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    let mut tracer = FullValidationTracer::new(validation_params, 55);
+    if let Some(static_call_policy) = static_call_policy {
+        tracer = tracer.with_static_call_policy(static_call_policy);
+    }
+    let mut tracers = ((), tracer);
+    let res = vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    (res.result, tracers.1.validation_error())
+}
+
+#[test]
+fn static_calls_to_allowlisted_contracts() {
+    let policy = StaticCallPolicy {
+        enabled: true,
+        allowlist: [MOCK_TOKEN_ADDRESS].into(),
+    };
+    let (result, violated_rule) =
+        test_rule_with_static_call_policy(TestCase::StaticCallToMockToken, Some(policy.clone()));
+    assert!(!result.is_failed(), "{result:?}");
+    assert_matches!(violated_rule, None);
+
+    let (result, violated_rule) =
+        test_rule_with_static_call_policy(TestCase::CallToMockToken, Some(policy));
+    assert_matches!(
+        result,
+        ExecutionResult::Halt {
+            reason: Halt::TracerCustom(_)
+        }
+    );
+    assert_matches!(
+        violated_rule,
+        Some(ViolatedValidationRule::NonStaticCallToStaticOnlyContract(address))
+            if address == MOCK_TOKEN_ADDRESS
+    );
+}
+
+#[test]
+fn static_calls_without_policy() {
+    // Without the policy, static calls are subject to the default rules.
+    let (_, violated_rule) =
+        test_rule_with_static_call_policy(TestCase::StaticCallToMockToken, None);
+    assert_matches!(
+        violated_rule,
+        Some(ViolatedValidationRule::TouchedDisallowedStorageSlots(address, _))
+            if address == MOCK_TOKEN_ADDRESS
+    );
+}
+
+#[test]
+fn static_calls_with_disabled_policy() {
+    // A disabled policy is ignored even if the called contract is allowlisted.
+    let policy = StaticCallPolicy {
+        enabled: false,
+        allowlist: [MOCK_TOKEN_ADDRESS].into(),
+    };
+    let (_, violated_rule) =
+        test_rule_with_static_call_policy(TestCase::StaticCallToMockToken, Some(policy));
+    assert_matches!(
+        violated_rule,
+        Some(ViolatedValidationRule::TouchedDisallowedStorageSlots(address, _))
+            if address == MOCK_TOKEN_ADDRESS
+    );
+}
+
+#[test]
+fn derived_validation_slots_for_known_accounts() {
+    let account = Address::repeat_byte(0x11);
+    let mut slots = derived_validation_slots(account);
+    assert_eq!(slots.account(), account);
+
+    // Base token balances are stored in a mapping at slot 0, and ERC20 balances in a mapping at slot 51.
+    let balance_slot = h256_to_u256(*storage_key_for_eth_balance(&account).key());
+    assert_eq!(slots.mapping_entry_slot(U256::zero()), balance_slot);
+    let token_balance_key =
+        storage_key_for_standard_token_balance(AccountTreeId::new(MOCK_TOKEN_ADDRESS), &account);
+    assert_eq!(
+        slots.mapping_entry_slot(51.into()),
+        h256_to_u256(*token_balance_key.key())
+    );
+
+    assert!(slots.contains(address_to_u256(&account)));
+    assert!(slots.is_mapping_preimage(address_to_u256(&account), U256::zero()));
+    // Mapping slots are only allowed once recorded.
+    assert!(!slots.contains(balance_slot));
+    slots.record_mapping_slot(balance_slot);
+    assert!(slots.contains(balance_slot));
+    let max_offset = U256::from(SlotPredicate::MAX_SLOT_OFFSET);
+    assert!(slots.contains(balance_slot + max_offset));
+    assert!(!slots.contains(balance_slot + max_offset + 1));
+    assert!(!slots.contains(balance_slot - 1));
+}
+
+#[test]
+fn derived_validation_slots_are_disjoint_for_different_accounts() {
+    let account = Address::repeat_byte(0x11);
+    let other_account = Address::repeat_byte(0x22);
+    let mut slots = derived_validation_slots(account);
+    let other_slots = derived_validation_slots(other_account);
+
+    assert!(!slots.contains(address_to_u256(&other_account)));
+    assert!(!other_slots.contains(address_to_u256(&account)));
+    assert!(!slots.is_mapping_preimage(address_to_u256(&other_account), U256::zero()));
+    let balance_slot = slots.mapping_entry_slot(U256::zero());
+    assert_ne!(balance_slot, other_slots.mapping_entry_slot(U256::zero()));
+
+    // Nested mappings are allowed only if the outer mapping entry is derived from the account.
+    let nested_key = U256::from(123);
+    assert!(!slots.is_mapping_preimage(nested_key, balance_slot));
+    slots.record_mapping_slot(balance_slot);
+    assert!(slots.is_mapping_preimage(nested_key, balance_slot));
+    assert!(!other_slots.is_mapping_preimage(nested_key, balance_slot));
+}
+
+fn test_rule_collecting_violations(collect_all: bool) -> FullValidationTracer {
+    let (mut vm, tx) = prepare_rule_test::<TestedFastVm<(), FullValidationTracer>>(
+        u32::MAX,
+        TestCase::StaticCallToMockToken,
+    );
+    let validation_params = validation_params(&tx, &vm.system_env);
+    vm.vm.push_transaction(tx.into());
+    // Reading `MockToken` storage breaks the storage access rules, and the access limit is broken by any access.
+    let tracer = FullValidationTracer::new(validation_params, 55)
+        .with_max_storage_accesses(Some(0))
+        .with_collect_all(collect_all);
+    let mut tracers = ((), tracer);
+    vm.vm.inspect(&mut tracers, InspectExecutionMode::OneTx);
+    tracers.1
+}
+
+#[test]
+fn all_violations_are_collected() {
+    let tracer = test_rule_collecting_violations(true);
+    let violations = tracer.all_violations();
+    assert_eq!(
+        violations[0],
+        ViolatedValidationRule::TooManyStorageAccesses(0)
+    );
+    // Repeated violations of the access limit are deduplicated, so all other violations are storage reads.
+    assert!(violations.len() > 1, "{violations:?}");
+    for violation in &violations[1..] {
+        assert_matches!(
+            violation,
+            ViolatedValidationRule::TouchedDisallowedStorageSlots(address, _)
+                if *address == MOCK_TOKEN_ADDRESS
+        );
+    }
+    // The first violation is still reported as the validation error.
+    assert_eq!(tracer.validation_error().as_ref(), violations.first());
+}
+
+#[test]
+fn only_first_violation_is_collected_by_default() {
+    let tracer = test_rule_collecting_violations(false);
+    assert_eq!(
+        tracer.all_violations(),
+        [ViolatedValidationRule::TooManyStorageAccesses(0)]
+    );
+    assert_eq!(
+        tracer.validation_error(),
+        Some(ViolatedValidationRule::TooManyStorageAccesses(0))
+    );
+}
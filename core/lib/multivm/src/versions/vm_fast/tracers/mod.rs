@@ -6,7 +6,11 @@ pub(super) use self::evm_deploy::DynamicBytecodes;
 pub use self::{
     calls::CallTracer,
     storage::StorageInvocationsTracer,
-    validation::{FastValidationTracer, FullValidationTracer, ValidationTracer},
+    validation::{
+        derived_validation_slots, eip7562_banned_precompiles, FastValidationTracer,
+        FullValidationTracer, SlotPredicate, StaticCallPolicy, ValidationTracer, ViolationCallback,
+        ViolationContext, ViolationLocation,
+    },
 };
 use self::{circuits::CircuitsTracer, evm_deploy::EvmDeployTracer};
 use crate::interface::CircuitStatistic;
@@ -1,14 +1,18 @@
-use std::collections::{BTreeSet, HashSet};
+use std::{
+    collections::{BTreeSet, HashSet},
+    fmt,
+};
 
 use zk_evm_1_3_1::address_to_u256;
 use zksync_types::{
-    u256_to_address, Address, ACCOUNT_CODE_STORAGE_ADDRESS, BOOTLOADER_ADDRESS,
-    CONTRACT_DEPLOYER_ADDRESS, KECCAK256_PRECOMPILE_ADDRESS, L2_BASE_TOKEN_ADDRESS,
-    MSG_VALUE_SIMULATOR_ADDRESS, SYSTEM_CONTEXT_ADDRESS, U256,
+    address_to_h256, h256_to_u256, u256_to_address, u256_to_h256, web3::keccak256, Address,
+    ACCOUNT_CODE_STORAGE_ADDRESS, BOOTLOADER_ADDRESS, CONTRACT_DEPLOYER_ADDRESS,
+    KECCAK256_PRECOMPILE_ADDRESS, L2_BASE_TOKEN_ADDRESS, MSG_VALUE_SIMULATOR_ADDRESS,
+    SYSTEM_CONTEXT_ADDRESS, U256,
 };
 use zksync_vm2::interface::{
-    CallframeInterface, GlobalStateInterface, Opcode::*, OpcodeType, ReturnType::*, ShouldStop,
-    Tracer,
+    CallframeInterface, GlobalStateInterface, Opcode, Opcode::*, OpcodeType, ReturnType::*,
+    ShouldStop, Tracer,
 };
 
 use crate::{
@@ -16,7 +20,7 @@ use crate::{
         tracer::{
             TimestampAsserterParams, ValidationParams, ValidationTraces, ViolatedValidationRule,
         },
-        Halt,
+        Halt, VmRevertReason,
     },
     tracers::TIMESTAMP_ASSERTER_FUNCTION_SELECTOR,
     vm_fast::utils::read_raw_fat_pointer,
@@ -68,6 +72,158 @@ impl ValidationTracer for FastValidationTracer {
     }
 }
 
+/// Information about the instruction about to be executed during validation,
+/// passed to the custom violation callback (see [`FullValidationTracer::with_violation_callback()`]).
+#[derive(Debug, Clone, Copy)]
+pub struct ViolationContext {
+    opcode: Opcode,
+    address: Address,
+    gas: u32,
+}
+
+impl ViolationContext {
+    /// Opcode of the instruction about to be executed.
+    pub fn opcode(&self) -> Opcode {
+        self.opcode
+    }
+
+    /// Address of the contract whose frame executes the instruction.
+    pub fn address(&self) -> Address {
+        self.address
+    }
+
+    /// Gas left in the current frame.
+    pub fn gas(&self) -> u32 {
+        self.gas
+    }
+}
+
+/// Location of the instruction that caused a validation violation (see [`FullValidationTracer::violation_location()`]).
+///
+/// For violations detected when making a far call (e.g., calling a contract without code), the location
+/// points to the called frame rather than to the call instruction.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ViolationLocation {
+    /// Address of the contract whose frame executed the instruction.
+    pub address: Address,
+    /// Program counter of the instruction in the contract bytecode, if it can be determined.
+    pub pc: Option<u16>,
+}
+
+/// Policy for external calls made during validation.
+///
+/// By default, validation may only read storage of external contracts in a few special cases (e.g., via mappings keyed
+/// by the account address). Enabling this policy additionally allows reading arbitrary storage of allowlisted contracts
+/// (e.g., oracles or registries) as long as they are called via `STATICCALL`. Since this relaxes [EIP-7562] rules,
+/// any state-mutating call to an allowlisted contract is treated as a violation.
+///
+/// [EIP-7562]: https://eips.ethereum.org/EIPS/eip-7562
+#[derive(Debug, Clone, Default)]
+pub struct StaticCallPolicy {
+    /// Whether the policy is enabled. If disabled, allowlisted contracts are treated like any other contract.
+    pub enabled: bool,
+    /// Contracts allowed to be called via `STATICCALL`.
+    pub allowlist: HashSet<Address>,
+}
+
+impl StaticCallPolicy {
+    fn is_static_only(&self, address: Address) -> bool {
+        self.enabled && self.allowlist.contains(&address)
+    }
+}
+
+/// Returns precompiles that cannot be called during validation per [EIP-7562]. The EIP only allows calling
+/// the core precompiles at addresses `0x01..=0x09` (plus `P256VERIFY` from RIP-7212), so the returned set
+/// contains the other Ethereum precompiles: point evaluation (`0x0a`), which depends on blob data, and BLS12-381
+/// precompiles (`0x0b..=0x11`).
+///
+/// [EIP-7562]: https://eips.ethereum.org/EIPS/eip-7562
+pub fn eip7562_banned_precompiles() -> HashSet<Address> {
+    (0x0a..=0x11_u64).map(Address::from_low_u64_be).collect()
+}
+
+/// Predicate for storage slots derived from an account address; such slots may be accessed during validation
+/// of the account regardless of the contract they belong to. Per [EIP-7562], a slot is derived from address `A` if:
+///
+/// - it's equal to `A`, or
+/// - it's equal to `keccak256(A ++ x) + n` (i.e., it's an entry of a Solidity mapping keyed by `A`),
+///   where `n` is in `0..=127`.
+///
+/// We extend this to nested mappings: `keccak256(k ++ x) + n` is derived as well if `x` is itself a derived
+/// mapping slot. Since `x` is generally not known in advance, the mapping slots are recorded as they are
+/// computed during execution (see [`Self::record_mapping_slot()`]).
+///
+/// [EIP-7562]: https://eips.ethereum.org/EIPS/eip-7562
+#[derive(Debug, Clone, Default)]
+pub struct SlotPredicate {
+    account: Address,
+    mapping_slots: BTreeSet<U256>,
+}
+
+/// Returns the predicate for storage slots derived from `account`, which may be accessed during its validation.
+pub fn derived_validation_slots(account: Address) -> SlotPredicate {
+    SlotPredicate {
+        account,
+        mapping_slots: BTreeSet::new(),
+    }
+}
+
+impl SlotPredicate {
+    /// Maximum offset of a derived slot from the corresponding mapping slot. Allows for mapped data occupying
+    /// more than 1 slot.
+    pub const MAX_SLOT_OFFSET: u32 = 127;
+
+    /// Returns the account that slots are derived from.
+    pub fn account(&self) -> Address {
+        self.account
+    }
+
+    /// Computes the slot of the entry keyed by the account in a Solidity mapping stored at `mapping_slot`,
+    /// i.e., `keccak256(account ++ mapping_slot)`.
+    pub fn mapping_entry_slot(&self, mapping_slot: U256) -> U256 {
+        let mut preimage = [0_u8; 64];
+        preimage[..32].copy_from_slice(address_to_h256(&self.account).as_bytes());
+        preimage[32..].copy_from_slice(u256_to_h256(mapping_slot).as_bytes());
+        h256_to_u256(keccak256(&preimage).into())
+    }
+
+    /// Checks whether the `keccak256(key ++ mapping_slot)` hash produces a derived mapping slot, i.e., whether
+    /// `key` is the account address or `mapping_slot` is a previously recorded mapping slot.
+    pub fn is_mapping_preimage(&self, key: U256, mapping_slot: U256) -> bool {
+        key == address_to_u256(&self.account) || self.mapping_slots.contains(&mapping_slot)
+    }
+
+    /// Records a derived mapping slot, so that it and slots following it are allowed.
+    pub fn record_mapping_slot(&mut self, slot: U256) {
+        self.mapping_slots.insert(slot);
+    }
+
+    /// Checks whether `slot` is derived from the account.
+    pub fn contains(&self, slot: U256) -> bool {
+        if slot == address_to_u256(&self.account) {
+            return true;
+        }
+        let min_slot = slot.saturating_sub(Self::MAX_SLOT_OFFSET.into());
+        self.mapping_slots.range(min_slot..=slot).next().is_some()
+    }
+}
+
+/// Custom validation policy. Returning a rule from the callback is treated as a validation violation.
+pub type ViolationCallback =
+    Box<dyn FnMut(&ViolationContext) -> Option<ViolatedValidationRule> + Send>;
+
+#[derive(Default)]
+struct CustomPolicy(Option<ViolationCallback>);
+
+impl fmt::Debug for CustomPolicy {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter
+            .debug_tuple("CustomPolicy")
+            .field(&self.0.as_ref().map(|_| "_"))
+            .finish()
+    }
+}
+
 /// Account abstraction exposes a chain to denial of service attacks because someone who fails to
 /// authenticate does not pay for the failed transaction.
 ///
@@ -99,10 +255,12 @@ impl ValidationTracer for FastValidationTracer {
 #[derive(Debug, Default)]
 pub struct FullValidationTracer {
     in_validation: bool,
+    validation_entered: bool,
+    unmatched_validation_exits: usize,
     validation_gas_limit: u32,
     add_return_value_to_allowed_slots: bool,
 
-    slots_obtained_via_keccak: BTreeSet<U256>,
+    derived_slots: SlotPredicate,
     trusted_addresses: HashSet<Address>,
 
     user_address: Address,
@@ -111,8 +269,18 @@ pub struct FullValidationTracer {
     storage_containing_trusted_addresses: HashSet<(Address, U256)>,
     timestamp_asserter_params: Option<TimestampAsserterParams>,
     l1_batch_timestamp: u64,
+    static_call_policy: StaticCallPolicy,
+    max_storage_accesses: Option<usize>,
+    banned_precompiles: HashSet<Address>,
+
+    custom_policy: CustomPolicy,
+    collect_all_violations: bool,
+    validation_instruction_count: u64,
+    storage_access_count: usize,
 
     validation_error: Option<ViolatedValidationRule>,
+    violation_location: Option<ViolationLocation>,
+    all_violations: Vec<ViolatedValidationRule>,
     traces: ValidationTraces,
 }
 
@@ -121,17 +289,20 @@ impl ValidationTracer for FullValidationTracer {
 
     fn account_validation_entered(&mut self, validation_gas_limit: u32, _gas_hidden: u32) {
         self.in_validation = true;
+        self.validation_entered = true;
         self.validation_gas_limit = validation_gas_limit;
     }
 
     fn validation_exited(&mut self) -> Option<Halt> {
-        self.in_validation = false;
-        match self.validation_error {
-            Some(ViolatedValidationRule::TookTooManyComputationalGas(_)) => {
-                Some(Halt::ValidationOutOfGas)
-            }
-            _ => None,
+        if !self.in_validation {
+            // Indicates that bootloader hooks are wired incorrectly.
+            tracing::warn!("Account validation exited without being entered");
+            self.unmatched_validation_exits += 1;
         }
+        self.in_validation = false;
+        // Only running out of validation gas halts the VM; other violations are reported via `validation_error()`.
+        self.halt_reason()
+            .filter(|halt| matches!(halt, Halt::ValidationOutOfGas))
     }
 }
 
@@ -140,6 +311,28 @@ impl Tracer for FullValidationTracer {
         if !self.in_validation {
             return;
         }
+        self.validation_instruction_count += 1;
+
+        let custom_violation = self.custom_policy.0.as_mut().and_then(|callback| {
+            let frame = state.current_frame();
+            callback(&ViolationContext {
+                opcode: OP::VALUE,
+                address: frame.address(),
+                gas: frame.gas(),
+            })
+        });
+        if let Some(err) = custom_violation {
+            self.set_error(state, err);
+        }
+
+        if matches!(OP::VALUE, StorageRead | StorageWrite) {
+            self.storage_access_count += 1;
+            if let Some(limit) = self.max_storage_accesses {
+                if self.storage_access_count > limit {
+                    self.set_error(state, ViolatedValidationRule::TooManyStorageAccesses(limit));
+                }
+            }
+        }
 
         match OP::VALUE {
             // FIXME: should this use the same filtering as the fast tracer?
@@ -147,14 +340,21 @@ impl Tracer for FullValidationTracer {
             Ret(Panic) if state.current_frame().gas() == 0 => {
                 let err =
                     ViolatedValidationRule::TookTooManyComputationalGas(self.validation_gas_limit);
-                self.set_error(err);
+                self.set_error(state, err);
             }
 
-            ContextMeta => self.set_error(ViolatedValidationRule::TouchedDisallowedContext),
+            ContextMeta => self.set_error(state, ViolatedValidationRule::TouchedDisallowedContext),
 
             StorageRead => {
                 let address = state.current_frame().address();
                 let caller = state.current_frame().caller();
+                // Static frames cannot mutate state, so reads are allowed for any allowlisted contract
+                // regardless of the slot. Non-static calls to such contracts are banned in `after_instruction()`.
+                if state.current_frame().is_static()
+                    && self.static_call_policy.is_static_only(address)
+                {
+                    return;
+                }
 
                 // Can unwrap because the instruction pointer does not point to a panic instruction
                 let pc = state.current_frame().program_counter().unwrap();
@@ -176,9 +376,10 @@ impl Tracer for FullValidationTracer {
                     slot,
                     state.get_storage(address, slot),
                 ) {
-                    self.set_error(ViolatedValidationRule::TouchedDisallowedStorageSlots(
-                        address, slot,
-                    ));
+                    self.set_error(
+                        state,
+                        ViolatedValidationRule::TouchedDisallowedStorageSlots(address, slot),
+                    );
                 }
             }
 
@@ -194,14 +395,33 @@ impl Tracer for FullValidationTracer {
             return ShouldStop::Continue;
         }
 
-        if self.validation_error.is_some() {
+        if self.validation_error.is_some() && !self.collect_all_violations {
             return ShouldStop::Stop;
         }
 
         match OP::VALUE {
             FarCall(_) => {
-                // Intercept calls to keccak, whitelist storage slots corresponding to the hash
+                let address = state.current_frame().address();
+                if !state.current_frame().is_static()
+                    && self.static_call_policy.is_static_only(address)
+                {
+                    self.set_error(
+                        state,
+                        ViolatedValidationRule::NonStaticCallToStaticOnlyContract(address),
+                    );
+                    return self.stop_on_violation();
+                }
+
                 let code_address = state.current_frame().code_address();
+                if self.banned_precompiles.contains(&code_address) {
+                    self.set_error(
+                        state,
+                        ViolatedValidationRule::CalledBannedPrecompile(code_address),
+                    );
+                    return self.stop_on_violation();
+                }
+
+                // Intercept calls to keccak, whitelist storage slots corresponding to the hash
                 if code_address == KECCAK256_PRECOMPILE_ADDRESS {
                     let calldata = read_raw_fat_pointer(state, state.read_register(1).0);
                     if calldata.len() != 64 {
@@ -211,10 +431,9 @@ impl Tracer for FullValidationTracer {
                     // Solidity mappings store values at the keccak256 hash of `key ++ slot_of_mapping`
                     let (key, mapping) = calldata.split_at(32);
 
-                    let mapping_is_allowed =
-                        self.slots_obtained_via_keccak.contains(&mapping.into());
-
-                    if U256::from(key) == address_to_u256(&self.user_address) || mapping_is_allowed
+                    if self
+                        .derived_slots
+                        .is_mapping_preimage(key.into(), mapping.into())
                     {
                         self.add_return_value_to_allowed_slots = true;
                     }
@@ -223,10 +442,11 @@ impl Tracer for FullValidationTracer {
                         .get_storage(ACCOUNT_CODE_STORAGE_ADDRESS, address_to_u256(&code_address))
                         .is_zero()
                 {
-                    self.set_error(ViolatedValidationRule::CalledContractWithNoCode(
-                        code_address,
-                    ));
-                    return ShouldStop::Stop;
+                    self.set_error(
+                        state,
+                        ViolatedValidationRule::CalledContractWithNoCode(code_address),
+                    );
+                    return self.stop_on_violation();
                 }
 
                 if let Some(ref params) = self.timestamp_asserter_params {
@@ -249,9 +469,10 @@ impl Tracer for FullValidationTracer {
                             // used in a oneshot execution mode
                             if end < self.l1_batch_timestamp + params.min_time_till_end.as_secs() {
                                 self.set_error(
+                                    state,
                                     ViolatedValidationRule::TimestampAssertionCloseToRangeEnd,
                                 );
-                                return ShouldStop::Stop;
+                                return self.stop_on_violation();
                             }
 
                             self.traces.apply_timestamp_asserter_range(start..end);
@@ -262,8 +483,8 @@ impl Tracer for FullValidationTracer {
             Ret(kind) => {
                 if self.add_return_value_to_allowed_slots && kind == Normal {
                     let return_value = read_raw_fat_pointer(state, state.read_register(1).0);
-                    self.slots_obtained_via_keccak
-                        .insert(return_value.as_slice().into());
+                    self.derived_slots
+                        .record_mapping_slot(return_value.as_slice().into());
                 }
                 self.add_return_value_to_allowed_slots = false;
             }
@@ -275,8 +496,8 @@ impl Tracer for FullValidationTracer {
 }
 
 impl FullValidationTracer {
-    const MAX_ALLOWED_SLOT_OFFSET: u32 = 127;
-
+    /// Creates a tracer. Calls to [EIP-7562 banned precompiles](eip7562_banned_precompiles) are treated as violations;
+    /// use [`Self::with_banned_precompiles()`] to change the banned set.
     pub fn new(params: ValidationParams, l1_batch_timestamp: u64) -> Self {
         let ValidationParams {
             user_address,
@@ -288,16 +509,71 @@ impl FullValidationTracer {
         } = params;
         Self {
             user_address,
+            derived_slots: derived_validation_slots(user_address),
             trusted_storage: trusted_slots,
             trusted_addresses,
             storage_containing_trusted_addresses: trusted_address_slots,
             l1_batch_timestamp,
             timestamp_asserter_params,
+            banned_precompiles: eip7562_banned_precompiles(),
 
             ..Self::default()
         }
     }
 
+    /// Adds a custom policy enforced in addition to the built-in rules. The callback is invoked
+    /// before each instruction executed during validation; if it returns a rule, validation is stopped
+    /// with this rule as the violation.
+    #[must_use]
+    pub fn with_violation_callback(
+        mut self,
+        callback: impl FnMut(&ViolationContext) -> Option<ViolatedValidationRule> + Send + 'static,
+    ) -> Self {
+        self.custom_policy = CustomPolicy(Some(Box::new(callback)));
+        self
+    }
+
+    /// Sets the policy for static calls to allowlisted contracts. By default, the policy is disabled.
+    #[must_use]
+    pub fn with_static_call_policy(mut self, static_call_policy: StaticCallPolicy) -> Self {
+        self.static_call_policy = static_call_policy;
+        self
+    }
+
+    /// Sets the maximum number of storage accesses (reads or writes) allowed during validation. By default,
+    /// the number of accesses is not limited.
+    #[must_use]
+    pub fn with_max_storage_accesses(mut self, max_storage_accesses: Option<usize>) -> Self {
+        self.max_storage_accesses = max_storage_accesses;
+        self
+    }
+
+    /// Sets precompiles that cannot be called during validation, replacing the [default ones](eip7562_banned_precompiles).
+    #[must_use]
+    pub fn with_banned_precompiles(mut self, banned_precompiles: HashSet<Address>) -> Self {
+        self.banned_precompiles = banned_precompiles;
+        self
+    }
+
+    /// Sets whether to continue validation after a violation, collecting all distinct violations
+    /// (see [`Self::all_violations()`]). Useful for debugging custom accounts. By default, validation is stopped
+    /// on the first violation.
+    ///
+    /// [`Self::validation_error()`] still returns the first violation if collection is enabled.
+    #[must_use]
+    pub fn with_collect_all(mut self, collect_all: bool) -> Self {
+        self.collect_all_violations = collect_all;
+        self
+    }
+
+    fn stop_on_violation(&self) -> ShouldStop {
+        if self.collect_all_violations {
+            ShouldStop::Continue
+        } else {
+            ShouldStop::Stop
+        }
+    }
+
     fn is_valid_storage_read(
         &self,
         address: Address,
@@ -307,8 +583,8 @@ impl FullValidationTracer {
     ) -> bool {
         // allow reading own slots
         address == self.user_address
-        // allow reading slot <own address>
-        || slot == address_to_u256(&self.user_address)
+        // allow reading slots derived from own address (incl. mapping-based slots)
+        || self.derived_slots.contains(slot)
         // some storage locations are always allowed
         || self.trusted_addresses.contains(&address)
         || self.trusted_storage.contains(&(address, slot))
@@ -321,20 +597,23 @@ impl FullValidationTracer {
         || address == SYSTEM_CONTEXT_ADDRESS && slot == U256::zero()
         // allow reading code hashes of existing contracts
         || address == ACCOUNT_CODE_STORAGE_ADDRESS && !value.is_zero()
-        // Allow mapping-based slots, accounting for the fact that the mapped data can occupy >1 slot.
-        || {
-            let min_slot = slot.saturating_sub(Self::MAX_ALLOWED_SLOT_OFFSET.into());
-            self.slots_obtained_via_keccak.range(min_slot..=slot).next().is_some()
-        }
         // allow TimestampAsserter to do its job
         || self.timestamp_asserter_params.as_ref()
             .map(|p| p.address == caller)
             .unwrap_or_default()
     }
 
-    fn set_error(&mut self, error: ViolatedValidationRule) {
+    fn set_error<S: GlobalStateInterface>(&mut self, state: &mut S, error: ViolatedValidationRule) {
         if self.validation_error.is_none() {
-            self.validation_error = Some(error);
+            self.validation_error = Some(error.clone());
+            let frame = state.current_frame();
+            self.violation_location = Some(ViolationLocation {
+                address: frame.address(),
+                pc: frame.program_counter(),
+            });
+            self.all_violations.push(error);
+        } else if self.collect_all_violations && !self.all_violations.contains(&error) {
+            self.all_violations.push(error);
         }
     }
 
@@ -342,7 +621,64 @@ impl FullValidationTracer {
         self.validation_error.clone()
     }
 
+    /// Converts the first validation violation into the halt reason reported to the user, or returns `None`
+    /// if validation didn't violate any rules. Exceeding the validation gas limit is reported as
+    /// [`Halt::ValidationOutOfGas`]; other violations are reported as [`Halt::ValidationFailed`]
+    /// with the violated rule as the message.
+    pub fn into_halt_reason(self) -> Option<Halt> {
+        self.halt_reason()
+    }
+
+    fn halt_reason(&self) -> Option<Halt> {
+        Some(match self.validation_error.as_ref()? {
+            ViolatedValidationRule::TookTooManyComputationalGas(_) => Halt::ValidationOutOfGas,
+            rule => Halt::ValidationFailed(VmRevertReason::General {
+                msg: rule.to_string(),
+                data: vec![],
+            }),
+        })
+    }
+
+    /// Returns the location of the instruction that caused the first validation violation.
+    /// Only set if [`Self::validation_error()`] is set.
+    pub fn violation_location(&self) -> Option<ViolationLocation> {
+        self.violation_location
+    }
+
+    /// Returns all distinct violations in the order they were encountered. Without [`Self::with_collect_all()`],
+    /// this contains at most 1 violation equal to [`Self::validation_error()`].
+    pub fn all_violations(&self) -> &[ViolatedValidationRule] {
+        &self.all_violations
+    }
+
+    /// Checks whether account validation was ever entered. Allows to distinguish between validation passing
+    /// and never running, since [`Self::validation_error()`] returns `None` in both cases.
+    pub fn validation_entered(&self) -> bool {
+        self.validation_entered
+    }
+
+    /// Returns the address of the account under validation, or `None` if account validation was never entered.
+    pub fn validated_account(&self) -> Option<Address> {
+        self.validation_entered.then_some(self.user_address)
+    }
+
+    /// Returns the number of times validation was exited without a preceding [`ValidationTracer::account_validation_entered()`]
+    /// call. A non-zero value indicates that bootloader hooks are wired incorrectly.
+    pub fn unmatched_validation_exits(&self) -> usize {
+        self.unmatched_validation_exits
+    }
+
     pub fn traces(&self) -> ValidationTraces {
         self.traces.clone()
     }
+
+    /// Returns the number of instructions executed during account validation.
+    pub fn validation_instruction_count(&self) -> u64 {
+        self.validation_instruction_count
+    }
+
+    /// Returns the number of storage accesses (reads and writes) made during validation.
+    pub fn storage_access_count(&self) -> usize {
+        self.storage_access_count
+    }
 }
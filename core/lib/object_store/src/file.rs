@@ -43,6 +43,7 @@ impl FileBackedObjectStore {
             Bucket::ProofsFri,
             Bucket::StorageSnapshot,
             Bucket::VmDumps,
+            Bucket::MerkleTree,
         ] {
             let bucket_path = base_dir.join(bucket.to_string());
             fs::create_dir_all(&bucket_path).await?;
@@ -21,6 +21,9 @@ pub enum Bucket {
     DataAvailability,
     VmDumps,
     PublicWitnessInputs,
+    /// Metadata of Merkle trees persisted in an object store rather than RocksDB (e.g., the tree manifest).
+    /// It is not produced by any other component, so it gets a separate bucket.
+    MerkleTree,
 }
 
 impl Bucket {
@@ -41,6 +44,7 @@ impl Bucket {
             Self::DataAvailability => "data_availability",
             Self::VmDumps => "vm_dumps",
             Self::PublicWitnessInputs => "public_witness_inputs",
+            Self::MerkleTree => "merkle_tree",
         }
     }
 }
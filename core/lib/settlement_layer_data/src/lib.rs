@@ -39,6 +39,7 @@ async fn get_l2_client(
     bridgehub_address: Address,
     l2_chain_id: L2ChainId,
     gateway_rpc_url: Option<SensitiveUrl>,
+    allow_default_url: bool,
 ) -> anyhow::Result<Option<Box<DynClient<L2>>>> {
     // If the server is the settlement layer, the gateway is the server itself,
     // so we can't point to ourselves.
@@ -47,7 +48,7 @@ async fn get_l2_client(
         .context("failed to call whitelistedSettlementLayers on the bridgehub contract")?;
 
     if !is_settlement_layer {
-        get_l2_client_unchecked(gateway_rpc_url, bridgehub_address).await
+        get_l2_client_unchecked(gateway_rpc_url, bridgehub_address, allow_default_url).await
     } else {
         Ok(None)
     }
@@ -56,10 +57,13 @@ async fn get_l2_client(
 async fn get_l2_client_unchecked(
     gateway_rpc_url: Option<SensitiveUrl>,
     l1_bridgehub_address: Address,
+    allow_default_url: bool,
 ) -> anyhow::Result<Option<Box<DynClient<L2>>>> {
     // If gateway rpc is not presented try to fallback to the default gateway url
     let gateway_rpc_url = if let Some(url) = gateway_rpc_url {
         Some(url)
+    } else if !allow_default_url {
+        None
     } else {
         gateway_urls::DefaultGatewayUrl::from_bridgehub_address(l1_bridgehub_address)
             .map(|a| a.to_gateway_url())
@@ -10,6 +10,7 @@ use zksync_config::configs::{
         chain::L2Contracts, ecosystem::L1SpecificContracts, SettlementLayerSpecificContracts,
     },
     eth_sender::SenderConfig,
+    networks::GatewayMode,
 };
 use zksync_contracts::getters_facet_contract;
 use zksync_dal::{
@@ -138,6 +139,7 @@ impl WiringLayer for SettlementLayerData<MainNodeConfig> {
                 .expect("Bridge Hub should be always presented"),
             self.config.l2_chain_id,
             self.config.gateway_rpc_url,
+            true,
         )
         .await?;
 
@@ -221,6 +223,9 @@ impl WiringLayer for SettlementLayerData<MainNodeConfig> {
 pub struct ENConfig {
     pub chain_id: L2ChainId,
     pub gateway_rpc_url: Option<SensitiveUrl>,
+    /// Explicit gateway mode. If not set, `gateway_rpc_url` is used if specified; otherwise,
+    /// the default gateway URL is inferred from the bridgehub address.
+    pub gateway_mode: Option<GatewayMode>,
 }
 
 impl SettlementLayerData<ENConfig> {
@@ -297,13 +302,41 @@ impl WiringLayer for SettlementLayerData<ENConfig> {
             .context("Error occured while getting current SL mode")?
         };
 
-        let l2_eth_client = get_l2_client(
-            &input.eth_client,
-            remote_config.l1_bridgehub_proxy_addr.unwrap(),
-            self.config.chain_id,
-            self.config.gateway_rpc_url,
-        )
-        .await?;
+        let l2_eth_client = match self.config.gateway_mode {
+            Some(GatewayMode::Disabled) => {
+                tracing::info!("Gateway is disabled in the node config");
+                if initial_sl_mode.is_gateway() {
+                    return Err(anyhow::anyhow!(
+                        "Chain settles on gateway ({initial_sl_mode:?}), but gateway is disabled in the node config"
+                    ))?;
+                }
+                None
+            }
+            Some(GatewayMode::Url) => {
+                let gateway_rpc_url = self
+                    .config
+                    .gateway_rpc_url
+                    .context("gateway mode is `url`, but gateway RPC URL is not specified")?;
+                get_l2_client(
+                    &input.eth_client,
+                    remote_config.l1_bridgehub_proxy_addr.unwrap(),
+                    self.config.chain_id,
+                    Some(gateway_rpc_url),
+                    false,
+                )
+                .await?
+            }
+            None => {
+                get_l2_client(
+                    &input.eth_client,
+                    remote_config.l1_bridgehub_proxy_addr.unwrap(),
+                    self.config.chain_id,
+                    self.config.gateway_rpc_url,
+                    true,
+                )
+                .await?
+            }
+        };
 
         let (client, bridgehub): (&dyn EthInterface, Address) = match initial_sl_mode {
             SettlementLayer::L1(_) => (
@@ -79,6 +79,27 @@ impl ManagedTasks {
         }
     }
 
+    /// Same as [`Self::complete()`], but aborts the tasks that haven't terminated within `timeout`.
+    /// Returns `true` if all tasks have terminated on their own.
+    pub async fn complete_or_abort(self, timeout: Duration) -> bool {
+        let abort_handles: Vec<_> = self
+            .task_handles
+            .iter()
+            .map(JoinHandle::abort_handle)
+            .collect();
+        if tokio::time::timeout(timeout, self.complete_inner())
+            .await
+            .is_ok()
+        {
+            return true;
+        }
+        tracing::warn!("Failed to terminate actors in {timeout:?}, aborting them");
+        for handle in abort_handles {
+            handle.abort();
+        }
+        false
+    }
+
     async fn complete_inner(self) {
         let futures = self.task_handles.into_iter().map(|fut| async move {
             match fut.await {
@@ -131,6 +152,38 @@ mod tests {
         assert_eq!(counter.load(Ordering::Relaxed), 5);
     }
 
+    #[tokio::test]
+    async fn aborting_tasks_on_completion_timeout() {
+        struct DropGuard(Arc<AtomicBool>);
+
+        impl Drop for DropGuard {
+            fn drop(&mut self) {
+                self.0.store(true, Ordering::Relaxed);
+            }
+        }
+
+        let is_dropped = Arc::new(AtomicBool::new(false));
+        let guard = DropGuard(is_dropped.clone());
+        let tasks = ManagedTasks::new(vec![tokio::spawn(async move {
+            let _guard = guard;
+            future::pending::<()>().await;
+            Ok(())
+        })]);
+        let completed = tasks.complete_or_abort(Duration::from_millis(50)).await;
+        assert!(!completed);
+
+        tokio::time::timeout(Duration::from_secs(1), async {
+            while !is_dropped.load(Ordering::Relaxed) {
+                tokio::task::yield_now().await;
+            }
+        })
+        .await
+        .expect("task was not aborted");
+
+        let tasks = ManagedTasks::new(vec![tokio::spawn(async { Ok(()) })]);
+        assert!(tasks.complete_or_abort(Duration::from_secs(1)).await);
+    }
+
     #[derive(Debug)]
     enum TaskTermination {
         Ok,
@@ -69,6 +69,19 @@ contract ValidationRuleBreaker is IAccount {
         } else if (typeOfRuleBreak == 10) {
             // Offset 1000 is too large to be recognized as belonging to an allowed mapping entry.
             require(mockToken.readAfterStats(address(this), 1000) == 0);
+        } else if (typeOfRuleBreak == 11) {
+            // Read storage of another contract unrelated to this account via `STATICCALL`.
+            // Only allowed if the contract is allowlisted for static calls.
+            (bool success, bytes memory data) = address(mockToken).staticcall(
+                abi.encodeCall(MockToken.getBalance, (address(0x10203)))
+            );
+            require(success && abi.decode(data, (uint256)) == 0);
+        } else if (typeOfRuleBreak == 12) {
+            // Same as above, but via a state-mutating call, which is never allowed.
+            (bool success, bytes memory data) = address(mockToken).call(
+                abi.encodeCall(MockToken.getBalance, (address(0x10203)))
+            );
+            require(success && abi.decode(data, (uint256)) == 0);
         }
 
         _validateTransaction(_suggestedSignedTxHash, _transaction);
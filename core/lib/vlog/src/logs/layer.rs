@@ -1,5 +1,9 @@
 use tracing::{span, Subscriber};
-use tracing_subscriber::{fmt, registry::LookupSpan, Layer};
+use tracing_subscriber::{
+    fmt::{self, writer::BoxMakeWriter},
+    registry::LookupSpan,
+    Layer,
+};
 
 /// Implementation of statically typed logs layer, which can be either plain or JSON.
 /// This is mostly required to avoid [boxing the layer][layer_box].
@@ -7,7 +11,7 @@ use tracing_subscriber::{fmt, registry::LookupSpan, Layer};
 /// [layer_box]: https://docs.rs/tracing-subscriber/latest/tracing_subscriber/layer/trait.Layer.html#method.boxed
 #[derive(Debug)]
 pub enum LogsLayer<S> {
-    Plain(fmt::Layer<S>),
+    Plain(PlainLayer<S>),
     Json(JsonLayer<S>),
 }
 
@@ -137,4 +141,12 @@ type JsonLayer<S> = tracing_subscriber::fmt::Layer<
         tracing_subscriber::fmt::format::Json,
         tracing_subscriber::fmt::time::UtcTime<time::format_description::well_known::Rfc3339>,
     >,
+    BoxMakeWriter,
+>;
+
+type PlainLayer<S> = fmt::Layer<
+    S,
+    fmt::format::DefaultFields,
+    fmt::format::Format<fmt::format::Full>,
+    BoxMakeWriter,
 >;
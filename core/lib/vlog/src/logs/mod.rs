@@ -1,10 +1,14 @@
 use std::{backtrace::Backtrace, str::FromStr};
 
-use tracing_subscriber::{fmt, registry::LookupSpan, EnvFilter, Layer};
+use tracing_subscriber::{
+    fmt::{self, writer::BoxMakeWriter},
+    registry::LookupSpan,
+    EnvFilter, Layer,
+};
 
 mod layer;
 
-/// Specifies the format of the logs in stdout.
+/// Specifies the format of the logs.
 #[derive(Debug, Clone, Copy, Default)]
 pub enum LogFormat {
     #[default]
@@ -29,6 +33,7 @@ pub struct Logs {
     format: LogFormat,
     log_directives: Option<String>,
     disable_default_logs: bool,
+    log_to_stderr: bool,
 }
 
 impl Logs {
@@ -37,6 +42,7 @@ impl Logs {
             format,
             log_directives: None,
             disable_default_logs: false,
+            log_to_stderr: false,
         }
     }
 
@@ -76,6 +82,12 @@ impl Logs {
         self
     }
 
+    /// Writes logs to stderr instead of stdout. Useful if stdout is reserved for the application output.
+    pub fn log_to_stderr(mut self) -> Self {
+        self.log_to_stderr = true;
+        self
+    }
+
     pub fn install_panic_hook(&self) {
         // Check whether we need to change the default panic handler.
         // Note that this must happen before we initialize Sentry, since otherwise
@@ -94,11 +106,17 @@ impl Logs {
         S: tracing::Subscriber + for<'span> LookupSpan<'span> + Send + Sync,
     {
         let filter = self.build_filter();
+        let writer = if self.log_to_stderr {
+            BoxMakeWriter::new(std::io::stderr)
+        } else {
+            BoxMakeWriter::new(std::io::stdout)
+        };
         let layer = match self.format {
-            LogFormat::Plain => layer::LogsLayer::Plain(fmt::Layer::new()),
+            LogFormat::Plain => layer::LogsLayer::Plain(fmt::Layer::new().with_writer(writer)),
             LogFormat::Json => {
                 let timer = tracing_subscriber::fmt::time::UtcTime::rfc_3339();
                 let json_layer = fmt::Layer::default()
+                    .with_writer(writer)
                     .with_file(true)
                     .with_line_number(true)
                     .with_timer(timer)
@@ -64,3 +64,14 @@ pub(super) fn report_vm_memory_metrics(
         .full
         .observe(memory_metrics.full_size() + storage_stats.cache_size);
 }
+
+#[derive(Debug, Metrics)]
+#[metrics(prefix = "oneshot_validation")]
+pub(super) struct ValidationMetrics {
+    /// Number of instructions executed during account validation of a single transaction.
+    #[metrics(buckets = Buckets::exponential(1.0..=10_000_000.0, 10.0))]
+    pub instructions: Histogram<u64>,
+}
+
+#[vise::register]
+pub(super) static VALIDATION_METRICS: vise::Global<ValidationMetrics> = vise::Global::new();
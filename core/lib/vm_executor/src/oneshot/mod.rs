@@ -352,9 +352,16 @@ fn validate_fast<S: ReadStorage>(
     validation_params: ValidationParams,
     batch_timestamp: u64,
 ) -> Result<ValidationTraces, ValidationError> {
+    // Besides the rules enforced by the legacy tracer, calls to EIP-7562 banned precompiles (0x0a..=0x11) are rejected
+    // by default; in the shadow mode, this matches the legacy tracer reporting such a call as a call to a contract
+    // with no code. Static call policy and storage access limit are not exposed in the node config; they keep
+    // their defaults (disabled / unlimited) and can only be set by custom tracer users (e.g., in tests).
     let validation = vm_fast::FullValidationTracer::new(validation_params, batch_timestamp);
     let mut tracer = ((), validation);
     let result_and_logs = vm.inspect(&mut tracer, InspectExecutionMode::OneTx);
+    metrics::VALIDATION_METRICS
+        .instructions
+        .observe(tracer.1.validation_instruction_count());
     if let Some(violation) = tracer.1.validation_error() {
         return Err(ValidationError::ViolatedRule(violation));
     }
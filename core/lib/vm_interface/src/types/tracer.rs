@@ -83,6 +83,13 @@ pub enum ViolatedValidationRule {
     TookTooManyComputationalGas(u32),
     /// The transaction failed block.timestamp assertion because the block.timestamp is too close to the range end
     TimestampAssertionCloseToRangeEnd,
+    /// The transaction called a contract that may only be called via `STATICCALL` during validation
+    /// with a state-mutating call.
+    NonStaticCallToStaticOnlyContract(Address),
+    /// The transaction accessed storage more times during validation than allowed by the limit.
+    TooManyStorageAccesses(usize),
+    /// The transaction called a precompile banned during validation.
+    CalledBannedPrecompile(Address),
 }
 
 impl fmt::Display for ViolatedValidationRule {
@@ -107,6 +114,18 @@ impl fmt::Display for ViolatedValidationRule {
             ViolatedValidationRule::TimestampAssertionCloseToRangeEnd => {
                 write!(f, "block.timestamp is too close to the range end")
             }
+            ViolatedValidationRule::NonStaticCallToStaticOnlyContract(contract) => {
+                write!(
+                    f,
+                    "Called static-only contract with a non-static call: {contract:x}"
+                )
+            }
+            ViolatedValidationRule::TooManyStorageAccesses(limit) => {
+                write!(f, "Accessed storage too many times, allowed limit: {limit}")
+            }
+            ViolatedValidationRule::CalledBannedPrecompile(precompile) => {
+                write!(f, "Called banned precompile: {precompile:x}")
+            }
         }
     }
 }
@@ -278,11 +278,38 @@ impl CheckDivergence for Result<ValidationTraces, ValidationError> {
             return errors;
         }
 
+        if let (
+            Err(ValidationError::ViolatedRule(main_rule)),
+            Err(ValidationError::ViolatedRule(shadow_rule)),
+        ) = (self, other)
+        {
+            if is_banned_precompile_call_in_legacy_vm(main_rule, shadow_rule)
+                || is_banned_precompile_call_in_legacy_vm(shadow_rule, main_rule)
+            {
+                return errors;
+            }
+        }
+
         errors.check_match("validation result", self, other);
         errors
     }
 }
 
+/// The legacy VM doesn't ban any precompiles during validation. Since precompiles have no code, a call to a banned precompile
+/// is reported by it as a call to a contract with no code, while the fast VM reports the same call as [`ViolatedValidationRule::CalledBannedPrecompile`].
+fn is_banned_precompile_call_in_legacy_vm(
+    legacy_rule: &ViolatedValidationRule,
+    fast_rule: &ViolatedValidationRule,
+) -> bool {
+    matches!(
+        (legacy_rule, fast_rule),
+        (
+            ViolatedValidationRule::CalledContractWithNoCode(legacy_address),
+            ViolatedValidationRule::CalledBannedPrecompile(fast_address),
+        ) if legacy_address == fast_address
+    )
+}
+
 /// `PartialEq` for `Call` doesn't compare gas-related fields. Here, we do compare them.
 #[derive(Debug, PartialEq)]
 struct StrictCall<'a> {
@@ -745,3 +772,59 @@ where
         self.main.pop_front_snapshot_no_rollback();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn violated_rule(rule: ViolatedValidationRule) -> Result<ValidationTraces, ValidationError> {
+        Err(ValidationError::ViolatedRule(rule))
+    }
+
+    #[test]
+    fn banned_precompile_call_does_not_diverge_from_call_without_code() {
+        let precompile = Address::from_low_u64_be(0x0a);
+        let legacy_result =
+            violated_rule(ViolatedValidationRule::CalledContractWithNoCode(precompile));
+        let fast_result = violated_rule(ViolatedValidationRule::CalledBannedPrecompile(precompile));
+
+        legacy_result
+            .check_divergence(&fast_result)
+            .into_result()
+            .unwrap();
+        fast_result
+            .check_divergence(&legacy_result)
+            .into_result()
+            .unwrap();
+    }
+
+    #[test]
+    fn banned_precompile_call_diverges_from_call_without_code_to_other_address() {
+        let legacy_result = violated_rule(ViolatedValidationRule::CalledContractWithNoCode(
+            Address::repeat_byte(0x23),
+        ));
+        let fast_result = violated_rule(ViolatedValidationRule::CalledBannedPrecompile(
+            Address::from_low_u64_be(0x0a),
+        ));
+
+        let err = legacy_result
+            .check_divergence(&fast_result)
+            .into_result()
+            .unwrap_err();
+        assert!(err.to_string().contains("validation result"), "{err}");
+    }
+
+    #[test]
+    fn banned_precompile_call_diverges_from_successful_validation() {
+        let legacy_result = Ok(ValidationTraces::default());
+        let fast_result = violated_rule(ViolatedValidationRule::CalledBannedPrecompile(
+            Address::from_low_u64_be(0x0a),
+        ));
+
+        let err = legacy_result
+            .check_divergence(&fast_result)
+            .into_result()
+            .unwrap_err();
+        assert!(err.to_string().contains("validation result"), "{err}");
+    }
+}
@@ -147,6 +147,19 @@ impl<Net: Network> Client<Net> {
         let client = HttpClientBuilder::default().build(url.expose_str())?;
         Ok(ClientBuilder::new(client, url))
     }
+
+    /// Creates an HTTP-backed client with the specified timeout for each request.
+    pub fn http_with_timeout(
+        url: SensitiveUrl,
+        request_timeout: Duration,
+    ) -> anyhow::Result<ClientBuilder<Net>> {
+        crate::client::rustls::set_rustls_backend_if_required();
+
+        let client = HttpClientBuilder::default()
+            .request_timeout(request_timeout)
+            .build(url.expose_str())?;
+        Ok(ClientBuilder::new(client, url))
+    }
 }
 
 impl<Net: Network> WsClient<Net> {
@@ -1,4 +1,4 @@
-use std::{num::NonZeroUsize, sync::Arc};
+use std::{num::NonZeroUsize, sync::Arc, time::Duration};
 
 use anyhow::Context;
 use async_trait::async_trait;
@@ -20,6 +20,7 @@ pub struct MainNodeClientLayer {
     url: SensitiveUrl,
     rate_limit_rps: NonZeroUsize,
     l2_chain_id: L2ChainId,
+    request_timeout: Option<Duration>,
 }
 
 #[derive(Debug, FromContext)]
@@ -39,8 +40,16 @@ impl MainNodeClientLayer {
             url,
             rate_limit_rps,
             l2_chain_id,
+            request_timeout: None,
         }
     }
+
+    /// Sets the timeout for each request to the main node. If not set, the client default is used.
+    #[must_use]
+    pub fn with_request_timeout(mut self, timeout: Option<Duration>) -> Self {
+        self.request_timeout = timeout;
+        self
+    }
 }
 
 #[async_trait::async_trait]
@@ -53,7 +62,11 @@ impl WiringLayer for MainNodeClientLayer {
     }
 
     async fn wire(self, input: Self::Input) -> Result<Self::Output, WiringError> {
-        let main_node_client = Client::http(self.url)
+        let builder = match self.request_timeout {
+            Some(timeout) => Client::http_with_timeout(self.url, timeout),
+            None => Client::http(self.url),
+        };
+        let main_node_client = builder
             .context("failed creating JSON-RPC client for main node")?
             .for_network(self.l2_chain_id.into())
             .with_allowed_requests_per_second(self.rate_limit_rps)
@@ -3,13 +3,14 @@
 // NOTE:
 // main_contract.getTotalBatchesCommitted actually checks the number of batches committed.
 // main_contract.getTotalBatchesExecuted actually checks the number of batches executed.
-import { beforeAll, describe, it } from 'vitest';
+import { afterEach, beforeAll, describe, it } from 'vitest';
 import { createChainAndStartServer, TESTED_CHAIN_TYPE, TestChain, getMainWalletPk } from '../src';
 import * as utils from 'utils';
 import {
     checkRandomTransfer,
     executeDepositAfterRevert,
     executeRevert,
+    printBatchTailOnFailure,
     revertExternalNode,
     waitToCommitBatchesWithoutExecution
 } from './revert-utils';
@@ -117,6 +118,13 @@ describe('Block reverting test', function () {
             : IZkSyncHyperchain__factory.connect(contractsConfig.l1.diamond_proxy_addr, l1Provider);
     });
 
+    afterEach(async ({ task }) => {
+        await printBatchTailOnFailure(task.result?.state === 'fail', [
+            { name: 'main node', provider: mainNodeTester.web3Provider },
+            { name: 'external node', provider: externalNodeTester.web3Provider }
+        ]);
+    });
+
     it('EN revert test', async () => {
         console.log('Funding wallets');
         await mainNodeTester.fundSyncWallet();
@@ -0,0 +1,107 @@
+import { afterEach, describe, expect, it, vi } from 'vitest';
+import { BatchTailNode, printBatchTailOnFailure, selectRevertTargetBatch, tailBatchesCount } from './revert-utils';
+
+function mockNode(name: string, lastBatch: number): BatchTailNode {
+    return {
+        name,
+        provider: {
+            getL1BatchNumber: async () => lastBatch,
+            getL1BatchDetails: async (number: number) => ({
+                number,
+                status: number < lastBatch ? 'verified' : 'sealed',
+                commitTxHash: number < lastBatch ? '0x01' : null,
+                executeTxHash: number < lastBatch - 1 ? '0x02' : null
+            })
+        }
+    };
+}
+
+describe('Selecting the target batch for revert', () => {
+    it('reverts to the last executed batch if the target is unset', () => {
+        expect(selectRevertTargetBatch(5n, 8n, undefined)).toBe(5n);
+        expect(selectRevertTargetBatch(5n, 8n, '')).toBe(5n);
+    });
+
+    it('uses the target batch if it is in range', () => {
+        expect(selectRevertTargetBatch(5n, 8n, '5')).toBe(5n);
+        expect(selectRevertTargetBatch(5n, 8n, '7')).toBe(7n);
+    });
+
+    it('rejects out-of-range target batches', () => {
+        expect(() => selectRevertTargetBatch(5n, 8n, '8')).toThrow('must be below the last committed batch');
+        expect(() => selectRevertTargetBatch(5n, 8n, '100')).toThrow('must be below the last committed batch');
+        expect(() => selectRevertTargetBatch(5n, 8n, '4')).toThrow('is already executed');
+    });
+
+    it('rejects malformed target batches', () => {
+        expect(() => selectRevertTargetBatch(5n, 8n, '-1')).toThrow('Invalid target batch');
+        expect(() => selectRevertTargetBatch(5n, 8n, 'latest')).toThrow('Invalid target batch');
+    });
+});
+
+describe('Printing recent batches on revert test failure', () => {
+    afterEach(() => {
+        vi.unstubAllEnvs();
+    });
+
+    it('parses the number of batches to print', () => {
+        expect(tailBatchesCount(undefined)).toBe(0);
+        expect(tailBatchesCount('')).toBe(0);
+        expect(tailBatchesCount('5')).toBe(5);
+        expect(() => tailBatchesCount('-1')).toThrow('Invalid number of batches');
+    });
+
+    it('prints batches from all nodes on simulated failure', async () => {
+        const output: string[] = [];
+        const failingNode: BatchTailNode = {
+            name: 'external node',
+            provider: {
+                getL1BatchNumber: async () => {
+                    throw new Error('connection refused');
+                },
+                getL1BatchDetails: async () => {
+                    throw new Error('unreachable');
+                }
+            }
+        };
+        await printBatchTailOnFailure(true, [mockNode('main node', 10), failingNode], 3, (message) =>
+            output.push(message)
+        );
+
+        expect(output).toEqual([
+            'Last 3 L1 batches on main node:\n  #8: verified, executed\n  #9: verified, committed\n  #10: sealed',
+            'Failed getting last 3 L1 batches on external node: Error: connection refused'
+        ]);
+    });
+
+    it('prints the number of batches set via env', async () => {
+        vi.stubEnv('REVERT_TAIL_BATCHES', '2');
+        const output: string[] = [];
+        // The count is not passed explicitly, so it's read from `REVERT_TAIL_BATCHES`.
+        await printBatchTailOnFailure(true, [mockNode('main node', 10)], undefined, (message) =>
+            output.push(message)
+        );
+        expect(output).toEqual(['Last 2 L1 batches on main node:\n  #9: verified, committed\n  #10: sealed']);
+    });
+
+    it('prints nothing if the number of batches is not set', async () => {
+        vi.stubEnv('REVERT_TAIL_BATCHES', '');
+        const output: string[] = [];
+        await printBatchTailOnFailure(true, [mockNode('main node', 10)], undefined, (message) =>
+            output.push(message)
+        );
+        expect(output).toEqual([]);
+    });
+
+    it('prints nothing on success', async () => {
+        const output: string[] = [];
+        await printBatchTailOnFailure(false, [mockNode('main node', 10)], 3, (message) => output.push(message));
+        expect(output).toEqual([]);
+    });
+
+    it('does not go below the genesis batch', async () => {
+        const output: string[] = [];
+        await printBatchTailOnFailure(true, [mockNode('main node', 1)], 5, (message) => output.push(message));
+        expect(output).toEqual(['Last 5 L1 batches on main node:\n  #0: verified, committed\n  #1: sealed']);
+    });
+});
@@ -34,6 +34,111 @@ export function parseSuggestedValues(jsonString: string): SuggestedValues {
     };
 }
 
+/**
+ * Returns the L1 batch to revert to. By default, this is the last executed batch; it can be overridden
+ * with the `REVERT_TARGET_BATCH` env variable to reproduce reverts to a specific batch.
+ */
+export function selectRevertTargetBatch(
+    lastExecutedL1BatchNumber: bigint,
+    batchesCommittedBeforeRevert: bigint,
+    targetBatchOverride: string | undefined = process.env.REVERT_TARGET_BATCH
+): bigint {
+    if (targetBatchOverride === undefined || targetBatchOverride === '') {
+        return lastExecutedL1BatchNumber;
+    }
+    if (!/^\d+$/.test(targetBatchOverride)) {
+        throw new Error(`Invalid target batch for revert: ${targetBatchOverride}`);
+    }
+
+    const targetBatch = BigInt(targetBatchOverride);
+    if (targetBatch >= batchesCommittedBeforeRevert) {
+        throw new Error(
+            `Target batch for revert (${targetBatch}) must be below the last committed batch (${batchesCommittedBeforeRevert})`
+        );
+    }
+    if (targetBatch < lastExecutedL1BatchNumber) {
+        throw new Error(
+            `Target batch for revert (${targetBatch}) is already executed and cannot be reverted; ` +
+                `the last executed batch is ${lastExecutedL1BatchNumber}`
+        );
+    }
+    return targetBatch;
+}
+
+/** L1 batch details used to print recent batch history; implemented by `zksync.Provider`. */
+interface BatchDetailsSource {
+    getL1BatchNumber(): Promise<number>;
+    getL1BatchDetails(number: number): Promise<{
+        number: number;
+        status: string;
+        commitTxHash?: string | null;
+        proveTxHash?: string | null;
+        executeTxHash?: string | null;
+    }>;
+}
+
+/** Node queried for recent batch history if the test fails. */
+export interface BatchTailNode {
+    name: string;
+    provider: BatchDetailsSource;
+}
+
+/**
+ * Returns the number of recent L1 batches to print if the test fails, as set by the `REVERT_TAIL_BATCHES` env variable.
+ * Returns 0 (i.e., nothing is printed) if the variable is not set.
+ */
+export function tailBatchesCount(tailBatches: string | undefined = process.env.REVERT_TAIL_BATCHES): number {
+    if (tailBatches === undefined || tailBatches === '') {
+        return 0;
+    }
+    if (!/^\d+$/.test(tailBatches)) {
+        throw new Error(`Invalid number of batches to print on failure: ${tailBatches}`);
+    }
+    return parseInt(tailBatches, 10);
+}
+
+/** Returns lines describing the last `count` L1 batches known to the node, from the oldest to the newest one. */
+export async function fetchBatchTail(provider: BatchDetailsSource, count: number): Promise<string[]> {
+    const lastBatch = await provider.getL1BatchNumber();
+    const lines = [];
+    for (let batchNumber = Math.max(lastBatch - count + 1, 0); batchNumber <= lastBatch; batchNumber++) {
+        const details = await provider.getL1BatchDetails(batchNumber);
+        let status = details.status;
+        if (details.executeTxHash) {
+            status += ', executed';
+        } else if (details.proveTxHash) {
+            status += ', proven';
+        } else if (details.commitTxHash) {
+            status += ', committed';
+        }
+        lines.push(`#${details.number}: ${status}`);
+    }
+    return lines;
+}
+
+/**
+ * Prints the last `count` L1 batches for each of the `nodes` if the test has failed; does nothing otherwise.
+ * Errors querying a node (e.g., one that was killed by the test) are printed instead of its batches.
+ */
+export async function printBatchTailOnFailure(
+    failed: boolean,
+    nodes: BatchTailNode[],
+    count: number = tailBatchesCount(),
+    log: (message: string) => void = console.log
+) {
+    if (!failed || count === 0) {
+        return;
+    }
+    for (const { name, provider } of nodes) {
+        try {
+            const lines = await fetchBatchTail(provider, count);
+            log(`Last ${count} L1 batches on ${name}:\n${lines.map((line) => `  ${line}`).join('\n')}`);
+        } catch (err) {
+            log(`Failed getting last ${count} L1 batches on ${name}: ${err}`);
+        }
+    }
+}
+
 async function runBlockReverter(pathToHome: string, chain: string, args: string[]) {
     const configPaths = getAllConfigsPath({ pathToHome, chain });
     const configsFlags = [
@@ -91,14 +196,15 @@ export async function executeRevert(
         values.lastExecutedL1BatchNumber < batchesCommittedBeforeRevert,
         'There should be at least one block for revert'
     );
+    const targetBatch = selectRevertTargetBatch(values.lastExecutedL1BatchNumber, batchesCommittedBeforeRevert);
 
-    utils.log('Reverting with parameters', values);
+    utils.log('Reverting with parameters', values, `target batch: ${targetBatch}`);
 
     utils.log('Sending ETH transaction..');
     await runBlockReverter(pathToHome, chain, [
         'send-eth-transaction',
         '--l1-batch-number',
-        values.lastExecutedL1BatchNumber.toString(),
+        targetBatch.toString(),
         '--nonce',
         values.nonce.toString(),
         '--priority-fee-per-gas',
@@ -109,7 +215,7 @@ export async function executeRevert(
     await runBlockReverter(pathToHome, chain, [
         'rollback-db',
         '--l1-batch-number',
-        values.lastExecutedL1BatchNumber.toString(),
+        targetBatch.toString(),
         '--rollback-postgres',
         '--rollback-tree',
         '--rollback-sk-cache',
@@ -117,8 +223,8 @@ export async function executeRevert(
     ]);
 
     const blocksCommitted = await mainContract.getTotalBatchesCommitted();
-    assert(blocksCommitted === values.lastExecutedL1BatchNumber, 'Revert on contract was unsuccessful');
-    return values.lastExecutedL1BatchNumber;
+    assert(blocksCommitted === targetBatch, 'Revert on contract was unsuccessful');
+    return targetBatch;
 }
 
 export async function waitToCommitBatchesWithoutExecution(mainContract: IZkSyncHyperchain): Promise<bigint> {
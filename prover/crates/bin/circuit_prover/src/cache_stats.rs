@@ -0,0 +1,88 @@
+use std::time::Duration;
+
+use tokio_util::sync::CancellationToken;
+use zksync_circuit_prover_service::{setup_cache_lookup_stats, SetupCache};
+
+use crate::PROVER_BINARY_METRICS;
+
+/// Periodically logs and reports sizes of setup data & finalization hints caches, together with
+/// the number of lookups in them.
+#[derive(Debug)]
+pub struct CacheStatsReporter {
+    setup_data_entries: usize,
+    finalization_hints_entries: usize,
+    interval: Duration,
+    cancellation_token: CancellationToken,
+}
+
+impl CacheStatsReporter {
+    pub fn new(
+        setup_data_entries: usize,
+        finalization_hints_entries: usize,
+        interval: Duration,
+        cancellation_token: CancellationToken,
+    ) -> Self {
+        Self {
+            setup_data_entries,
+            finalization_hints_entries,
+            interval,
+            cancellation_token,
+        }
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        while !self.cancellation_token.is_cancelled() {
+            self.report();
+            // Error here corresponds to a timeout w/o receiving cancel; we're OK with this.
+            tokio::time::timeout(self.interval, self.cancellation_token.cancelled())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop request received, shutting down cache stats reporter...");
+        Ok(())
+    }
+
+    fn report(&self) {
+        let caches = [
+            (SetupCache::SetupData, self.setup_data_entries),
+            (
+                SetupCache::FinalizationHints,
+                self.finalization_hints_entries,
+            ),
+        ];
+        for (cache, entries) in caches {
+            PROVER_BINARY_METRICS.setup_cache_entries[&cache].set(entries);
+            let stats = setup_cache_lookup_stats(cache);
+            tracing::info!(
+                "Cache {cache:?} has {entries} entries; lookups: {} hits, {} misses",
+                stats.hits,
+                stats.misses
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test(start_paused = true)]
+    async fn reporter_reports_cache_sizes_until_cancelled() {
+        let cancellation_token = CancellationToken::new();
+        let reporter =
+            CacheStatsReporter::new(3, 5, Duration::from_secs(60), cancellation_token.clone());
+        let reporter_task = tokio::spawn(reporter.run());
+        tokio::time::sleep(Duration::from_secs(90)).await;
+
+        let entries = |cache| PROVER_BINARY_METRICS.setup_cache_entries[&cache].get();
+        assert_eq!(entries(SetupCache::SetupData), 3);
+        assert_eq!(entries(SetupCache::FinalizationHints), 5);
+
+        cancellation_token.cancel();
+        tokio::time::timeout(Duration::from_secs(1), reporter_task)
+            .await
+            .expect("reporter didn't stop on cancellation")
+            .unwrap()
+            .unwrap();
+    }
+}
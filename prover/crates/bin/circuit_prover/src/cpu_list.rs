@@ -0,0 +1,70 @@
+use std::{fmt, str::FromStr};
+
+use anyhow::Context as _;
+
+/// List of CPU cores in the Linux `cpulist` format, e.g. `0-3,8,10-11`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CpuList(pub Vec<usize>);
+
+impl FromStr for CpuList {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut cores = vec![];
+        for part in s.split(',').map(str::trim) {
+            anyhow::ensure!(!part.is_empty(), "empty entry in CPU list `{s}`");
+            if let Some((start, end)) = part.split_once('-') {
+                let start: usize = start
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("invalid CPU range `{part}`"))?;
+                let end: usize = end
+                    .trim()
+                    .parse()
+                    .with_context(|| format!("invalid CPU range `{part}`"))?;
+                anyhow::ensure!(start <= end, "CPU range `{part}` is reversed");
+                cores.extend(start..=end);
+            } else {
+                let core = part
+                    .parse()
+                    .with_context(|| format!("invalid CPU core `{part}`"))?;
+                cores.push(core);
+            }
+        }
+        Ok(Self(cores))
+    }
+}
+
+impl fmt::Display for CpuList {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (i, core) in self.0.iter().enumerate() {
+            if i > 0 {
+                formatter.write_str(",")?;
+            }
+            write!(formatter, "{core}")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_cpu_list() {
+        let list: CpuList = "0-3,8, 10-11".parse().unwrap();
+        assert_eq!(list.0, [0, 1, 2, 3, 8, 10, 11]);
+        assert_eq!(list.to_string(), "0,1,2,3,8,10,11");
+        let list: CpuList = "5".parse().unwrap();
+        assert_eq!(list.0, [5]);
+    }
+
+    #[test]
+    fn parsing_invalid_cpu_list() {
+        for raw in ["", "1,,2", "a", "3-1", "1-", "-1"] {
+            let err = raw.parse::<CpuList>().unwrap_err();
+            assert!(err.to_string().contains(raw), "{raw}: {err}");
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use anyhow::Context as _;
+use zksync_prover_fri_types::circuit_definitions::boojum::cs::implementations::setup::FinalizationHintsForProver;
+
+use crate::FinalizationHintsCache;
+
+/// Runs cheap structural checks over all loaded finalization hints.
+/// Fails on the first inconsistent hint, naming the circuit it belongs to.
+pub fn validate_finalization_hints(cache: &FinalizationHintsCache) -> anyhow::Result<()> {
+    // Sort keys to make validation (and the reported failure) deterministic.
+    let mut keys: Vec<_> = cache.keys().collect();
+    keys.sort_by_key(|key| (key.stage as u8, key.circuit_id));
+    for key in keys {
+        validate_finalization_hint(&cache[key]).with_context(|| {
+            format!(
+                "invalid finalization hints for circuit {} at stage {:?}",
+                key.circuit_id, key.stage
+            )
+        })?;
+    }
+    Ok(())
+}
+
+/// Checks that a single finalization hint is internally consistent.
+pub fn validate_finalization_hint(hint: &FinalizationHintsForProver) -> anyhow::Result<()> {
+    let trace_len = hint.final_trace_len;
+    anyhow::ensure!(
+        trace_len.is_power_of_two(),
+        "final trace length {trace_len} is not a non-zero power of 2"
+    );
+    anyhow::ensure!(
+        hint.nop_gates_to_add <= trace_len,
+        "number of nop gates to add ({}) exceeds final trace length {trace_len}",
+        hint.nop_gates_to_add
+    );
+    anyhow::ensure!(!hint.public_inputs.is_empty(), "no public inputs");
+    for &(column, row) in &hint.public_inputs {
+        anyhow::ensure!(
+            row < trace_len,
+            "public input at column {column} has row {row} outside of trace (length {trace_len})"
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_prover_fri_types::ProverServiceDataKey;
+    use zksync_prover_keystore::keystore::Keystore;
+
+    use super::*;
+
+    fn load_hint() -> FinalizationHintsForProver {
+        Keystore::locate()
+            .load_finalization_hints(ProverServiceDataKey::new_basic(1))
+            .unwrap()
+    }
+
+    #[test]
+    fn valid_hint_passes() {
+        validate_finalization_hint(&load_hint()).unwrap();
+    }
+
+    #[test]
+    fn broken_hints_are_rejected() {
+        let mut hint = load_hint();
+        hint.final_trace_len = 0;
+        validate_finalization_hint(&hint).unwrap_err();
+
+        let mut hint = load_hint();
+        hint.public_inputs.clear();
+        validate_finalization_hint(&hint).unwrap_err();
+
+        let mut hint = load_hint();
+        hint.public_inputs[0].1 = hint.final_trace_len;
+        validate_finalization_hint(&hint).unwrap_err();
+    }
+}
@@ -0,0 +1,106 @@
+use std::{fmt, str::FromStr};
+
+/// Maximum length of an instance label; matches the Kubernetes limit for label values.
+const MAX_LABEL_LEN: usize = 63;
+/// Label used by [`InstanceLabel::sanitize()`] if nothing remains of the raw value.
+const FALLBACK_LABEL: &str = "unknown";
+
+/// Operator-supplied label identifying a prover instance in metrics, e.g. the pod name.
+///
+/// The label is restricted to ASCII alphanumeric chars, `-`, `_` and `.`, so that it can be used
+/// both as a Prometheus label value and as a path segment in the push gateway URL.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InstanceLabel(String);
+
+impl InstanceLabel {
+    /// Returns the label value.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Converts an arbitrary string (e.g., a pod name) into a valid label, replacing invalid chars with `_`
+    /// and truncating it to the max label length. An empty string is converted to `unknown`.
+    pub fn sanitize(raw: &str) -> Self {
+        let label: String = raw
+            .chars()
+            .map(|ch| if Self::is_valid_char(ch) { ch } else { '_' })
+            .take(MAX_LABEL_LEN)
+            .collect();
+        if label.is_empty() {
+            Self(FALLBACK_LABEL.to_owned())
+        } else {
+            Self(label)
+        }
+    }
+
+    fn is_valid_char(ch: char) -> bool {
+        ch.is_ascii_alphanumeric() || matches!(ch, '-' | '_' | '.')
+    }
+}
+
+impl FromStr for InstanceLabel {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        anyhow::ensure!(!s.is_empty(), "instance label is empty");
+        anyhow::ensure!(
+            s.len() <= MAX_LABEL_LEN,
+            "instance label `{s}` is too long ({} chars; max {MAX_LABEL_LEN})",
+            s.len()
+        );
+        if let Some(ch) = s.chars().find(|&ch| !Self::is_valid_char(ch)) {
+            anyhow::bail!("instance label `{s}` contains invalid char {ch:?}");
+        }
+        Ok(Self(s.to_owned()))
+    }
+}
+
+impl fmt::Display for InstanceLabel {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        formatter.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_instance_label() {
+        for raw in ["circuit-prover-gpu-0", "UNKNOWN_POD", "prover.eu-1"] {
+            let label: InstanceLabel = raw.parse().unwrap();
+            assert_eq!(label.as_str(), raw);
+            assert_eq!(label.to_string(), raw);
+        }
+    }
+
+    #[test]
+    fn parsing_invalid_instance_label() {
+        let err = "".parse::<InstanceLabel>().unwrap_err();
+        assert!(err.to_string().contains("empty"), "{err}");
+        let err = "a".repeat(64).parse::<InstanceLabel>().unwrap_err();
+        assert!(err.to_string().contains("too long"), "{err}");
+        for raw in ["prover/0", "prover 0", "prover\"0", "прувер"] {
+            let err = raw.parse::<InstanceLabel>().unwrap_err();
+            assert!(err.to_string().contains("invalid char"), "{raw}: {err}");
+        }
+    }
+
+    #[test]
+    fn sanitizing_instance_label() {
+        let label = InstanceLabel::sanitize("circuit-prover-gpu-0");
+        assert_eq!(label.as_str(), "circuit-prover-gpu-0");
+        let label = InstanceLabel::sanitize("prover/0 (eu)");
+        assert_eq!(label.as_str(), "prover_0__eu_");
+        let label = InstanceLabel::sanitize(&"a".repeat(100));
+        assert_eq!(label.as_str(), "a".repeat(MAX_LABEL_LEN));
+        let label = InstanceLabel::sanitize("");
+        assert_eq!(label.as_str(), "unknown");
+
+        // Sanitized labels are always valid.
+        for raw in ["прувер", "prover\"0", ""] {
+            let label = InstanceLabel::sanitize(raw);
+            assert_eq!(label.as_str().parse::<InstanceLabel>().unwrap(), label);
+        }
+    }
+}
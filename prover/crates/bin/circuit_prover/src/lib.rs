@@ -1,5 +1,23 @@
-pub use metrics::PROVER_BINARY_METRICS;
+pub use cache_stats::CacheStatsReporter;
+pub use cpu_list::CpuList;
+pub use finalization_hints::{validate_finalization_hint, validate_finalization_hints};
+pub use instance_label::InstanceLabel;
+pub use metrics::{InstanceInfo, StartupStage, PROVER_BINARY_METRICS};
+pub use profiler::Profiler;
+pub use remote_config::{fetch_config, ConfigLocation};
+pub use snapshot::{hash_source_files, CacheSnapshot};
+pub use startup_summary::{GpuSummary, StartupSummary, StartupSummaryFormat, WvgSummary};
 pub use types::{FinalizationHintsCache, SetupDataCache};
+pub use wvg_balancer::{WvgBalancer, WvgBounds};
 
+mod cache_stats;
+mod cpu_list;
+mod finalization_hints;
+mod instance_label;
 mod metrics;
+mod profiler;
+mod remote_config;
+mod snapshot;
+mod startup_summary;
 mod types;
+mod wvg_balancer;
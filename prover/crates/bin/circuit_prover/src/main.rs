@@ -1,5 +1,6 @@
 use std::{
-    path::PathBuf,
+    fmt, fs, io, iter,
+    path::{Path, PathBuf},
     sync::Arc,
     time::{Duration, Instant},
 };
@@ -7,22 +8,38 @@ use std::{
 use anyhow::Context as _;
 use clap::Parser;
 use shivini::{ProverContext, ProverContextConfig};
-use tokio::sync::oneshot;
+use smart_config::{ConfigSchema, DescribeConfig};
+use tokio::{sync::oneshot, task::JoinHandle};
 use tokio_util::sync::CancellationToken;
-use zksync_circuit_prover::{FinalizationHintsCache, SetupDataCache, PROVER_BINARY_METRICS};
-use zksync_circuit_prover_service::job_runner::{circuit_prover_runner, WvgRunnerBuilder};
+use zksync_circuit_prover::{
+    fetch_config, hash_source_files, CacheSnapshot, CacheStatsReporter, ConfigLocation, CpuList,
+    FinalizationHintsCache, GpuSummary, InstanceInfo, InstanceLabel, Profiler, SetupDataCache,
+    StartupStage, StartupSummary, StartupSummaryFormat, WvgBalancer, WvgSummary,
+    PROVER_BINARY_METRICS,
+};
+use zksync_circuit_prover_service::{
+    job_runner::{circuit_prover_runner, WvgRunnerBuilder},
+    UnservableCircuits, WvgCpuAffinity, CIRCUIT_PROVER_METRICS,
+};
 use zksync_config::{
-    configs::{GeneralConfig, PostgresSecrets},
+    configs::{fri_prover::FriProverConfig, GeneralConfig, PostgresSecrets},
     full_config_schema,
-    sources::ConfigFilePaths,
+    sources::{ConfigFilePaths, ConfigSources},
     ObjectStoreConfig,
 };
 use zksync_object_store::{ObjectStore, ObjectStoreFactory};
 use zksync_prover_dal::{ConnectionPool, Prover};
-use zksync_prover_fri_types::PROVER_PROTOCOL_SEMANTIC_VERSION;
-use zksync_prover_keystore::keystore::Keystore;
+use zksync_prover_fri_types::{
+    get_current_pod_name, ProverServiceDataKey, PROVER_PROTOCOL_SEMANTIC_VERSION,
+};
+use zksync_prover_job_processor::{Backoff, JobRunnerTasks, WorkerScaler};
+use zksync_prover_keystore::keystore::{Keystore, DEFAULT_LOAD_CONCURRENCY, MAX_LOAD_CONCURRENCY};
 use zksync_task_management::ManagedTasks;
+use zksync_types::url::SensitiveUrl;
+use zksync_vlog::prometheus::PrometheusExporterConfig;
 
+/// Default for the total graceful shutdown timeout.
+///
 /// On most commodity hardware, WVG can take ~30 seconds to complete.
 /// GPU processing is ~1 second.
 /// Typical setup is ~25 WVGs & 1 GPU.
@@ -32,17 +49,32 @@ const GRACEFUL_SHUTDOWN_DURATION: Duration = Duration::from_secs(70);
 
 /// With current setup, only a single job is expected to be in flight.
 /// This guarantees memory consumption is going to be fixed (1 job in memory, no more).
+/// The size of the in-flight witness vector can additionally be bounded via `--max-witness-vector-bytes`.
 /// Additionally, helps with estimating graceful shutdown time.
 /// Free side effect, if the machine dies, only 1 job is in "pending" state.
 const CHANNEL_SIZE: usize = 1;
 
+/// Exit code used if no jobs were completed within the `--idle-exit` window.
+const IDLE_EXIT_CODE: i32 = 3;
+
+/// Interval at which remaining runtime is reported if `--max-runtime` is set.
+const REMAINING_RUNTIME_REPORT_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Lower bound for job poll interval, so that database isn't hammered with queries.
+const MIN_POLL_INTERVAL_MS: u64 = 100;
+/// When there are no jobs, pickers back off exponentially, up to this multiple of poll interval.
+const MAX_POLL_BACKOFF_MULTIPLIER: u32 = 5;
+
 #[derive(Debug, Parser)]
 #[command(author = "Matter Labs", version)]
 struct Cli {
-    /// Path to file configuration
+    /// Path to file configuration. May also be an `http(s)://` URL, in which case the config is fetched over network.
     #[arg(short = 'c', long)]
     pub(crate) config_path: Option<PathBuf>,
-    /// Path to file secrets
+    /// Timeout (in seconds) for fetching the configuration if `config_path` is a URL.
+    #[arg(long, default_value_t = 30)]
+    config_fetch_timeout: u64,
+    /// Path to file secrets. Unlike configuration, secrets can only be read from a local file.
     #[arg(short = 's', long)]
     pub(crate) secrets_path: Option<PathBuf>,
     /// Number of light witness vector generators to run in parallel.
@@ -70,14 +102,225 @@ struct Cli {
         conflicts_with_all = ["light_wvg_count", "heavy_wvg_count"]
     )]
     threads: Option<usize>,
+    /// Periodically rebalances light vs heavy witness vector generators based on the job queue.
+    /// Total number of WVGs is kept at `light_wvg_count + heavy_wvg_count`,
+    /// the provided counts are used as the initial split.
+    #[arg(long, default_value_t = false, conflicts_with = "threads")]
+    adaptive_wvg: bool,
+    /// Minimum number of light witness vector generators in adaptive mode.
+    #[arg(long, default_value_t = 1, requires = "adaptive_wvg")]
+    light_wvg_min: usize,
+    /// Maximum number of light witness vector generators in adaptive mode.
+    /// Defaults to the total WVG count.
+    #[arg(long, requires = "adaptive_wvg")]
+    light_wvg_max: Option<usize>,
+    /// Minimum number of heavy witness vector generators in adaptive mode.
+    #[arg(long, default_value_t = 1, requires = "adaptive_wvg")]
+    heavy_wvg_min: usize,
+    /// Maximum number of heavy witness vector generators in adaptive mode.
+    /// Defaults to the total WVG count.
+    #[arg(long, requires = "adaptive_wvg")]
+    heavy_wvg_max: Option<usize>,
+    /// Interval (in seconds) at which the job queue is inspected in adaptive mode.
+    #[arg(long, default_value_t = 60, requires = "adaptive_wvg")]
+    adaptive_wvg_interval: u64,
     /// Max VRAM to allocate. Useful if you want to limit the size of VRAM used.
     /// None corresponds to allocating all available VRAM.
     #[arg(short = 'm', long)]
     pub(crate) max_allocation: Option<usize>,
+    /// Number of setup keys & finalization hints loaded in parallel at startup.
+    #[arg(long, default_value_t = DEFAULT_LOAD_CONCURRENCY as u64,
+        value_parser = clap::value_parser!(u64).range(1..=MAX_LOAD_CONCURRENCY as u64)
+    )]
+    setup_load_concurrency: u64,
+    /// Runs structural checks over finalization hints at startup, failing on the first broken one.
+    #[arg(long, default_value_t = false)]
+    validate_finalization_hints: bool,
+    /// Re-reads setup data or finalization hints for a circuit once if they are corrupted at startup
+    /// (e.g., were torn by a concurrent upload), before failing.
+    #[arg(long, default_value_t = false)]
+    reload_corrupted_setup_data: bool,
+    /// Path to a binary snapshot of loaded setup data. If the snapshot exists and is valid, setup data is restored
+    /// from it instead of the keystore; otherwise, setup data is loaded from the keystore and the snapshot
+    /// is (re)written to this path for subsequent boots. The snapshot is invalidated once any of the setup data
+    /// files it was built from changes (as determined by file sizes and modification times).
+    #[arg(long)]
+    setup_cache_snapshot: Option<PathBuf>,
+    /// Prometheus push gateway URL. If set, metrics are pushed to the gateway,
+    /// overriding the Prometheus configuration from config file.
+    #[arg(long)]
+    prometheus_push_gateway: Option<String>,
+    /// Interval (in seconds) at which metrics are pushed to the Prometheus push gateway.
+    #[arg(long, default_value_t = 10, requires = "prometheus_push_gateway")]
+    prometheus_push_interval: u64,
+    /// Overrides the log level of the `zksync` directive set in observability config (one of `trace`, `debug`, `info`,
+    /// `warn`, `error`). Other directives (e.g., `zksync_prover=debug`) are kept as is.
+    #[arg(long)]
+    log_level: Option<tracing::Level>,
+    /// Interval (in milliseconds) at which witness vector generators poll database for new jobs.
+    /// If no jobs are available, the interval grows exponentially up to 5x the specified value.
+    /// (GPU circuit prover receives jobs directly from witness vector generators and doesn't poll.)
+    #[arg(long, default_value_t = 1_000,
+        value_parser = clap::value_parser!(u64).range(MIN_POLL_INTERVAL_MS..)
+    )]
+    poll_interval_ms: u64,
+    /// Time (in seconds) given to witness vector generators to stop picking new jobs on shutdown.
+    /// Job pickers still running after this timeout are aborted.
+    #[arg(long, default_value_t = 10)]
+    drain_timeout: u64,
+    /// Total time (in seconds) given for graceful shutdown, including `drain_timeout`.
+    /// In-flight jobs get the time remaining after job intake is stopped.
+    #[arg(long, default_value_t = GRACEFUL_SHUTDOWN_DURATION.as_secs())]
+    graceful_shutdown_timeout: u64,
+    /// Enables a sampling profiler over the process lifetime; the profile is written to the specified path on shutdown.
+    /// Paths with `.svg` extension get a flamegraph, others get a `pprof` protobuf.
+    #[arg(long)]
+    profile: Option<PathBuf>,
+    /// Exits with code 3 if the circuit prover completes no jobs within the specified time (in seconds) after startup.
+    /// Useful to detect job routing issues. Disabled by default.
+    #[arg(long)]
+    idle_exit: Option<u64>,
+    /// Maximum time (in seconds) the circuit prover runs for, counting from startup. Once it elapses,
+    /// the prover shuts down gracefully, the same way as on a stop signal. Unlimited by default.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_runtime: Option<u64>,
+    /// URL of a read-only replica of the prover DB. If specified, read-only queries (e.g., checking for available jobs)
+    /// are sent to the replica; jobs are still locked and saved using the primary DB.
+    #[arg(long)]
+    replica_database_url: Option<SensitiveUrl>,
+    /// Maximum lifetime (in seconds) of prover DB connections; older connections are closed and reopened.
+    /// Useful behind connection proxies such as PgBouncer. Defaults to 30 minutes.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    db_max_connection_lifetime_secs: Option<u64>,
+    /// Time (in seconds) after which idle prover DB connections are closed. Defaults to 10 minutes.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    db_idle_timeout_secs: Option<u64>,
+    /// Prints circuits that setup data is available for (without loading it) and exits.
+    #[arg(long, default_value_t = false)]
+    list_circuits: bool,
+    /// Prints the absolute setup data directory used by the keystore (and whether it exists and is readable),
+    /// then exits. Can be combined with `--list-circuits`.
+    #[arg(long, default_value_t = false)]
+    print_setup_path: bool,
+    /// Emits a machine-readable summary of the effective configuration to stdout once resources are loaded.
+    /// In `json` mode, logs are written to stderr so that stdout only contains the summary.
+    #[arg(long, value_enum, default_value_t = StartupSummaryFormat::None)]
+    startup_summary: StartupSummaryFormat,
+    /// Path to a YAML object store config (in the same format as `prover.prover_object_store`).
+    /// If specified, proofs are additionally written to this store. Failures to write to it are logged,
+    /// but do not fail prover jobs.
+    #[arg(long)]
+    secondary_object_store_config: Option<PathBuf>,
+    /// CPU cores (in the `cpulist` format, e.g. `0-7,16`) that WVG threads are pinned to, e.g. to co-locate them
+    /// with the GPU's NUMA node. Each concurrently running WVG job is pinned to a separate core from the list.
+    /// By default, WVG threads are not pinned.
+    #[arg(long)]
+    wvg_cpu_affinity: Option<CpuList>,
+    /// Label identifying this prover instance in metrics. Exported as the `prover_binary_instance_info` metric
+    /// and, if metrics are pushed to a Prometheus push gateway, attached as the `instance` label to all pushed metrics.
+    /// Defaults to the pod name, with chars not allowed in labels replaced by `_`.
+    #[arg(long)]
+    instance_label: Option<InstanceLabel>,
+    /// Interval (in seconds) at which setup data & finalization hints cache sizes and lookup stats are logged
+    /// and reported as metrics. Disabled by default.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    cache_stats_interval: Option<u64>,
+    /// Maximum size (in bytes) of a witness vector sent to the GPU prover. WVG jobs producing larger vectors
+    /// are marked as failed. Unlimited by default.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    max_witness_vector_bytes: Option<u64>,
+    /// Number of tokio runtime worker threads. WVGs do heavy work on dedicated threads, so the prover needs
+    /// few workers; limiting their number reduces context switching on large hosts.
+    /// Defaults to the number of CPU cores.
+    #[arg(long, value_parser = clap::value_parser!(u64).range(1..))]
+    tokio_worker_threads: Option<u64>,
+}
+
+impl Cli {
+    fn db_pool_options(&self) -> DbPoolOptions {
+        DbPoolOptions {
+            max_lifetime: self
+                .db_max_connection_lifetime_secs
+                .map(Duration::from_secs),
+            idle_timeout: self.db_idle_timeout_secs.map(Duration::from_secs),
+        }
+    }
+
+    fn wvg_summary(&self) -> WvgSummary {
+        if let Some(threads) = self.threads {
+            WvgSummary::Simple { threads }
+        } else if self.adaptive_wvg {
+            let budget = self.light_wvg_count + self.heavy_wvg_count;
+            WvgSummary::Adaptive {
+                light: self.light_wvg_count,
+                heavy: self.heavy_wvg_count,
+                light_min: self.light_wvg_min,
+                light_max: self.light_wvg_max.unwrap_or(budget),
+                heavy_min: self.heavy_wvg_min,
+                heavy_max: self.heavy_wvg_max.unwrap_or(budget),
+            }
+        } else {
+            WvgSummary::Fixed {
+                light: self.light_wvg_count,
+                heavy: self.heavy_wvg_count,
+            }
+        }
+    }
+}
+
+/// Error returned if the circuit prover was idle for the whole `--idle-exit` window.
+#[derive(Debug)]
+struct IdleExit(Duration);
+
+impl fmt::Display for IdleExit {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            formatter,
+            "circuit prover completed no jobs within {:?} after startup",
+            self.0
+        )
+    }
+}
+
+impl std::error::Error for IdleExit {}
+
+/// Builds the tokio runtime, using the default number of worker threads unless `worker_threads` is specified.
+fn tokio_runtime(worker_threads: Option<u64>) -> anyhow::Result<tokio::runtime::Runtime> {
+    let mut builder = tokio::runtime::Builder::new_multi_thread();
+    if let Some(worker_threads) = worker_threads {
+        let worker_threads =
+            usize::try_from(worker_threads).context("invalid `--tokio-worker-threads`")?;
+        builder.worker_threads(worker_threads);
+    }
+    Ok(builder.enable_all().build()?)
 }
 
-#[tokio::main]
-async fn main() -> anyhow::Result<()> {
+fn main() -> anyhow::Result<()> {
+    let opt = Cli::parse();
+    tokio_runtime(opt.tokio_worker_threads)?.block_on(run(opt))
+}
+
+async fn run(opt: Cli) -> anyhow::Result<()> {
+    if opt.print_setup_path || opt.list_circuits {
+        let prover_config = load_prover_config(&opt).await?;
+        if opt.print_setup_path {
+            print_setup_path(&prover_config)?;
+        }
+        if opt.list_circuits {
+            list_circuits(&prover_config)?;
+        }
+        return Ok(());
+    }
+    let drain_timeout = Duration::from_secs(opt.drain_timeout);
+    let graceful_shutdown_timeout = Duration::from_secs(opt.graceful_shutdown_timeout);
+    anyhow::ensure!(
+        drain_timeout <= graceful_shutdown_timeout,
+        "drain timeout ({drain_timeout:?}) must not exceed graceful shutdown timeout ({graceful_shutdown_timeout:?})"
+    );
+
+    let profiler = opt.profile.clone().map(Profiler::start).transpose()?;
+    let max_runtime = opt.max_runtime.map(Duration::from_secs);
+
     let (stop_signal_sender, stop_signal_receiver) = oneshot::channel();
     let mut stop_signal_sender = Some(stop_signal_sender);
     ctrlc::set_handler(move || {
@@ -89,44 +332,214 @@ async fn main() -> anyhow::Result<()> {
 
     let cancellation_token = CancellationToken::new();
     let mut managed_tasks = ManagedTasks::new(vec![]);
+    let mut intake_tasks = ManagedTasks::new(vec![]);
+    let mut push_metrics_task = None;
     let (metrics_stop_sender, metrics_stop_receiver) = tokio::sync::watch::channel(false);
+    let mut is_idle_exit = false;
 
     tokio::select! {
-        res = run_inner(cancellation_token.clone(), metrics_stop_receiver, &mut managed_tasks) => {
-            res?
+        res = run_inner(
+            opt,
+            cancellation_token.clone(),
+            metrics_stop_receiver,
+            &mut managed_tasks,
+            &mut intake_tasks,
+            &mut push_metrics_task,
+        ) => {
+            match res {
+                Err(err) if err.is::<IdleExit>() => {
+                    tracing::error!("{err}, shutting down");
+                    is_idle_exit = true;
+                }
+                res => res?,
+            }
         },
         _ = stop_signal_receiver => {
             tracing::info!("Stop request received, shutting down");
         }
+        () = wait_for_max_runtime(max_runtime) => {
+            tracing::info!("Max runtime of {max_runtime:?} has elapsed, shutting down");
+        }
     }
     let shutdown_time = Instant::now();
     cancellation_token.cancel();
-    metrics_stop_sender
-        .send(true)
-        .context("failed to stop metrics")?;
-    managed_tasks.complete(GRACEFUL_SHUTDOWN_DURATION).await;
+    if push_metrics_task.is_none() {
+        metrics_stop_sender
+            .send(true)
+            .context("failed to stop metrics")?;
+    }
+
+    // Stop intake first, so that no new jobs are picked while in-flight jobs are being finished.
+    tracing::info!("Stopping job intake, waiting up to {drain_timeout:?} for job pickers...");
+    if intake_tasks.complete_or_abort(drain_timeout).await {
+        tracing::info!("Job intake stopped in {:?}.", shutdown_time.elapsed());
+    }
+    let remaining_timeout = graceful_shutdown_timeout.saturating_sub(shutdown_time.elapsed());
+    tracing::info!("Waiting up to {remaining_timeout:?} for in-flight jobs to complete...");
+    managed_tasks.complete(remaining_timeout).await;
     tracing::info!("Tasks completed in {:?}.", shutdown_time.elapsed());
+    PROVER_BINARY_METRICS
+        .shutdown_time
+        .set(shutdown_time.elapsed());
+
+    if let Some(profiler) = profiler {
+        tracing::info!("Writing profile to {:?}...", profiler.output_path());
+        profiler.finish().context("failed writing profile")?;
+    }
+
+    if let Some(push_metrics_task) = push_metrics_task {
+        // Push exporter is stopped only after all tasks are drained.
+        // On stop, it pushes metrics one last time, so shutdown metrics aren't lost.
+        metrics_stop_sender
+            .send(true)
+            .context("failed to stop metrics")?;
+        push_metrics_task
+            .await
+            .context("metrics push task panicked")?
+            .context("metrics push task failed")?;
+    }
+    if is_idle_exit {
+        std::process::exit(IDLE_EXIT_CODE);
+    }
+    Ok(())
+}
+
+/// Replaces the level of the default `zksync` directive in `log_directives`, keeping other directives intact.
+/// If there's no such directive, it's added.
+fn override_log_level(log_directives: &str, log_level: tracing::Level) -> String {
+    let default_directive = format!("zksync={}", log_level.as_str().to_lowercase());
+    let other_directives = log_directives
+        .split(',')
+        .map(str::trim)
+        .filter(|directive| !directive.is_empty() && directive.split('=').next() != Some("zksync"));
+    iter::once(default_directive.as_str())
+        .chain(other_directives)
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Loads prover config for one-off commands (e.g., `--list-circuits`) without installing observability.
+async fn load_prover_config(opt: &Cli) -> anyhow::Result<FriProverConfig> {
+    let schema = full_config_schema();
+    let config_sources = load_config_sources(
+        opt.config_path.clone(),
+        opt.secrets_path.clone(),
+        Duration::from_secs(opt.config_fetch_timeout),
+    )
+    .await?;
+    let general_config: GeneralConfig = config_sources.build_repository(&schema).parse()?;
+    general_config
+        .prover_config
+        .context("failed loading prover config")
+}
+
+/// State of a directory used by the keystore.
+#[derive(Debug)]
+struct DirectoryStatus {
+    absolute_path: PathBuf,
+    exists: bool,
+    readable: bool,
+}
+
+impl DirectoryStatus {
+    fn inspect(path: &Path) -> io::Result<Self> {
+        let absolute_path = std::path::absolute(path)?;
+        let exists = absolute_path.is_dir();
+        let readable = exists && fs::read_dir(&absolute_path).is_ok();
+        Ok(Self {
+            absolute_path,
+            exists,
+            readable,
+        })
+    }
+}
+
+impl fmt::Display for DirectoryStatus {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let yes_no = |flag: bool| if flag { "yes" } else { "no" };
+        write!(
+            formatter,
+            "{} (exists: {}, readable: {})",
+            self.absolute_path.display(),
+            yes_no(self.exists),
+            yes_no(self.readable)
+        )
+    }
+}
+
+/// Prints setup data directory resolved by the keystore, together with the keystore base directory.
+fn print_setup_path(prover_config: &FriProverConfig) -> anyhow::Result<()> {
+    let keystore = Keystore::locate().with_setup_path(Some(prover_config.setup_data_path.clone()));
+    let base_path = DirectoryStatus::inspect(keystore.get_base_path())
+        .context("failed resolving keystore base path")?;
+    let setup_data_path = DirectoryStatus::inspect(keystore.get_setup_data_path())
+        .context("failed resolving setup data path")?;
+    println!(
+        "Configured setup data path: {}",
+        prover_config.setup_data_path.display()
+    );
+    println!("Keystore base path (`Keystore::locate()`): {base_path}");
+    println!("Setup data path: {setup_data_path}");
+    Ok(())
+}
+
+/// Prints circuits that have setup data in the configured setup data directory.
+fn list_circuits(prover_config: &FriProverConfig) -> anyhow::Result<()> {
+    let keystore = Keystore::locate().with_setup_path(Some(prover_config.setup_data_path.clone()));
+    let circuits = keystore
+        .available_setup_circuits()
+        .context("failed listing available setup circuits")?;
+    println!(
+        "Setup data is available for {} circuit(s) in {:?}:",
+        circuits.len(),
+        prover_config.setup_data_path
+    );
+    for key in circuits {
+        println!(
+            "  {} (circuit {}, stage {:?})",
+            key.name(),
+            key.circuit_id,
+            key.stage
+        );
+    }
     Ok(())
 }
 
 /// The main service entrypoint, contains business logic.
+///
+/// Job picker tasks of witness vector generators (i.e., tasks pulling new jobs) are placed to `intake_tasks`,
+/// so that they can be stopped separately on shutdown.
 async fn run_inner(
+    opt: Cli,
     cancellation_token: CancellationToken,
     metrics_stop_receiver: tokio::sync::watch::Receiver<bool>,
     managed_tasks: &mut ManagedTasks,
+    intake_tasks: &mut ManagedTasks,
+    push_metrics_task: &mut Option<JoinHandle<anyhow::Result<()>>>,
 ) -> anyhow::Result<()> {
     let start_time = Instant::now();
 
-    let opt = Cli::parse();
     let schema = full_config_schema();
-    let config_file_paths = ConfigFilePaths {
-        general: opt.config_path,
-        secrets: opt.secrets_path,
-        ..ConfigFilePaths::default()
-    };
-    let config_sources = config_file_paths.into_config_sources("ZKSYNC_")?;
+    let config_sources = load_config_sources(
+        opt.config_path.clone(),
+        opt.secrets_path.clone(),
+        Duration::from_secs(opt.config_fetch_timeout),
+    )
+    .await?;
 
-    let _observability_guard = config_sources.observability()?.install()?;
+    let mut observability_config = config_sources.observability()?;
+    if let Some(log_level) = opt.log_level {
+        observability_config.log_directives =
+            override_log_level(&observability_config.log_directives, log_level);
+    }
+    let log_directives = observability_config.log_directives.clone();
+    let _observability_guard = if opt.startup_summary == StartupSummaryFormat::Json {
+        // Keep stdout reserved for the startup summary.
+        observability_config.install_with_logs(|logs| logs.log_to_stderr())?
+    } else {
+        observability_config.install()?
+    };
+    tracing::info!("Effective log directives: {log_directives}");
 
     let mut repo = config_sources.build_repository(&schema);
     let general_config: GeneralConfig = repo.parse()?;
@@ -138,29 +551,132 @@ async fn run_inner(
     let object_store_config = prover_config.prover_object_store.clone();
     tracing::info!("Loaded configs.");
 
-    let prometheus_exporter_config = general_config
-        .prometheus_config
-        .build_exporter_config(prover_config.prometheus_port)
-        .context("Failed to build Prometheus exporter configuration")?;
-    tracing::info!("Using Prometheus exporter with {prometheus_exporter_config:?}");
+    // Validate the CPU list before loading resources, so that misconfiguration is reported early.
+    let wvg_cpu_affinity = opt
+        .wvg_cpu_affinity
+        .clone()
+        .map(|cpu_list| WvgCpuAffinity::new(cpu_list.0))
+        .transpose()
+        .context("invalid `--wvg-cpu-affinity`")?;
+    if let Some(affinity) = &wvg_cpu_affinity {
+        tracing::info!("Pinning WVG threads to CPU cores {:?}", affinity.cores());
+    }
+    let max_witness_vector_bytes = opt
+        .max_witness_vector_bytes
+        .map(usize::try_from)
+        .transpose()
+        .context("invalid `--max-witness-vector-bytes`")?;
+
+    let instance_label = match opt.instance_label.clone() {
+        Some(label) => label,
+        None => {
+            let pod_name = get_current_pod_name();
+            pod_name.parse().unwrap_or_else(|err| {
+                let label = InstanceLabel::sanitize(&pod_name);
+                tracing::warn!(
+                    "Pod name is not a valid instance label ({err:#}); using `{label}` instead. \
+                     Set `--instance-label` to override"
+                );
+                label
+            })
+        }
+    };
+    tracing::info!("Using instance label `{instance_label}` for metrics");
+    PROVER_BINARY_METRICS
+        .instance
+        .set(InstanceInfo {
+            instance: instance_label.to_string(),
+        })
+        .ok();
 
-    let mut tasks = vec![tokio::spawn(
-        prometheus_exporter_config.run(metrics_stop_receiver),
-    )];
+    let mut tasks = vec![];
+    if let Some(gateway_url) = &opt.prometheus_push_gateway {
+        // Grouping labels in the push gateway endpoint are attached to all pushed metrics.
+        let gateway_endpoint = format!(
+            "{}/instance/{instance_label}",
+            PrometheusExporterConfig::gateway_endpoint(gateway_url)
+        );
+        let prometheus_exporter_config = PrometheusExporterConfig::push(
+            gateway_endpoint,
+            Duration::from_secs(opt.prometheus_push_interval),
+        );
+        tracing::info!("Using Prometheus exporter with {prometheus_exporter_config:?}");
+        // Not a managed task, as it needs to outlive other tasks to push final metrics.
+        *push_metrics_task = Some(tokio::spawn(
+            prometheus_exporter_config.run(metrics_stop_receiver),
+        ));
+    } else {
+        let prometheus_exporter_config = general_config
+            .prometheus_config
+            .build_exporter_config(prover_config.prometheus_port)
+            .context("Failed to build Prometheus exporter configuration")?;
+        tracing::info!("Using Prometheus exporter with {prometheus_exporter_config:?}");
+        tasks.push(tokio::spawn(
+            prometheus_exporter_config.run(metrics_stop_receiver),
+        ));
+    }
 
     let (connection_pool, object_store, prover_context, setup_data_cache, hints) = load_resources(
         database_secrets,
         opt.max_allocation,
         object_store_config,
-        prover_config.setup_data_path,
+        prover_config.setup_data_path.clone(),
+        SetupDataLoadOptions {
+            concurrency: opt.setup_load_concurrency as usize,
+            validate_finalization_hints: opt.validate_finalization_hints,
+            reload_on_corruption: opt.reload_corrupted_setup_data,
+            snapshot_path: opt.setup_cache_snapshot.clone(),
+        },
+        opt.db_pool_options(),
     )
     .await
     .context("failed to load configs")?;
 
+    if opt.startup_summary == StartupSummaryFormat::Json {
+        let summary = StartupSummary {
+            protocol_version: PROVER_PROTOCOL_SEMANTIC_VERSION.to_string(),
+            setup_data_path: prover_config.setup_data_path,
+            poll_interval_ms: opt.poll_interval_ms,
+            witness_vector_generators: opt.wvg_summary(),
+            gpu: GpuSummary {
+                max_allocation: opt.max_allocation,
+            },
+            setup_data_cache_size: setup_data_cache.len(),
+            finalization_hints_cache_size: hints.len(),
+            startup_time_ms: start_time.elapsed().as_millis(),
+        };
+        println!("{}", summary.to_json());
+    }
+
+    if let Some(interval) = opt.cache_stats_interval {
+        let reporter = CacheStatsReporter::new(
+            setup_data_cache.len(),
+            hints.len(),
+            Duration::from_secs(interval),
+            cancellation_token.clone(),
+        );
+        tasks.push(tokio::spawn(reporter.run()));
+    }
+
+    let read_pool = match opt.replica_database_url {
+        Some(url) => Some(
+            build_replica_connection_pool(url, opt.db_pool_options())
+                .await
+                .context("failed to build replica connection pool")?,
+        ),
+        None => None,
+    };
+
     let (witness_vector_sender, witness_vector_receiver) = tokio::sync::mpsc::channel(CHANNEL_SIZE);
+    let unservable_circuits = UnservableCircuits::default();
 
     PROVER_BINARY_METRICS.startup_time.set(start_time.elapsed());
 
+    let poll_interval = Duration::from_millis(opt.poll_interval_ms);
+    tracing::info!(
+        "Polling for WVG jobs every {poll_interval:?}, backing off up to {:?} if there are no jobs.",
+        poll_interval * MAX_POLL_BACKOFF_MULTIPLIER
+    );
     let builder = WvgRunnerBuilder::new(
         connection_pool.clone(),
         object_store.clone(),
@@ -168,14 +684,62 @@ async fn run_inner(
         hints.clone(),
         witness_vector_sender,
         cancellation_token.clone(),
-    );
+    )
+    .with_backoff(Backoff::new(
+        poll_interval,
+        poll_interval * MAX_POLL_BACKOFF_MULTIPLIER,
+    ))
+    .with_read_pool(read_pool.clone())
+    .with_unservable_circuits(unservable_circuits.clone())
+    .with_cpu_affinity(wvg_cpu_affinity)
+    .with_max_witness_vector_bytes(max_witness_vector_bytes);
 
-    let wvg_tasks = if let Some(threads) = opt.threads {
+    let mut wvg_runner_tasks = vec![];
+    if let Some(threads) = opt.threads {
         // If threads are specified, we run a simple WVG runner.
         // Otherwise, we use heavy and light job functionality.
         tracing::info!("Starting {} WVGs.", threads,);
         let simple_wvg_runner = builder.simple_wvg_runner(threads);
-        simple_wvg_runner.run()
+        wvg_runner_tasks.push(simple_wvg_runner.spawn_tasks());
+    } else if opt.adaptive_wvg {
+        let budget = opt.light_wvg_count + opt.heavy_wvg_count;
+        let light_wvg_max = opt.light_wvg_max.unwrap_or(budget);
+        let heavy_wvg_max = opt.heavy_wvg_max.unwrap_or(budget);
+        anyhow::ensure!(
+            opt.light_wvg_min <= light_wvg_max && opt.heavy_wvg_min <= heavy_wvg_max,
+            "minimum WVG count must not exceed maximum WVG count"
+        );
+        let light_scaler = WorkerScaler::new(opt.light_wvg_count, opt.light_wvg_min, light_wvg_max);
+        let heavy_scaler = WorkerScaler::new(opt.heavy_wvg_count, opt.heavy_wvg_min, heavy_wvg_max);
+        let balancer = WvgBalancer::new(
+            // Balancer only reads queue stats, so it can use the replica if there's one.
+            read_pool.clone().unwrap_or_else(|| connection_pool.clone()),
+            PROVER_PROTOCOL_SEMANTIC_VERSION,
+            light_scaler.clone(),
+            heavy_scaler.clone(),
+            budget,
+            Duration::from_secs(opt.adaptive_wvg_interval),
+            cancellation_token.clone(),
+        )?;
+        tracing::info!(
+            "Starting {} adaptive WVGs (light: {}..={}, heavy: {}..={}), initially {} light and {} heavy.",
+            budget,
+            light_scaler.min(),
+            light_scaler.max(),
+            heavy_scaler.min(),
+            heavy_scaler.max(),
+            light_scaler.target(),
+            heavy_scaler.target()
+        );
+        let light_wvg_runner = builder
+            .light_wvg_runner(opt.light_wvg_count)
+            .with_worker_scaler(light_scaler);
+        let heavy_wvg_runner = builder
+            .heavy_wvg_runner(opt.heavy_wvg_count)
+            .with_worker_scaler(heavy_scaler);
+        wvg_runner_tasks.push(light_wvg_runner.spawn_tasks());
+        wvg_runner_tasks.push(heavy_wvg_runner.spawn_tasks());
+        tasks.push(tokio::spawn(balancer.run()));
     } else {
         tracing::info!(
             "Starting {} light WVGs and {} heavy WVGs.",
@@ -184,31 +748,114 @@ async fn run_inner(
         );
         let light_wvg_runner = builder.light_wvg_runner(opt.light_wvg_count);
         let heavy_wvg_runner = builder.heavy_wvg_runner(opt.heavy_wvg_count);
-        let mut wvg_tasks = light_wvg_runner.run();
-        wvg_tasks.extend(heavy_wvg_runner.run());
-        wvg_tasks
-    };
-    tasks.extend(wvg_tasks);
+        wvg_runner_tasks.push(light_wvg_runner.spawn_tasks());
+        wvg_runner_tasks.push(heavy_wvg_runner.spawn_tasks());
+    }
+    let mut wvg_picker_tasks = vec![];
+    for JobRunnerTasks { picker, processing } in wvg_runner_tasks {
+        wvg_picker_tasks.push(picker);
+        tasks.extend(processing);
+    }
 
     // necessary as it has a connection_pool which will keep 1 connection active by default
     drop(builder);
 
+    let secondary_object_store = match &opt.secondary_object_store_config {
+        Some(path) => Some(load_secondary_object_store(path).await?),
+        None => None,
+    };
     let circuit_prover_runner = circuit_prover_runner(
         connection_pool,
         object_store,
+        secondary_object_store,
         PROVER_PROTOCOL_SEMANTIC_VERSION,
         setup_data_cache,
         witness_vector_receiver,
         prover_context,
+        unservable_circuits,
     );
 
     tasks.extend(circuit_prover_runner.run());
 
     *managed_tasks = ManagedTasks::new(tasks);
-    managed_tasks.wait_single().await;
+    *intake_tasks = ManagedTasks::new(wvg_picker_tasks);
+    let idle_exit = opt.idle_exit.map(Duration::from_secs);
+    tokio::select! {
+        () = managed_tasks.wait_single() => {}
+        () = intake_tasks.wait_single() => {}
+        err = wait_for_idle_exit(idle_exit) => return Err(err.into()),
+    }
     Ok(())
 }
 
+/// Resolves if the circuit prover completes no jobs within `window`. Never resolves if `window` is not set
+/// or a job was completed.
+async fn wait_for_idle_exit(window: Option<Duration>) -> IdleExit {
+    let Some(window) = window else {
+        return std::future::pending().await;
+    };
+    let completed_jobs = CIRCUIT_PROVER_METRICS.completed_jobs.get();
+    tokio::time::sleep(window).await;
+    if CIRCUIT_PROVER_METRICS.completed_jobs.get() > completed_jobs {
+        tracing::info!("Circuit prover completed jobs within {window:?}, idle exit is disarmed");
+        return std::future::pending().await;
+    }
+    IdleExit(window)
+}
+
+/// Resolves once `max_runtime` has elapsed, periodically reporting the remaining runtime.
+/// Never resolves if `max_runtime` is not set.
+async fn wait_for_max_runtime(max_runtime: Option<Duration>) {
+    let Some(max_runtime) = max_runtime else {
+        return std::future::pending().await;
+    };
+    let deadline = tokio::time::Instant::now() + max_runtime;
+    loop {
+        let remaining = deadline.saturating_duration_since(tokio::time::Instant::now());
+        PROVER_BINARY_METRICS.remaining_run_time.set(remaining);
+        if remaining.is_zero() {
+            return;
+        }
+        tokio::time::sleep(remaining.min(REMAINING_RUNTIME_REPORT_INTERVAL)).await;
+    }
+}
+
+/// Loads config sources, fetching the general config over network if its path is a URL.
+async fn load_config_sources(
+    config_path: Option<PathBuf>,
+    secrets_path: Option<PathBuf>,
+    fetch_timeout: Duration,
+) -> anyhow::Result<ConfigSources> {
+    if let Some(secrets_path) = &secrets_path {
+        anyhow::ensure!(
+            matches!(
+                ConfigLocation::new(secrets_path.clone()),
+                Ok(ConfigLocation::File(_))
+            ),
+            "secrets can only be read from a local file, got {secrets_path:?}"
+        );
+    }
+
+    let config_location = config_path.map(ConfigLocation::new).transpose()?;
+    let mut config_file_paths = ConfigFilePaths {
+        secrets: secrets_path,
+        ..ConfigFilePaths::default()
+    };
+    match config_location {
+        Some(ConfigLocation::Url(url)) => {
+            let contents = fetch_config(&url, fetch_timeout).await?;
+            let general = ConfigFilePaths::parse_yaml(url.as_str(), &contents)
+                .with_context(|| format!("failed parsing config fetched from {url}"))?;
+            config_file_paths.into_config_sources_with_general(general, "ZKSYNC_")
+        }
+        Some(ConfigLocation::File(path)) => {
+            config_file_paths.general = Some(path);
+            config_file_paths.into_config_sources("ZKSYNC_")
+        }
+        None => config_file_paths.into_config_sources("ZKSYNC_"),
+    }
+}
+
 /// Loads resources necessary for proving.
 /// - connection pool - necessary to pick & store jobs from database
 /// - object store - necessary  for loading and storing artifacts to object store
@@ -220,6 +867,8 @@ async fn load_resources(
     max_gpu_vram_allocation: Option<usize>,
     object_store_config: ObjectStoreConfig,
     setup_data_path: PathBuf,
+    load_options: SetupDataLoadOptions,
+    pool_options: DbPoolOptions,
 ) -> anyhow::Result<(
     ConnectionPool<Prover>,
     Arc<dyn ObjectStore>,
@@ -230,18 +879,28 @@ async fn load_resources(
     let database_url = database_secrets
         .prover_url
         .context("no prover DB URl present")?;
-    // 2 connections for the witness vector generator job pickers (1 each) and 1 for gpu circuit prover job saver
-    let max_connections = 3;
+    // 2 connections for the witness vector generator job pickers (1 each), 1 for gpu circuit prover job picker
+    // (re-queueing jobs without loaded setup data), 1 for gpu circuit prover job saver and 1 for the adaptive WVG
+    // balancer if there's no DB replica
+    let max_connections = 5;
+    let stage_start = Instant::now();
+    tracing::info!("Using prover DB pool options: {pool_options}");
     let connection_pool = ConnectionPool::<Prover>::builder(database_url, max_connections)
+        .set_max_lifetime(pool_options.max_lifetime)
+        .set_idle_timeout(pool_options.idle_timeout)
         .build()
         .await
         .context("failed to build connection pool")?;
+    observe_startup_stage(StartupStage::DbPool, stage_start);
 
+    let stage_start = Instant::now();
     let object_store = ObjectStoreFactory::new(object_store_config)
         .create_store()
         .await
         .context("failed to create object store")?;
+    observe_startup_stage(StartupStage::ObjectStore, stage_start);
 
+    let stage_start = Instant::now();
     let prover_context = match max_gpu_vram_allocation {
         Some(max_allocation) => ProverContext::create_with_config(
             ProverContextConfig::default().with_maximum_device_allocation(max_allocation),
@@ -249,28 +908,352 @@ async fn load_resources(
         .context("failed initializing fixed gpu prover context")?,
         None => ProverContext::create().context("failed initializing gpu prover context")?,
     };
+    observe_startup_stage(StartupStage::ProverContext, stage_start);
 
-    tracing::info!("Loading setup data from disk...");
+    let (setup_data_cache, finalization_hints) =
+        load_setup_data(setup_data_path, load_options).await?;
 
-    let keystore = Keystore::locate().with_setup_path(Some(setup_data_path));
-    let setup_data_cache = keystore
-        .load_all_setup_key_mapping()
+    Ok((
+        connection_pool,
+        object_store,
+        prover_context,
+        setup_data_cache,
+        finalization_hints,
+    ))
+}
+
+fn observe_startup_stage(stage: StartupStage, started_at: Instant) {
+    let elapsed = started_at.elapsed();
+    tracing::debug!("Startup stage {stage:?} took {elapsed:?}");
+    PROVER_BINARY_METRICS.startup_stage_time[&stage].set(elapsed);
+}
+
+/// Builds a connection pool to the read-only replica of the prover DB, checking that the replica is reachable.
+async fn build_replica_connection_pool(
+    url: SensitiveUrl,
+    pool_options: DbPoolOptions,
+) -> anyhow::Result<ConnectionPool<Prover>> {
+    // 1 connection for each witness vector generator job picker (light & heavy) and 1 for the WVG balancer
+    let max_connections = 3;
+    let pool = ConnectionPool::<Prover>::builder(url, max_connections)
+        .set_max_lifetime(pool_options.max_lifetime)
+        .set_idle_timeout(pool_options.idle_timeout)
+        .build()
         .await
-        .context("failed to load setup key mapping")?;
+        .context("failed to build connection pool")?;
+    pool.connection()
+        .await
+        .context("replica DB is unreachable")?;
+    tracing::info!("Using read-only prover DB replica for job availability checks");
+    Ok(pool)
+}
+
+/// Creates the secondary object store from the YAML config at `path`.
+async fn load_secondary_object_store(path: &Path) -> anyhow::Result<Arc<dyn ObjectStore>> {
+    let config = parse_object_store_config(path)?;
+    tracing::info!("Using secondary object store: {config:?}");
+    ObjectStoreFactory::new(config)
+        .create_store()
+        .await
+        .context("failed to create secondary object store")
+}
+
+fn parse_object_store_config(path: &Path) -> anyhow::Result<ObjectStoreConfig> {
+    let schema = ConfigSchema::new(&ObjectStoreConfig::DESCRIPTION, "");
+    let mut repo = ConfigSources::default()
+        .with_yaml(path)?
+        .build_repository(&schema);
+    repo.parse()
+        .with_context(|| format!("failed parsing object store config at {path:?}"))
+}
+
+/// Connection lifetime options for prover DB pools. `None` values mean that `sqlx` defaults are used.
+#[derive(Debug, Clone, Copy, Default)]
+struct DbPoolOptions {
+    max_lifetime: Option<Duration>,
+    idle_timeout: Option<Duration>,
+}
+
+impl fmt::Display for DbPoolOptions {
+    fn fmt(&self, formatter: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let fmt_duration = |duration: Option<Duration>| {
+            duration.map_or_else(|| "default".to_owned(), |duration| format!("{duration:?}"))
+        };
+        write!(
+            formatter,
+            "max connection lifetime: {}, idle timeout: {}",
+            fmt_duration(self.max_lifetime),
+            fmt_duration(self.idle_timeout)
+        )
+    }
+}
+
+/// Options for loading setup data & finalization hints from keystore.
+#[derive(Debug, Clone)]
+struct SetupDataLoadOptions {
+    /// Number of keys loaded in parallel.
+    concurrency: usize,
+    /// Whether to run structural checks over loaded finalization hints.
+    validate_finalization_hints: bool,
+    /// Whether to re-read a corrupted key once before failing.
+    reload_on_corruption: bool,
+    /// Path to the setup data snapshot used instead of the keystore if it's valid.
+    snapshot_path: Option<PathBuf>,
+}
+
+/// Loads setup data & finalization hints from keystore.
+async fn load_setup_data(
+    setup_data_path: PathBuf,
+    options: SetupDataLoadOptions,
+) -> anyhow::Result<(SetupDataCache, FinalizationHintsCache)> {
+    let setup_load_concurrency = options.concurrency;
+    tracing::info!("Loading setup data from disk (concurrency {setup_load_concurrency})...");
+    let setup_load_start = Instant::now();
+
+    let keystore = Keystore::locate()
+        .with_setup_path(Some(setup_data_path))
+        .with_load_concurrency(setup_load_concurrency)
+        .with_reload_on_corruption(options.reload_on_corruption);
+    let setup_data_cache = match &options.snapshot_path {
+        Some(path) => load_setup_data_with_snapshot(&keystore, path.clone()).await?,
+        None => keystore
+            .load_all_setup_key_mapping()
+            .await
+            .context("failed to load setup key mapping")?,
+    };
+    observe_startup_stage(StartupStage::SetupData, setup_load_start);
 
     tracing::info!("Loading finalization hints from disk...");
+    let stage_start = Instant::now();
     let finalization_hints = keystore
         .load_all_finalization_hints_mapping()
         .await
         .context("failed to load finalization hints mapping")?;
 
-    tracing::info!("Finished loading mappings from disk.");
+    if options.validate_finalization_hints {
+        tracing::info!("Validating finalization hints...");
+        zksync_circuit_prover::validate_finalization_hints(&finalization_hints)
+            .context("finalization hints validation failed")?;
+    }
+    observe_startup_stage(StartupStage::FinalizationHints, stage_start);
 
-    Ok((
-        connection_pool,
-        object_store,
-        prover_context,
-        setup_data_cache,
-        finalization_hints,
-    ))
+    tracing::info!(
+        "Finished loading mappings from disk in {:?}.",
+        setup_load_start.elapsed()
+    );
+    Ok((setup_data_cache, finalization_hints))
+}
+
+/// Restores setup data from the snapshot at `snapshot_path` if possible. Otherwise, loads setup data from
+/// the keystore and writes a new snapshot; failing to write the snapshot is not fatal. The snapshot is only used
+/// if it was built from the current setup data files.
+async fn load_setup_data_with_snapshot(
+    keystore: &Keystore,
+    snapshot_path: PathBuf,
+) -> anyhow::Result<SetupDataCache> {
+    let setup_data_paths: Vec<_> = ProverServiceDataKey::all_boojum()
+        .into_iter()
+        .map(|key| keystore.setup_data_file_path(key))
+        .collect();
+    let sources_hash = match hash_source_files(&setup_data_paths) {
+        Ok(hash) => hash,
+        Err(err) => {
+            tracing::warn!("Failed hashing setup data files, not using snapshot: {err:#}");
+            return keystore
+                .load_all_setup_key_mapping()
+                .await
+                .context("failed to load setup key mapping");
+        }
+    };
+
+    if snapshot_path.exists() {
+        let path = snapshot_path.clone();
+        let restored =
+            tokio::task::spawn_blocking(move || SetupDataCache::restore_from(&path, sources_hash))
+                .await
+                .context("restoring setup data snapshot panicked")?;
+        match restored {
+            Ok(cache) => {
+                tracing::info!(
+                    "Restored setup data for {} circuits from snapshot {snapshot_path:?}",
+                    cache.len()
+                );
+                return Ok(cache);
+            }
+            Err(err) => {
+                tracing::warn!(
+                    "Failed restoring setup data snapshot, falling back to keystore: {err:#}"
+                );
+            }
+        }
+    }
+
+    let setup_data_cache = keystore
+        .load_all_setup_key_mapping()
+        .await
+        .context("failed to load setup key mapping")?;
+    tracing::info!("Writing setup data snapshot to {snapshot_path:?}...");
+    let cache = setup_data_cache.clone();
+    let dumped = tokio::task::spawn_blocking(move || cache.dump_to(&snapshot_path, sources_hash))
+        .await
+        .context("writing setup data snapshot panicked")?;
+    if let Err(err) = dumped {
+        tracing::warn!("Failed writing setup data snapshot: {err:#}");
+    }
+    Ok(setup_data_cache)
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_config::configs::object_store::ObjectStoreMode;
+
+    use super::*;
+
+    const TEST_LOAD_OPTIONS: SetupDataLoadOptions = SetupDataLoadOptions {
+        concurrency: 4,
+        validate_finalization_hints: false,
+        reload_on_corruption: false,
+        snapshot_path: None,
+    };
+
+    #[tokio::test(start_paused = true)]
+    async fn max_runtime_is_enforced() {
+        let max_runtime = Duration::from_secs(25);
+        let started_at = tokio::time::Instant::now();
+        wait_for_max_runtime(Some(max_runtime)).await;
+        assert_eq!(started_at.elapsed(), max_runtime);
+        assert_eq!(
+            PROVER_BINARY_METRICS.remaining_run_time.get(),
+            Duration::ZERO
+        );
+
+        let unlimited =
+            tokio::time::timeout(Duration::from_secs(3_600), wait_for_max_runtime(None));
+        unlimited.await.unwrap_err();
+    }
+
+    #[test]
+    fn overriding_log_level() {
+        assert_eq!(
+            override_log_level("zksync=info,zksync_prover=debug", tracing::Level::WARN),
+            "zksync=warn,zksync_prover=debug"
+        );
+        assert_eq!(
+            override_log_level("zksync_prover=debug, sqlx=error", tracing::Level::TRACE),
+            "zksync=trace,zksync_prover=debug,sqlx=error"
+        );
+        assert_eq!(override_log_level("", tracing::Level::INFO), "zksync=info");
+    }
+
+    #[test]
+    fn building_tokio_runtime() {
+        let runtime = tokio_runtime(Some(2)).unwrap();
+        assert_eq!(runtime.metrics().num_workers(), 2);
+        let runtime = tokio_runtime(None).unwrap();
+        assert!(runtime.metrics().num_workers() >= 1);
+    }
+
+    #[test]
+    fn inspecting_directory_status() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let status = DirectoryStatus::inspect(dir.path()).unwrap();
+        assert!(status.absolute_path.is_absolute());
+        assert!(status.exists && status.readable, "{status}");
+
+        let status = DirectoryStatus::inspect(Path::new("./missing-setup-dir")).unwrap();
+        assert!(status.absolute_path.is_absolute());
+        assert!(status.absolute_path.ends_with("missing-setup-dir"));
+        assert!(!status.exists && !status.readable, "{status}");
+        assert!(
+            status.to_string().ends_with("(exists: no, readable: no)"),
+            "{status}"
+        );
+    }
+
+    #[tokio::test]
+    async fn loading_setup_data_fails_on_missing_artifacts() {
+        let setup_dir = tempfile::TempDir::new().unwrap();
+        let err = load_setup_data(setup_dir.path().to_owned(), TEST_LOAD_OPTIONS)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to load setup key mapping"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn loading_setup_data_fails_on_partial_artifacts() {
+        let setup_dir = tempfile::TempDir::new().unwrap();
+        // Only a single (corrupted) artifact is present.
+        std::fs::write(setup_dir.path().join("setup_basic_1_data.bin"), b"garbage").unwrap();
+        let err = load_setup_data(setup_dir.path().to_owned(), TEST_LOAD_OPTIONS)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to load setup key mapping"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn invalid_setup_data_snapshot_falls_back_to_keystore() {
+        let setup_dir = tempfile::TempDir::new().unwrap();
+        let snapshot_path = setup_dir.path().join("setup_data.snapshot");
+        std::fs::write(&snapshot_path, b"garbage").unwrap();
+        let options = SetupDataLoadOptions {
+            snapshot_path: Some(snapshot_path.clone()),
+            ..TEST_LOAD_OPTIONS
+        };
+        // The keystore is empty, so the fallback fails as well.
+        let err = load_setup_data(setup_dir.path().to_owned(), options)
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to load setup key mapping"),
+            "{err:#}"
+        );
+        // The invalid snapshot is not overwritten if loading from the keystore fails.
+        assert_eq!(std::fs::read(&snapshot_path).unwrap(), b"garbage");
+    }
+
+    #[test]
+    fn displaying_db_pool_options() {
+        let options = DbPoolOptions::default();
+        assert_eq!(
+            options.to_string(),
+            "max connection lifetime: default, idle timeout: default"
+        );
+        let options = DbPoolOptions {
+            max_lifetime: Some(Duration::from_secs(300)),
+            idle_timeout: None,
+        };
+        assert_eq!(
+            options.to_string(),
+            "max connection lifetime: 300s, idle timeout: default"
+        );
+    }
+
+    #[test]
+    fn parsing_secondary_object_store_config() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let config_path = dir.path().join("object_store.yaml");
+        std::fs::write(
+            &config_path,
+            "mode: FileBacked\nfile_backed_base_path: /mirror\nmax_retries: 5\n",
+        )
+        .unwrap();
+
+        let config = parse_object_store_config(&config_path).unwrap();
+        assert_eq!(
+            config.mode,
+            ObjectStoreMode::FileBacked {
+                file_backed_base_path: "/mirror".into(),
+            }
+        );
+        assert_eq!(config.max_retries, 5);
+
+        let err = parse_object_store_config(&dir.path().join("missing.yaml")).unwrap_err();
+        assert!(format!("{err:#}").contains("missing.yaml"), "{err:#}");
+    }
 }
@@ -1,6 +1,30 @@
 use std::time::Duration;
 
-use vise::{Gauge, Metrics};
+use vise::{EncodeLabelSet, EncodeLabelValue, Family, Gauge, Info, LabeledFamily, Metrics};
+use zksync_circuit_prover_service::SetupCache;
+
+/// Stage of loading resources during prover startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "stage", rename_all = "snake_case")]
+pub enum StartupStage {
+    /// Building the prover DB connection pool.
+    DbPool,
+    /// Creating the object store.
+    ObjectStore,
+    /// Creating the GPU prover context.
+    ProverContext,
+    /// Loading setup keys from disk.
+    SetupData,
+    /// Loading (and optionally validating) finalization hints from disk.
+    FinalizationHints,
+}
+
+/// Labels identifying a prover instance.
+#[derive(Debug, EncodeLabelSet)]
+pub struct InstanceInfo {
+    /// Instance label supplied via `--instance-label`, or the pod name by default.
+    pub instance: String,
+}
 
 /// Instrument prover binary lifecycle
 #[derive(Debug, Metrics)]
@@ -8,10 +32,19 @@ use vise::{Gauge, Metrics};
 pub struct ProverBinaryMetrics {
     /// How long does it take for prover to load data before it can produce proofs?
     pub startup_time: Gauge<Duration>,
+    /// Breakdown of the startup time by stages of loading resources.
+    pub startup_stage_time: Family<StartupStage, Gauge<Duration>>,
     /// How long did the prover binary run for?
     pub run_time: Gauge<Duration>,
     /// How long does it take prover to gracefully shutdown?
     pub shutdown_time: Gauge<Duration>,
+    /// Time left until the prover binary shuts down because of `--max-runtime`. Not reported if runtime is unlimited.
+    pub remaining_run_time: Gauge<Duration>,
+    /// Instance label of the prover binary. Can be joined with other metrics to slice them by instance.
+    pub instance: Info<InstanceInfo>,
+    /// Number of entries in setup caches. Only reported if `--cache-stats-interval` is set.
+    #[metrics(labels = ["cache"])]
+    pub setup_cache_entries: LabeledFamily<SetupCache, Gauge<usize>>,
 }
 
 #[vise::register]
@@ -0,0 +1,111 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::Context as _;
+use pprof::{protos::Message as _, ProfilerGuard, ProfilerGuardBuilder};
+
+/// Sampling frequency (in Hz) of the profiler. Slightly off from 100 Hz to avoid lockstep sampling with periodic activity.
+const SAMPLING_FREQUENCY: i32 = 99;
+
+/// Output format of the collected profile, determined by the output path extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ProfileFormat {
+    /// Flamegraph rendered as SVG; used for `.svg` paths.
+    Flamegraph,
+    /// `pprof` protobuf; used for all other paths.
+    Protobuf,
+}
+
+impl ProfileFormat {
+    fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("svg") => Self::Flamegraph,
+            _ => Self::Protobuf,
+        }
+    }
+}
+
+/// Sampling profiler running over the process lifetime. The collected profile is written on [`Self::finish()`].
+pub struct Profiler {
+    guard: ProfilerGuard<'static>,
+    output_path: PathBuf,
+}
+
+impl std::fmt::Debug for Profiler {
+    fn fmt(&self, formatter: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        formatter
+            .debug_struct("Profiler")
+            .field("output_path", &self.output_path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Profiler {
+    /// Starts sampling the process. The profile will be written to `output_path`.
+    pub fn start(output_path: PathBuf) -> anyhow::Result<Self> {
+        let guard = ProfilerGuardBuilder::default()
+            .frequency(SAMPLING_FREQUENCY)
+            .blocklist(&["libc", "libgcc", "pthread", "vdso"])
+            .build()
+            .context("failed starting profiler")?;
+        Ok(Self { guard, output_path })
+    }
+
+    pub fn output_path(&self) -> &Path {
+        &self.output_path
+    }
+
+    /// Stops sampling and writes the collected profile to the output path.
+    pub fn finish(self) -> anyhow::Result<()> {
+        let report = self
+            .guard
+            .report()
+            .build()
+            .context("failed building profiler report")?;
+        match ProfileFormat::from_path(&self.output_path) {
+            ProfileFormat::Flamegraph => {
+                let file = fs::File::create(&self.output_path).with_context(|| {
+                    format!("failed creating profile file {:?}", self.output_path)
+                })?;
+                report
+                    .flamegraph(file)
+                    .context("failed writing flamegraph")?;
+            }
+            ProfileFormat::Protobuf => {
+                let profile = report
+                    .pprof()
+                    .context("failed converting report to pprof")?;
+                fs::write(&self.output_path, profile.encode_to_vec())
+                    .with_context(|| format!("failed writing profile to {:?}", self.output_path))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_format_is_detected_from_extension() {
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("/tmp/profile.svg")),
+            ProfileFormat::Flamegraph
+        );
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("profile.SVG")),
+            ProfileFormat::Flamegraph
+        );
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("profile.pb")),
+            ProfileFormat::Protobuf
+        );
+        assert_eq!(
+            ProfileFormat::from_path(Path::new("profile")),
+            ProfileFormat::Protobuf
+        );
+    }
+}
@@ -0,0 +1,71 @@
+use std::{path::PathBuf, time::Duration};
+
+use anyhow::Context as _;
+use url::Url;
+
+/// Location of a config file: either a local path, or an `http(s)://` URL served by a config service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ConfigLocation {
+    File(PathBuf),
+    Url(Url),
+}
+
+impl ConfigLocation {
+    /// Interprets `path` as a URL if it has an `http://` or `https://` scheme; otherwise, as a file path.
+    pub fn new(path: PathBuf) -> anyhow::Result<Self> {
+        let Some(raw) = path.to_str() else {
+            return Ok(Self::File(path));
+        };
+        if !raw.starts_with("http://") && !raw.starts_with("https://") {
+            return Ok(Self::File(path));
+        }
+        let url = raw
+            .parse()
+            .with_context(|| format!("invalid config URL `{raw}`"))?;
+        Ok(Self::Url(url))
+    }
+}
+
+/// Fetches raw config contents from `url`. Fails if the config service doesn't respond with a success status
+/// within `timeout`.
+pub async fn fetch_config(url: &Url, timeout: Duration) -> anyhow::Result<String> {
+    let client = reqwest::Client::builder()
+        .timeout(timeout)
+        .build()
+        .context("failed building HTTP client")?;
+    let response = client
+        .get(url.clone())
+        .send()
+        .await
+        .with_context(|| format!("failed fetching config from {url}"))?;
+    let response = response
+        .error_for_status()
+        .with_context(|| format!("config service returned an error for {url}"))?;
+    response
+        .text()
+        .await
+        .with_context(|| format!("failed reading config fetched from {url}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_location_is_parsed() {
+        assert_eq!(
+            ConfigLocation::new("configs/general.yaml".into()).unwrap(),
+            ConfigLocation::File("configs/general.yaml".into())
+        );
+        // Paths with other schemes have file semantics.
+        assert_eq!(
+            ConfigLocation::new("file:///general.yaml".into()).unwrap(),
+            ConfigLocation::File("file:///general.yaml".into())
+        );
+        assert_eq!(
+            ConfigLocation::new("https://config.local:8080/general.yaml".into()).unwrap(),
+            ConfigLocation::Url("https://config.local:8080/general.yaml".parse().unwrap())
+        );
+        ConfigLocation::new("http://[invalid".into()).unwrap_err();
+    }
+}
@@ -0,0 +1,303 @@
+use std::{
+    collections::HashMap,
+    fs,
+    io::{self, BufWriter, Write},
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::UNIX_EPOCH,
+};
+
+use anyhow::Context as _;
+use serde::{de::DeserializeOwned, Serialize};
+use sha3::{Digest, Keccak256};
+use zksync_prover_fri_types::{ProverServiceDataKey, PROVER_PROTOCOL_SEMANTIC_VERSION};
+
+/// Magic bytes at the start of each snapshot file; the trailing byte is the snapshot format version.
+const SNAPSHOT_MAGIC: &[u8; 8] = b"ZKSCSNP\x02";
+/// Length of the Keccak-256 checksum appended to each snapshot file.
+const CHECKSUM_LEN: usize = 32;
+
+/// Compact binary snapshot of a materialized cache (e.g., [`SetupDataCache`](crate::SetupDataCache)),
+/// allowing to restore the cache with a single sequential read instead of loading it from the keystore.
+///
+/// A snapshot consists of magic bytes, the bincode-serialized protocol version, hash of the source files
+/// (see [`hash_source_files()`]) and cache entries, and the Keccak-256 checksum of all preceding bytes.
+/// Snapshots with a mismatching checksum, created for another protocol version or from other source files
+/// are rejected on restore.
+pub trait CacheSnapshot: Sized {
+    /// Writes a snapshot of this cache, built from the source files with the specified hash, to the specified path.
+    /// The snapshot is written to a temporary file first and then moved to `path`, so that a torn write never
+    /// leaves a partial snapshot behind.
+    fn dump_to(&self, path: &Path, sources_hash: [u8; 32]) -> anyhow::Result<()>;
+
+    /// Restores a cache from a snapshot previously written by [`Self::dump_to()`]. The snapshot is rejected
+    /// if it was built from source files with another hash.
+    fn restore_from(path: &Path, sources_hash: [u8; 32]) -> anyhow::Result<Self>;
+}
+
+impl<T: Serialize + DeserializeOwned> CacheSnapshot for HashMap<ProverServiceDataKey, Arc<T>> {
+    fn dump_to(&self, path: &Path, sources_hash: [u8; 32]) -> anyhow::Result<()> {
+        let version = PROVER_PROTOCOL_SEMANTIC_VERSION.to_string();
+        dump_snapshot(self, &version, sources_hash, path)
+    }
+
+    fn restore_from(path: &Path, sources_hash: [u8; 32]) -> anyhow::Result<Self> {
+        let version = PROVER_PROTOCOL_SEMANTIC_VERSION.to_string();
+        restore_snapshot(&version, sources_hash, path)
+    }
+}
+
+/// Hashes the files a cache is built from, so that a snapshot of the cache is invalidated once any of them changes.
+/// Only file paths, sizes and modification times are hashed; hashing contents would require reading all files,
+/// which is what a snapshot is meant to avoid. All files must exist.
+pub fn hash_source_files(paths: &[PathBuf]) -> anyhow::Result<[u8; 32]> {
+    let mut hasher = Keccak256::new();
+    for path in paths {
+        let metadata =
+            fs::metadata(path).with_context(|| format!("failed reading metadata for {path:?}"))?;
+        let modified_at = metadata
+            .modified()
+            .with_context(|| format!("failed getting modification time for {path:?}"))?
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default();
+
+        let path = path.to_string_lossy();
+        hasher.update((path.len() as u64).to_le_bytes());
+        hasher.update(path.as_bytes());
+        hasher.update(metadata.len().to_le_bytes());
+        hasher.update(modified_at.as_nanos().to_le_bytes());
+    }
+    Ok(hasher.finalize().into())
+}
+
+/// Writer computing the checksum of all data written through it.
+struct ChecksumWriter<W> {
+    inner: W,
+    hasher: Keccak256,
+}
+
+impl<W: Write> Write for ChecksumWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.hasher.update(&buf[..written]);
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+fn dump_snapshot<T: Serialize>(
+    cache: &HashMap<ProverServiceDataKey, Arc<T>>,
+    protocol_version: &str,
+    sources_hash: [u8; 32],
+    path: &Path,
+) -> anyhow::Result<()> {
+    // Sort entries so that snapshots of the same cache are byte-for-byte identical.
+    let mut entries: Vec<_> = cache.iter().map(|(key, value)| (*key, &**value)).collect();
+    entries.sort_by_key(|(key, _)| (key.stage as u8, key.circuit_id));
+
+    let tmp_path = path.with_extension("tmp");
+    let file = fs::File::create(&tmp_path)
+        .with_context(|| format!("failed creating snapshot file {tmp_path:?}"))?;
+    let mut writer = ChecksumWriter {
+        inner: BufWriter::new(file),
+        hasher: Keccak256::new(),
+    };
+    writer.write_all(SNAPSHOT_MAGIC)?;
+    bincode::serialize_into(&mut writer, &(protocol_version, sources_hash, entries))
+        .context("failed serializing snapshot")?;
+
+    let ChecksumWriter { mut inner, hasher } = writer;
+    inner.write_all(&hasher.finalize())?;
+    let file = inner.into_inner().map_err(io::IntoInnerError::into_error)?;
+    file.sync_all()?;
+    drop(file);
+    fs::rename(&tmp_path, path)
+        .with_context(|| format!("failed moving snapshot {tmp_path:?} to {path:?}"))
+}
+
+fn restore_snapshot<T: DeserializeOwned>(
+    expected_protocol_version: &str,
+    expected_sources_hash: [u8; 32],
+    path: &Path,
+) -> anyhow::Result<HashMap<ProverServiceDataKey, Arc<T>>> {
+    let buffer = fs::read(path).with_context(|| format!("failed reading snapshot {path:?}"))?;
+    anyhow::ensure!(
+        buffer.len() >= SNAPSHOT_MAGIC.len() + CHECKSUM_LEN,
+        "snapshot {path:?} is truncated ({} bytes)",
+        buffer.len()
+    );
+    anyhow::ensure!(
+        buffer.starts_with(SNAPSHOT_MAGIC),
+        "{path:?} is not a snapshot or has an unsupported format version"
+    );
+    let (data, checksum) = buffer.split_at(buffer.len() - CHECKSUM_LEN);
+    anyhow::ensure!(
+        Keccak256::digest(data).as_slice() == checksum,
+        "checksum mismatch for snapshot {path:?}; the snapshot is corrupted"
+    );
+
+    let (protocol_version, sources_hash, entries): (
+        String,
+        [u8; 32],
+        Vec<(ProverServiceDataKey, T)>,
+    ) = bincode::deserialize(&data[SNAPSHOT_MAGIC.len()..])
+        .with_context(|| format!("failed deserializing snapshot {path:?}"))?;
+    anyhow::ensure!(
+        protocol_version == expected_protocol_version,
+        "snapshot {path:?} is stale: it was created for protocol version {protocol_version}, \
+         while the expected version is {expected_protocol_version}"
+    );
+    anyhow::ensure!(
+        sources_hash == expected_sources_hash,
+        "snapshot {path:?} is stale: source files have changed since it was created"
+    );
+    Ok(entries
+        .into_iter()
+        .map(|(key, value)| (key, Arc::new(value)))
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_prover_fri_types::ProvingStage;
+    use zksync_prover_keystore::keystore::Keystore;
+
+    use super::*;
+    use crate::FinalizationHintsCache;
+
+    type TestCache = HashMap<ProverServiceDataKey, Arc<Vec<u64>>>;
+
+    const SOURCES_HASH: [u8; 32] = [1; 32];
+
+    fn test_cache() -> TestCache {
+        (1..=5)
+            .map(|circuit_id| {
+                let key = ProverServiceDataKey::new_basic(circuit_id);
+                (key, Arc::new(vec![u64::from(circuit_id); 100]))
+            })
+            .chain([(
+                ProverServiceDataKey::new(1, ProvingStage::LeafAggregation),
+                Arc::new(vec![]),
+            )])
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+        let cache = test_cache();
+        cache.dump_to(&path, SOURCES_HASH).unwrap();
+        assert!(!path.with_extension("tmp").exists());
+
+        let restored = TestCache::restore_from(&path, SOURCES_HASH).unwrap();
+        assert_eq!(restored, cache);
+
+        // Snapshots must be deterministic.
+        let other_path = dir.path().join("other.snapshot");
+        restored.dump_to(&other_path, SOURCES_HASH).unwrap();
+        assert_eq!(fs::read(&path).unwrap(), fs::read(&other_path).unwrap());
+    }
+
+    #[test]
+    fn snapshot_round_trip_for_finalization_hints() {
+        let keystore = Keystore::locate();
+        let cache: FinalizationHintsCache = (1..=3)
+            .map(|circuit_id| {
+                let key = ProverServiceDataKey::new_basic(circuit_id);
+                (
+                    key,
+                    Arc::new(keystore.load_finalization_hints(key).unwrap()),
+                )
+            })
+            .collect();
+
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("hints.snapshot");
+        cache.dump_to(&path, SOURCES_HASH).unwrap();
+        let restored = FinalizationHintsCache::restore_from(&path, SOURCES_HASH).unwrap();
+        assert_eq!(restored.len(), cache.len());
+        for (key, hint) in &cache {
+            assert_eq!(
+                bincode::serialize(&restored[key]).unwrap(),
+                bincode::serialize(hint).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn corrupted_snapshot_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+        test_cache().dump_to(&path, SOURCES_HASH).unwrap();
+
+        let mut buffer = fs::read(&path).unwrap();
+        let idx = buffer.len() / 2;
+        buffer[idx] ^= 1;
+        fs::write(&path, &buffer).unwrap();
+        let err = TestCache::restore_from(&path, SOURCES_HASH).unwrap_err();
+        assert!(err.to_string().contains("checksum mismatch"), "{err}");
+
+        buffer.truncate(10);
+        fs::write(&path, &buffer).unwrap();
+        let err = TestCache::restore_from(&path, SOURCES_HASH).unwrap_err();
+        assert!(err.to_string().contains("truncated"), "{err}");
+
+        fs::write(&path, [0_u8; 64]).unwrap();
+        let err = TestCache::restore_from(&path, SOURCES_HASH).unwrap_err();
+        assert!(err.to_string().contains("not a snapshot"), "{err}");
+    }
+
+    #[test]
+    fn stale_snapshot_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+        dump_snapshot(&test_cache(), "0.1.0", SOURCES_HASH, &path).unwrap();
+
+        let err = TestCache::restore_from(&path, SOURCES_HASH).unwrap_err();
+        assert!(err.to_string().contains("stale"), "{err}");
+        restore_snapshot::<Vec<u64>>("0.1.0", SOURCES_HASH, &path).unwrap();
+    }
+
+    #[test]
+    fn snapshot_for_other_source_files_is_rejected() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("cache.snapshot");
+        test_cache().dump_to(&path, SOURCES_HASH).unwrap();
+
+        let err = TestCache::restore_from(&path, [2; 32]).unwrap_err();
+        assert!(
+            err.to_string().contains("source files have changed"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn hashing_source_files() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let paths = [
+            dir.path().join("setup_1.bin"),
+            dir.path().join("setup_2.bin"),
+        ];
+        for path in &paths {
+            fs::write(path, b"setup data").unwrap();
+        }
+
+        let hash = hash_source_files(&paths).unwrap();
+        assert_eq!(hash_source_files(&paths).unwrap(), hash);
+        // The order of files matters.
+        let reversed_paths = [paths[1].clone(), paths[0].clone()];
+        assert_ne!(hash_source_files(&reversed_paths).unwrap(), hash);
+
+        // Rewriting a file with other data changes the hash.
+        fs::write(&paths[1], b"updated setup data").unwrap();
+        assert_ne!(hash_source_files(&paths).unwrap(), hash);
+
+        fs::remove_file(&paths[0]).unwrap();
+        let err = hash_source_files(&paths).unwrap_err();
+        assert!(err.to_string().contains("failed reading metadata"), "{err}");
+    }
+}
@@ -0,0 +1,104 @@
+use std::path::PathBuf;
+
+use serde::Serialize;
+
+/// Format of the startup summary emitted once circuit prover resources are loaded.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum StartupSummaryFormat {
+    /// No summary is emitted.
+    #[default]
+    None,
+    /// Summary is emitted to stdout as a single-line JSON object. Logs are written to stderr.
+    Json,
+}
+
+/// Machine-readable record of the effective runtime configuration of circuit prover.
+#[derive(Debug, Serialize)]
+pub struct StartupSummary {
+    pub protocol_version: String,
+    pub setup_data_path: PathBuf,
+    pub poll_interval_ms: u64,
+    pub witness_vector_generators: WvgSummary,
+    pub gpu: GpuSummary,
+    /// Number of circuits with loaded setup data.
+    pub setup_data_cache_size: usize,
+    /// Number of circuits with loaded finalization hints.
+    pub finalization_hints_cache_size: usize,
+    pub startup_time_ms: u128,
+}
+
+/// Witness vector generator (WVG) configuration.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum WvgSummary {
+    /// WVGs pick jobs of any type.
+    Simple { threads: usize },
+    /// Fixed number of light and heavy WVGs.
+    Fixed { light: usize, heavy: usize },
+    /// Light vs heavy WVGs are rebalanced based on the job queue.
+    Adaptive {
+        light: usize,
+        heavy: usize,
+        light_min: usize,
+        light_max: usize,
+        heavy_min: usize,
+        heavy_max: usize,
+    },
+}
+
+#[derive(Debug, Serialize)]
+pub struct GpuSummary {
+    /// Max VRAM allocation in bytes; `None` means that all available VRAM is allocated.
+    pub max_allocation: Option<usize>,
+}
+
+impl StartupSummary {
+    /// Serializes the summary as a single-line JSON object.
+    pub fn to_json(&self) -> String {
+        serde_json::to_string(self).expect("failed serializing startup summary")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serializing_summary() {
+        let summary = StartupSummary {
+            protocol_version: "0.28.0".to_owned(),
+            setup_data_path: "/setup".into(),
+            poll_interval_ms: 1_000,
+            witness_vector_generators: WvgSummary::Adaptive {
+                light: 3,
+                heavy: 1,
+                light_min: 1,
+                light_max: 4,
+                heavy_min: 1,
+                heavy_max: 2,
+            },
+            gpu: GpuSummary {
+                max_allocation: None,
+            },
+            setup_data_cache_size: 35,
+            finalization_hints_cache_size: 35,
+            startup_time_ms: 1_234,
+        };
+        let json = summary.to_json();
+        assert!(!json.contains('\n'), "{json}");
+
+        let json: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(json["protocol_version"], "0.28.0");
+        assert_eq!(json["setup_data_path"], "/setup");
+        assert_eq!(json["witness_vector_generators"]["mode"], "adaptive");
+        assert_eq!(json["witness_vector_generators"]["light_max"], 4);
+        assert_eq!(json["gpu"]["max_allocation"], serde_json::Value::Null);
+        assert_eq!(json["setup_data_cache_size"], 35);
+    }
+
+    #[test]
+    fn serializing_simple_wvg_summary() {
+        let json = serde_json::to_value(WvgSummary::Simple { threads: 8 }).unwrap();
+        assert_eq!(json, serde_json::json!({ "mode": "simple", "threads": 8 }));
+    }
+}
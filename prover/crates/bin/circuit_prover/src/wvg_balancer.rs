@@ -0,0 +1,209 @@
+use std::time::Duration;
+
+use anyhow::Context as _;
+use tokio_util::sync::CancellationToken;
+use zksync_prover_dal::{
+    fri_prover_dal::HEAVY_BASIC_CIRCUIT_IDS, ConnectionPool, Prover, ProverDal,
+};
+use zksync_prover_job_processor::WorkerScaler;
+use zksync_types::{basic_fri_types::AggregationRound, protocol_version::ProtocolSemanticVersion};
+
+/// Bounds for the number of WVGs of a single type (light or heavy).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WvgBounds {
+    pub min: usize,
+    pub max: usize,
+}
+
+/// Computes how many (light, heavy) WVGs should be active, given the observed queue.
+///
+/// The total thread budget is split proportionally to the number of queued light & heavy jobs,
+/// respecting per type bounds. If there are no queued jobs, current split is kept.
+pub fn compute_wvg_split(
+    budget: usize,
+    light: WvgBounds,
+    heavy: WvgBounds,
+    queued_light: usize,
+    queued_heavy: usize,
+    current: (usize, usize),
+) -> (usize, usize) {
+    let queued_total = queued_light + queued_heavy;
+    if queued_total == 0 {
+        return current;
+    }
+    // Rounded proportional share of the budget.
+    let heavy_share = (budget * queued_heavy + queued_total / 2) / queued_total;
+    let heavy_count = heavy_share.clamp(heavy.min, heavy.max);
+    let light_count = budget
+        .saturating_sub(heavy_count)
+        .clamp(light.min, light.max);
+    // Light bounds may have taken away from heavy's share, give back what's left of the budget.
+    let heavy_count = budget
+        .saturating_sub(light_count)
+        .clamp(heavy.min, heavy.max);
+    (light_count, heavy_count)
+}
+
+/// Periodically inspects prover job queue and rebalances light vs heavy WVGs,
+/// within a fixed total budget.
+#[derive(Debug)]
+pub struct WvgBalancer {
+    connection_pool: ConnectionPool<Prover>,
+    protocol_version: ProtocolSemanticVersion,
+    light_scaler: WorkerScaler,
+    heavy_scaler: WorkerScaler,
+    budget: usize,
+    interval: Duration,
+    cancellation_token: CancellationToken,
+}
+
+impl WvgBalancer {
+    pub fn new(
+        connection_pool: ConnectionPool<Prover>,
+        protocol_version: ProtocolSemanticVersion,
+        light_scaler: WorkerScaler,
+        heavy_scaler: WorkerScaler,
+        budget: usize,
+        interval: Duration,
+        cancellation_token: CancellationToken,
+    ) -> anyhow::Result<Self> {
+        anyhow::ensure!(
+            light_scaler.min() + heavy_scaler.min() <= budget,
+            "WVG budget {budget} is lower than sum of minimum light ({}) and heavy ({}) WVGs",
+            light_scaler.min(),
+            heavy_scaler.min()
+        );
+        anyhow::ensure!(
+            light_scaler.max() + heavy_scaler.max() >= budget,
+            "WVG budget {budget} is higher than sum of maximum light ({}) and heavy ({}) WVGs",
+            light_scaler.max(),
+            heavy_scaler.max()
+        );
+        Ok(Self {
+            connection_pool,
+            protocol_version,
+            light_scaler,
+            heavy_scaler,
+            budget,
+            interval,
+            cancellation_token,
+        })
+    }
+
+    pub async fn run(self) -> anyhow::Result<()> {
+        while !self.cancellation_token.is_cancelled() {
+            self.rebalance().await?;
+            // Error here corresponds to a timeout w/o receiving cancel; we're OK with this.
+            tokio::time::timeout(self.interval, self.cancellation_token.cancelled())
+                .await
+                .ok();
+        }
+        tracing::info!("Stop request received, shutting down WVG balancer...");
+        Ok(())
+    }
+
+    async fn rebalance(&self) -> anyhow::Result<()> {
+        let (queued_light, queued_heavy) = self.queued_jobs().await?;
+        let current = (self.light_scaler.target(), self.heavy_scaler.target());
+        let (light_count, heavy_count) = compute_wvg_split(
+            self.budget,
+            WvgBounds {
+                min: self.light_scaler.min(),
+                max: self.light_scaler.max(),
+            },
+            WvgBounds {
+                min: self.heavy_scaler.min(),
+                max: self.heavy_scaler.max(),
+            },
+            queued_light,
+            queued_heavy,
+            current,
+        );
+        if (light_count, heavy_count) != current {
+            tracing::info!(
+                "Rebalancing WVGs from {} light/{} heavy to {} light/{} heavy (queued jobs: {} light, {} heavy)",
+                current.0,
+                current.1,
+                light_count,
+                heavy_count,
+                queued_light,
+                queued_heavy
+            );
+            // Scale down first, so that the budget is never exceeded.
+            if light_count < current.0 {
+                self.light_scaler.scale_to(light_count);
+                self.heavy_scaler.scale_to(heavy_count);
+            } else {
+                self.heavy_scaler.scale_to(heavy_count);
+                self.light_scaler.scale_to(light_count);
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns number of queued (light, heavy) jobs for the current protocol version.
+    async fn queued_jobs(&self) -> anyhow::Result<(usize, usize)> {
+        let stats = self
+            .connection_pool
+            .connection()
+            .await
+            .context("failed to get db connection")?
+            .fri_prover_jobs_dal()
+            .get_prover_jobs_stats()
+            .await;
+        let (mut queued_light, mut queued_heavy) = (0, 0);
+        for (protocol_version, circuit_stats) in stats {
+            if protocol_version != self.protocol_version {
+                continue;
+            }
+            for (circuit, job_stats) in circuit_stats {
+                let is_heavy = circuit.aggregation_round == AggregationRound::BasicCircuits as u8
+                    && HEAVY_BASIC_CIRCUIT_IDS.contains(&i16::from(circuit.circuit_id));
+                if is_heavy {
+                    queued_heavy += job_stats.queued;
+                } else {
+                    queued_light += job_stats.queued;
+                }
+            }
+        }
+        Ok((queued_light, queued_heavy))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BOUNDS: WvgBounds = WvgBounds { min: 1, max: 10 };
+
+    #[test]
+    fn split_is_proportional_to_queue() {
+        assert_eq!(
+            compute_wvg_split(12, BOUNDS, BOUNDS, 75, 25, (6, 6)),
+            (9, 3)
+        );
+        assert_eq!(
+            compute_wvg_split(12, BOUNDS, BOUNDS, 25, 75, (6, 6)),
+            (3, 9)
+        );
+    }
+
+    #[test]
+    fn split_respects_bounds() {
+        assert_eq!(
+            compute_wvg_split(12, BOUNDS, BOUNDS, 100, 0, (6, 6)),
+            (10, 2)
+        );
+        assert_eq!(
+            compute_wvg_split(12, BOUNDS, BOUNDS, 0, 100, (6, 6)),
+            (2, 10)
+        );
+        let heavy = WvgBounds { min: 2, max: 4 };
+        assert_eq!(compute_wvg_split(12, BOUNDS, heavy, 1, 100, (6, 6)), (8, 4));
+    }
+
+    #[test]
+    fn split_is_kept_on_empty_queue() {
+        assert_eq!(compute_wvg_split(12, BOUNDS, BOUNDS, 0, 0, (7, 5)), (7, 5));
+    }
+}
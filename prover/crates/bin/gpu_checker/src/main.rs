@@ -78,7 +78,7 @@ async fn create_witness_vector(
             start_time.elapsed()
         );
 
-    let executor = WitnessVectorGeneratorExecutor {};
+    let executor = WitnessVectorGeneratorExecutor::default();
     let wvg = executor.execute(
         WitnessVectorGeneratorPayload {
             circuit_wrapper,
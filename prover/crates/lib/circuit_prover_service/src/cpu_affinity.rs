@@ -0,0 +1,146 @@
+use std::sync::{Arc, Mutex};
+
+use core_affinity::CoreId;
+
+/// Set of CPU cores that Witness Vector Generator (WVG) threads are pinned to.
+///
+/// Each running WVG job exclusively reserves a free core from the set and pins its thread to it;
+/// the core reservation is returned to the set once the job completes. If there are more concurrent jobs than cores,
+/// the excess jobs run unpinned.
+///
+/// **Important.** Pinning is permanent: a thread remains pinned after the job completes, since `core_affinity`
+/// cannot restore the original (multi-core) affinity mask. WVG jobs run on the blocking thread pool, so a thread
+/// is re-pinned once it picks up another WVG job, but it stays pinned if it's reused for other blocking tasks.
+#[derive(Debug)]
+pub struct WvgCpuAffinity {
+    cores: Vec<usize>,
+    free_cores: Mutex<Vec<usize>>,
+}
+
+impl WvgCpuAffinity {
+    /// Creates an affinity for the specified cores, checking them against the cores available to the process.
+    /// Available cores may be non-contiguous (e.g., if the process is restricted by a cgroup cpuset).
+    pub fn new(cores: Vec<usize>) -> anyhow::Result<Self> {
+        let available_cores: Vec<_> = core_affinity::get_core_ids()
+            .ok_or_else(|| anyhow::anyhow!("failed to get available CPU cores"))?
+            .into_iter()
+            .map(|core_id| core_id.id)
+            .collect();
+        Self::with_available_cores(cores, &available_cores)
+    }
+
+    fn with_available_cores(cores: Vec<usize>, available_cores: &[usize]) -> anyhow::Result<Self> {
+        anyhow::ensure!(!cores.is_empty(), "CPU list for WVG affinity is empty");
+        for (i, &core) in cores.iter().enumerate() {
+            anyhow::ensure!(
+                available_cores.contains(&core),
+                "CPU core {core} is not available; available cores: {available_cores:?}"
+            );
+            anyhow::ensure!(
+                !cores[..i].contains(&core),
+                "CPU core {core} is specified multiple times"
+            );
+        }
+
+        // Cores are popped from the end, so reverse them to take cores in the specified order.
+        let free_cores = cores.iter().rev().copied().collect();
+        Ok(Self {
+            cores,
+            free_cores: Mutex::new(free_cores),
+        })
+    }
+
+    /// Returns cores that WVG threads are pinned to.
+    pub fn cores(&self) -> &[usize] {
+        &self.cores
+    }
+
+    fn reserve(self: &Arc<Self>) -> Option<CoreReservation> {
+        let core = self.free_cores.lock().unwrap().pop()?;
+        Some(CoreReservation {
+            affinity: self.clone(),
+            core,
+        })
+    }
+
+    /// Reserves a free core for a WVG job and permanently pins the current thread to it. Dropping
+    /// the returned reservation makes the core available to other jobs, but does not unpin the thread.
+    pub(crate) fn pin_current_thread(self: &Arc<Self>) -> Option<CoreReservation> {
+        let Some(reservation) = self.reserve() else {
+            tracing::debug!("All WVG cores are busy; running WVG job unpinned");
+            return None;
+        };
+        if !core_affinity::set_for_current(CoreId {
+            id: reservation.core,
+        }) {
+            tracing::warn!("Failed pinning WVG thread to CPU core {}", reservation.core);
+        }
+        Some(reservation)
+    }
+}
+
+/// Core exclusively reserved by a WVG job.
+#[derive(Debug)]
+pub(crate) struct CoreReservation {
+    affinity: Arc<WvgCpuAffinity>,
+    core: usize,
+}
+
+impl Drop for CoreReservation {
+    fn drop(&mut self) {
+        self.affinity.free_cores.lock().unwrap().push(self.core);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn validating_cores() {
+        let affinity = WvgCpuAffinity::with_available_cores(vec![0, 2, 3], &[0, 1, 2, 3]).unwrap();
+        assert_eq!(affinity.cores(), [0, 2, 3]);
+
+        let err = WvgCpuAffinity::with_available_cores(vec![0, 4], &[0, 1, 2, 3]).unwrap_err();
+        assert!(err.to_string().contains("not available"), "{err}");
+        let err = WvgCpuAffinity::with_available_cores(vec![1, 1], &[0, 1, 2, 3]).unwrap_err();
+        assert!(err.to_string().contains("multiple times"), "{err}");
+        let err = WvgCpuAffinity::with_available_cores(vec![], &[0, 1, 2, 3]).unwrap_err();
+        assert!(err.to_string().contains("empty"), "{err}");
+    }
+
+    #[test]
+    fn validating_cores_with_non_contiguous_availability() {
+        // E.g., the process is restricted to cores 2, 3, 6 and 7 by a cpuset.
+        let available_cores = [2, 3, 6, 7];
+        let affinity = WvgCpuAffinity::with_available_cores(vec![3, 6], &available_cores).unwrap();
+        assert_eq!(affinity.cores(), [3, 6]);
+
+        // Core 1 is within the number of available cores, but isn't available itself.
+        let err = WvgCpuAffinity::with_available_cores(vec![1], &available_cores).unwrap_err();
+        assert!(
+            err.to_string().contains("CPU core 1 is not available"),
+            "{err}"
+        );
+        let err = WvgCpuAffinity::with_available_cores(vec![4, 6], &available_cores).unwrap_err();
+        assert!(
+            err.to_string().contains("CPU core 4 is not available"),
+            "{err}"
+        );
+    }
+
+    #[test]
+    fn cores_are_held_exclusively() {
+        let affinity =
+            Arc::new(WvgCpuAffinity::with_available_cores(vec![1, 3], &[0, 1, 2, 3]).unwrap());
+        let first = affinity.reserve().unwrap();
+        assert_eq!(first.core, 1);
+        let second = affinity.reserve().unwrap();
+        assert_eq!(second.core, 3);
+        assert!(affinity.reserve().is_none());
+
+        drop(first);
+        let third = affinity.reserve().unwrap();
+        assert_eq!(third.core, 1);
+    }
+}
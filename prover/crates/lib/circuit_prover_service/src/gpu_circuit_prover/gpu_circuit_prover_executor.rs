@@ -33,7 +33,12 @@ impl Executor for GpuCircuitProverExecutor {
     #[tracing::instrument(
         name = "gpu_circuit_prover_executor",
         skip_all,
-        fields(l1_batch = % metadata.batch_id)
+        fields(
+            l1_batch = %metadata.batch_id,
+            job_id = metadata.id,
+            circuit_type = metadata.circuit_id,
+            round = %metadata.aggregation_round
+        )
     )]
     fn execute(
         &self,
@@ -2,39 +2,50 @@ use std::{collections::HashMap, sync::Arc, time::Instant};
 
 use anyhow::Context;
 use async_trait::async_trait;
+use zksync_prover_dal::{ConnectionPool, Prover, ProverDal};
 use zksync_prover_fri_types::ProverServiceDataKey;
 use zksync_prover_job_processor::JobPicker;
 use zksync_types::prover_dal::FriProverJobMetadata;
 
 use crate::{
     gpu_circuit_prover::GpuCircuitProverExecutor,
-    metrics::CIRCUIT_PROVER_METRICS,
+    job_fields::record_job_fields,
+    metrics::{lookup_setup_cache, SetupCache, CIRCUIT_PROVER_METRICS},
     types::{
         circuit_prover_payload::GpuCircuitProverPayload, setup_data::GoldilocksGpuProverSetupData,
         witness_vector_generator_execution_output::WitnessVectorGeneratorExecutionOutput,
     },
+    unservable_circuits::UnservableCircuits,
 };
 
 /// GpuCircuitProver job picker implementation.
 /// Retrieves job & data from WVG job saver.
+/// Jobs for circuits without loaded setup data are re-queued in database; such circuits are recorded
+/// as unservable, so that witness vector generators don't pick jobs for them afterward.
 #[derive(Debug)]
 pub struct GpuCircuitProverJobPicker {
+    connection_pool: ConnectionPool<Prover>,
     receiver:
         tokio::sync::mpsc::Receiver<(WitnessVectorGeneratorExecutionOutput, FriProverJobMetadata)>,
     setup_data_cache: HashMap<ProverServiceDataKey, Arc<GoldilocksGpuProverSetupData>>,
+    unservable_circuits: UnservableCircuits,
 }
 
 impl GpuCircuitProverJobPicker {
     pub fn new(
+        connection_pool: ConnectionPool<Prover>,
         receiver: tokio::sync::mpsc::Receiver<(
             WitnessVectorGeneratorExecutionOutput,
             FriProverJobMetadata,
         )>,
         setup_data_cache: HashMap<ProverServiceDataKey, Arc<GoldilocksGpuProverSetupData>>,
+        unservable_circuits: UnservableCircuits,
     ) -> Self {
         Self {
+            connection_pool,
             receiver,
             setup_data_cache,
+            unservable_circuits,
         }
     }
 }
@@ -43,49 +54,81 @@ impl GpuCircuitProverJobPicker {
 impl JobPicker for GpuCircuitProverJobPicker {
     type ExecutorType = GpuCircuitProverExecutor;
 
+    #[tracing::instrument(
+        name = "gpu_circuit_prover_job_picker",
+        skip_all,
+        fields(
+            l1_batch = tracing::field::Empty,
+            job_id = tracing::field::Empty,
+            circuit_type = tracing::field::Empty,
+            round = tracing::field::Empty
+        )
+    )]
     async fn pick_job(
         &mut self,
     ) -> anyhow::Result<Option<(GpuCircuitProverPayload, FriProverJobMetadata)>> {
         let start_time = Instant::now();
         tracing::info!("Started picking gpu circuit prover job");
 
-        let (wvg_output, metadata) = self
-            .receiver
-            .recv()
-            .await
-            .context("no witness vector generators are available, stopping...")?;
-        let WitnessVectorGeneratorExecutionOutput {
-            circuit_wrapper,
-            witness_vector,
-        } = wvg_output;
+        loop {
+            let (wvg_output, metadata) = self
+                .receiver
+                .recv()
+                .await
+                .context("no witness vector generators are available, stopping...")?;
+            record_job_fields(&tracing::Span::current(), &metadata);
+            let WitnessVectorGeneratorExecutionOutput {
+                circuit_wrapper,
+                witness_vector,
+                circuit_type,
+            } = wvg_output;
+            CIRCUIT_PROVER_METRICS.received_witness_vectors[&circuit_type].inc();
 
-        let key = ProverServiceDataKey {
-            circuit_id: metadata.circuit_id,
-            stage: metadata.aggregation_round.into(),
-        }
-        .crypto_setup_key();
-        let setup_data = self
-            .setup_data_cache
-            .get(&key)
-            .context("failed to retrieve setup data from cache")?
-            .clone();
+            let key = ProverServiceDataKey {
+                circuit_id: metadata.circuit_id,
+                stage: metadata.aggregation_round.into(),
+            }
+            .crypto_setup_key();
+            let Some(setup_data) = lookup_setup_cache(
+                &self.setup_data_cache,
+                &key,
+                SetupCache::SetupData,
+                circuit_type,
+            ) else {
+                tracing::error!(
+                    "Re-queueing gpu circuit prover job {}, on batch {}: no setup data loaded for circuit {} at round {}; \
+                     jobs for this circuit won't be picked anymore",
+                    metadata.id,
+                    metadata.batch_id,
+                    metadata.circuit_id,
+                    metadata.aggregation_round
+                );
+                self.unservable_circuits.insert(&metadata);
+                self.connection_pool
+                    .connection()
+                    .await
+                    .context("failed to get db connection")?
+                    .fri_prover_jobs_dal()
+                    .requeue_job_without_attempt(metadata.id, metadata.batch_id.chain_id())
+                    .await;
+                continue;
+            };
 
-        let payload = GpuCircuitProverPayload {
-            circuit_wrapper,
-            witness_vector,
-            setup_data,
-        };
-        tracing::info!(
-            "Finished picking gpu circuit prover job {}, on batch {}, for circuit {}, at round {} in {:?}",
-            metadata.id,
-            metadata.batch_id,
-            metadata.circuit_id,
-            metadata.aggregation_round,
-            start_time.elapsed()
-        );
-        CIRCUIT_PROVER_METRICS
-            .load_time
-            .observe(start_time.elapsed());
-        Ok(Some((payload, metadata)))
+            let payload = GpuCircuitProverPayload {
+                circuit_wrapper,
+                witness_vector,
+                setup_data,
+            };
+            tracing::info!(
+                "Finished picking gpu circuit prover job {}, on batch {}, for circuit {}, at round {} in {:?}",
+                metadata.id,
+                metadata.batch_id,
+                metadata.circuit_id,
+                metadata.aggregation_round,
+                start_time.elapsed()
+            );
+            CIRCUIT_PROVER_METRICS.load_time[&circuit_type].observe(start_time.elapsed());
+            return Ok(Some((payload, metadata)));
+        }
     }
 }
@@ -2,7 +2,7 @@ use std::{sync::Arc, time::Instant};
 
 use anyhow::Context;
 use async_trait::async_trait;
-use zksync_object_store::ObjectStore;
+use zksync_object_store::{ObjectStore, StoredObject};
 use zksync_prover_dal::{ConnectionPool, Prover, ProverDal};
 use zksync_prover_fri_types::FriProofWrapper;
 use zksync_prover_job_processor::JobSaver;
@@ -12,10 +12,12 @@ use crate::{gpu_circuit_prover::GpuCircuitProverExecutor, metrics::CIRCUIT_PROVE
 
 /// GpuCircuitProver job saver implementation.
 /// Persists the job execution to database. In case of success, artifacts are uploaded to object store.
+/// If a secondary object store is configured, proofs are additionally uploaded to it on a best-effort basis.
 #[derive(Debug)]
 pub struct GpuCircuitProverJobSaver {
     connection_pool: ConnectionPool<Prover>,
     object_store: Arc<dyn ObjectStore>,
+    secondary_object_store: Option<Arc<dyn ObjectStore>>,
     protocol_version: ProtocolSemanticVersion,
 }
 
@@ -28,9 +30,44 @@ impl GpuCircuitProverJobSaver {
         Self {
             connection_pool,
             object_store,
+            secondary_object_store: None,
             protocol_version,
         }
     }
+
+    /// Sets the secondary object store that proofs are mirrored to. Failures to upload to it are logged,
+    /// but do not fail the job.
+    pub fn with_secondary_object_store(
+        mut self,
+        secondary_object_store: Option<Arc<dyn ObjectStore>>,
+    ) -> Self {
+        self.secondary_object_store = secondary_object_store;
+        self
+    }
+}
+
+/// Uploads `value` to the primary object store and, if provided, to the secondary one.
+/// Returns the key in the primary store. Only a failed primary upload results in an error.
+pub(crate) async fn upload_to_object_stores<'k, V: StoredObject>(
+    object_store: &dyn ObjectStore,
+    secondary_object_store: Option<&dyn ObjectStore>,
+    key: V::Key<'k>,
+    value: &V,
+) -> anyhow::Result<String>
+where
+    V::Key<'k>: Copy,
+{
+    let blob_url = object_store
+        .put(key, value)
+        .await
+        .context("failed to upload to object store")?;
+    if let Some(secondary_object_store) = secondary_object_store {
+        if let Err(err) = secondary_object_store.put(key, value).await {
+            tracing::warn!("Failed to upload {blob_url} to secondary object store: {err}");
+            CIRCUIT_PROVER_METRICS.secondary_upload_failures.inc();
+        }
+    }
+    Ok(blob_url)
 }
 
 #[async_trait]
@@ -40,7 +77,12 @@ impl JobSaver for GpuCircuitProverJobSaver {
     #[tracing::instrument(
         name = "gpu_circuit_prover_job_saver",
         skip_all,
-        fields(l1_batch = % data.1.batch_id)
+        fields(
+            l1_batch = %data.1.batch_id,
+            job_id = data.1.id,
+            circuit_type = data.1.circuit_id,
+            round = %data.1.aggregation_round
+        )
     )]
     async fn save_job_result(
         &self,
@@ -66,11 +108,13 @@ impl JobSaver for GpuCircuitProverJobSaver {
 
                 let is_scheduler_proof = metadata.is_scheduler_proof()?;
 
-                let blob_url = self
-                    .object_store
-                    .put((metadata.id, metadata.batch_id.chain_id()), &proof_wrapper)
-                    .await
-                    .context("failed to upload to object store")?;
+                let blob_url = upload_to_object_stores(
+                    self.object_store.as_ref(),
+                    self.secondary_object_store.as_deref(),
+                    (metadata.id, metadata.batch_id.chain_id()),
+                    &proof_wrapper,
+                )
+                .await?;
 
                 let mut transaction = connection
                     .start_transaction()
@@ -101,6 +145,7 @@ impl JobSaver for GpuCircuitProverJobSaver {
                     .commit()
                     .await
                     .context("failed to commit db transaction")?;
+                CIRCUIT_PROVER_METRICS.completed_jobs.inc();
             }
             Err(error) => {
                 let error_message = error.to_string();
@@ -131,3 +176,151 @@ impl JobSaver for GpuCircuitProverJobSaver {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use async_trait::async_trait;
+    use tracing_test::traced_test;
+    use zksync_object_store::{Bucket, MockObjectStore, ObjectStoreError};
+    use zksync_types::{
+        basic_fri_types::AggregationRound, snapshots::SnapshotFactoryDependencies, L1BatchId,
+        L1BatchNumber,
+    };
+
+    use super::*;
+
+    /// Object store failing all operations.
+    #[derive(Debug)]
+    struct FailingObjectStore;
+
+    impl FailingObjectStore {
+        fn error() -> ObjectStoreError {
+            ObjectStoreError::Other {
+                source: "store is unavailable".into(),
+                is_retriable: false,
+            }
+        }
+    }
+
+    #[async_trait]
+    impl ObjectStore for FailingObjectStore {
+        async fn get_raw(&self, _bucket: Bucket, _key: &str) -> Result<Vec<u8>, ObjectStoreError> {
+            Err(Self::error())
+        }
+
+        async fn put_raw(
+            &self,
+            _bucket: Bucket,
+            _key: &str,
+            _value: Vec<u8>,
+        ) -> Result<(), ObjectStoreError> {
+            Err(Self::error())
+        }
+
+        async fn remove_raw(&self, _bucket: Bucket, _key: &str) -> Result<(), ObjectStoreError> {
+            Err(Self::error())
+        }
+
+        fn storage_prefix_raw(&self, bucket: Bucket) -> String {
+            bucket.to_string()
+        }
+    }
+
+    fn test_object() -> SnapshotFactoryDependencies {
+        SnapshotFactoryDependencies {
+            factory_deps: vec![],
+        }
+    }
+
+    #[tokio::test]
+    async fn uploading_to_both_object_stores() {
+        let primary = MockObjectStore::arc();
+        let secondary = MockObjectStore::arc();
+        let key = L1BatchNumber(1);
+        let blob_url = upload_to_object_stores(&*primary, Some(&*secondary), key, &test_object())
+            .await
+            .unwrap();
+
+        assert_eq!(blob_url, SnapshotFactoryDependencies::encode_key(key));
+        primary
+            .get::<SnapshotFactoryDependencies>(key)
+            .await
+            .unwrap();
+        secondary
+            .get::<SnapshotFactoryDependencies>(key)
+            .await
+            .unwrap();
+    }
+
+    #[tokio::test]
+    async fn primary_upload_failure_fails_job() {
+        let secondary = MockObjectStore::arc();
+        let key = L1BatchNumber(1);
+        let err =
+            upload_to_object_stores(&FailingObjectStore, Some(&*secondary), key, &test_object())
+                .await
+                .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("failed to upload to object store"),
+            "{err:#}"
+        );
+    }
+
+    #[tokio::test]
+    async fn secondary_upload_failure_is_tolerated() {
+        let primary = MockObjectStore::arc();
+        let key = L1BatchNumber(1);
+        let failures_before = CIRCUIT_PROVER_METRICS.secondary_upload_failures.get();
+        let blob_url =
+            upload_to_object_stores(&*primary, Some(&FailingObjectStore), key, &test_object())
+                .await
+                .unwrap();
+
+        assert_eq!(blob_url, SnapshotFactoryDependencies::encode_key(key));
+        primary
+            .get::<SnapshotFactoryDependencies>(key)
+            .await
+            .unwrap();
+        assert!(CIRCUIT_PROVER_METRICS.secondary_upload_failures.get() > failures_before);
+    }
+
+    #[tokio::test]
+    #[traced_test]
+    async fn saving_job_result_is_logged_with_job_fields() {
+        let pool = ConnectionPool::<Prover>::prover_test_pool().await;
+        let saver = GpuCircuitProverJobSaver::new(pool, MockObjectStore::arc(), Default::default());
+        let metadata = FriProverJobMetadata {
+            id: 42,
+            batch_id: L1BatchId::from_raw(270, 7),
+            batch_sealed_at: Default::default(),
+            circuit_id: 3,
+            aggregation_round: AggregationRound::LeafAggregation,
+            sequence_number: 0,
+            depth: 0,
+            is_node_final_proof: false,
+            pick_time: Instant::now(),
+        };
+
+        saver
+            .save_job_result((Err(anyhow::anyhow!("proving failed")), metadata))
+            .await
+            .unwrap();
+
+        logs_assert(|lines| {
+            let line = lines
+                .iter()
+                .find(|line| line.contains("GPU circuit prover failed"))
+                .ok_or("no job failure log event")?;
+            let expected_fields = [
+                "gpu_circuit_prover_job_saver{",
+                "job_id=42",
+                "circuit_type=3",
+                "round=leaf_aggregation",
+            ];
+            match expected_fields.iter().find(|field| !line.contains(*field)) {
+                None => Ok(()),
+                Some(field) => Err(format!("`{field}` is missing from `{line}`")),
+            }
+        });
+    }
+}
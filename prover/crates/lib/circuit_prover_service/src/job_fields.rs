@@ -0,0 +1,69 @@
+//! Structured identifiers of prover jobs attached to tracing spans, so that log events emitted while processing
+//! a job can be correlated across the witness vector generator and GPU circuit prover stages.
+
+use tracing::Span;
+use zksync_types::prover_dal::FriProverJobMetadata;
+
+/// Records identifiers of the job being processed as fields of `span`. The span must declare `l1_batch`, `job_id`,
+/// `circuit_type` and `round` fields (e.g., as [`tracing::field::Empty`] if the job is not known when the span
+/// is created); undeclared fields are ignored.
+pub(crate) fn record_job_fields(span: &Span, metadata: &FriProverJobMetadata) {
+    span.record("l1_batch", tracing::field::display(metadata.batch_id));
+    span.record("job_id", metadata.id);
+    span.record("circuit_type", metadata.circuit_id);
+    span.record("round", tracing::field::display(metadata.aggregation_round));
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use tracing::field::Empty;
+    use tracing_test::traced_test;
+    use zksync_types::{basic_fri_types::AggregationRound, L1BatchId, L1BatchNumber, L2ChainId};
+
+    use super::*;
+
+    #[traced_test]
+    #[test]
+    fn job_fields_are_attached_to_log_events() {
+        let metadata = FriProverJobMetadata {
+            id: 42,
+            batch_id: L1BatchId::new(L2ChainId::default(), L1BatchNumber(7)),
+            batch_sealed_at: Default::default(),
+            circuit_id: 3,
+            aggregation_round: AggregationRound::LeafAggregation,
+            sequence_number: 0,
+            depth: 0,
+            is_node_final_proof: false,
+            pick_time: Instant::now(),
+        };
+        let span = tracing::info_span!(
+            "test_job",
+            l1_batch = Empty,
+            job_id = Empty,
+            circuit_type = Empty,
+            round = Empty
+        );
+        let _guard = span.enter();
+        tracing::info!("before picking job");
+        record_job_fields(&span, &metadata);
+        tracing::info!("processing job");
+
+        assert!(logs_contain("job_id=42"));
+        assert!(logs_contain("circuit_type=3"));
+        assert!(logs_contain("round=leaf_aggregation"));
+        assert!(logs_contain("batch_number: 7"));
+        logs_assert(|lines| {
+            let line = lines
+                .iter()
+                .find(|line| line.contains("processing job"))
+                .ok_or("no job log event")?;
+            if line.contains("job_id=42") {
+                Ok(())
+            } else {
+                Err(format!("job ID is missing from `{line}`"))
+            }
+        });
+    }
+}
@@ -12,6 +12,7 @@ use zksync_prover_job_processor::{Backoff, BackoffAndCancellable, JobRunner};
 use zksync_types::{protocol_version::ProtocolSemanticVersion, prover_dal::FriProverJobMetadata};
 
 use crate::{
+    cpu_affinity::WvgCpuAffinity,
     gpu_circuit_prover::{
         GpuCircuitProverExecutor, GpuCircuitProverJobPicker, GpuCircuitProverJobSaver,
     },
@@ -19,6 +20,7 @@ use crate::{
         setup_data::GoldilocksGpuProverSetupData,
         witness_vector_generator_execution_output::WitnessVectorGeneratorExecutionOutput,
     },
+    unservable_circuits::UnservableCircuits,
     witness_vector_generator::{
         HeavyWitnessVectorMetadataLoader, LightWitnessVectorMetadataLoader,
         SimpleWitnessVectorMetadataLoader, WitnessVectorGeneratorExecutor,
@@ -38,6 +40,11 @@ pub struct WvgRunnerBuilder {
         tokio::sync::mpsc::Sender<(WitnessVectorGeneratorExecutionOutput, FriProverJobMetadata)>,
     cancellation_token: CancellationToken,
     pod_name: String,
+    backoff: Backoff,
+    read_pool: Option<ConnectionPool<Prover>>,
+    cpu_affinity: Option<Arc<WvgCpuAffinity>>,
+    max_witness_vector_bytes: Option<usize>,
+    unservable_circuits: UnservableCircuits,
 }
 
 impl WvgRunnerBuilder {
@@ -60,9 +67,51 @@ impl WvgRunnerBuilder {
             sender,
             cancellation_token,
             pod_name: get_current_pod_name(),
+            backoff: Backoff::default(),
+            read_pool: None,
+            cpu_affinity: None,
+            max_witness_vector_bytes: None,
+            unservable_circuits: UnservableCircuits::default(),
         }
     }
 
+    /// Sets a read-only connection pool (e.g., to a DB replica) used by job pickers to check for available jobs.
+    /// Jobs are still locked and saved using the main connection pool.
+    pub fn with_read_pool(mut self, read_pool: Option<ConnectionPool<Prover>>) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    /// Pins WVG threads to the specified CPU cores. The affinity is shared among all runners created by the builder.
+    pub fn with_cpu_affinity(mut self, cpu_affinity: Option<WvgCpuAffinity>) -> Self {
+        self.cpu_affinity = cpu_affinity.map(Arc::new);
+        self
+    }
+
+    /// Fails WVG jobs producing witness vectors larger than the specified number of bytes, instead of sending them
+    /// to the circuit prover. By default, the size is unlimited.
+    pub fn with_max_witness_vector_bytes(
+        mut self,
+        max_witness_vector_bytes: Option<usize>,
+    ) -> Self {
+        self.max_witness_vector_bytes = max_witness_vector_bytes;
+        self
+    }
+
+    /// Sets circuits that cannot be processed by the prover. Should be shared with the circuit prover runner,
+    /// so that jobs re-queued by it because of missing setup data aren't picked again.
+    pub fn with_unservable_circuits(mut self, unservable_circuits: UnservableCircuits) -> Self {
+        self.unservable_circuits = unservable_circuits;
+        self
+    }
+
+    /// Sets backoff used by job pickers when there are no jobs available in database.
+    /// By default, [`Backoff::default()`] is used.
+    pub fn with_backoff(mut self, backoff: Backoff) -> Self {
+        self.backoff = backoff;
+        self
+    }
+
     /// Witness Vector Generator runner implementation for light jobs.
     pub fn light_wvg_runner(
         &self,
@@ -119,24 +168,26 @@ impl WvgRunnerBuilder {
         WitnessVectorGeneratorJobPicker<ML>,
         WitnessVectorGeneratorJobSaver,
     > {
-        let executor = WitnessVectorGeneratorExecutor;
+        let executor = WitnessVectorGeneratorExecutor::default()
+            .with_cpu_affinity(self.cpu_affinity.clone())
+            .with_max_witness_vector_bytes(self.max_witness_vector_bytes);
         let job_picker = WitnessVectorGeneratorJobPicker::new(
             self.connection_pool.clone(),
             self.object_store.clone(),
             self.finalization_hints_cache.clone(),
             metadata_loader,
-        );
+        )
+        .with_read_pool(self.read_pool.clone())
+        .with_unservable_circuits(self.unservable_circuits.clone());
         let job_saver =
             WitnessVectorGeneratorJobSaver::new(self.connection_pool.clone(), self.sender.clone());
-        let backoff = Backoff::default();
-
         JobRunner::new(
             executor,
             job_picker,
             job_saver,
             count,
             Some(BackoffAndCancellable::new(
-                backoff,
+                self.backoff.clone(),
                 self.cancellation_token.clone(),
             )),
         )
@@ -144,9 +195,13 @@ impl WvgRunnerBuilder {
 }
 
 /// Circuit Prover runner implementation.
+/// If `secondary_object_store` is provided, proofs are additionally uploaded to it (on a best-effort basis).
+/// Circuits without loaded setup data are recorded in `unservable_circuits`, which should be shared
+/// with WVG runners (see [`WvgRunnerBuilder::with_unservable_circuits()`]).
 pub fn circuit_prover_runner(
     connection_pool: ConnectionPool<Prover>,
     object_store: Arc<dyn ObjectStore>,
+    secondary_object_store: Option<Arc<dyn ObjectStore>>,
     protocol_version: ProtocolSemanticVersion,
     setup_data_cache: HashMap<ProverServiceDataKey, Arc<GoldilocksGpuProverSetupData>>,
     receiver: tokio::sync::mpsc::Receiver<(
@@ -154,9 +209,16 @@ pub fn circuit_prover_runner(
         FriProverJobMetadata,
     )>,
     prover_context: ProverContext,
+    unservable_circuits: UnservableCircuits,
 ) -> JobRunner<GpuCircuitProverExecutor, GpuCircuitProverJobPicker, GpuCircuitProverJobSaver> {
     let executor = GpuCircuitProverExecutor::new(prover_context);
-    let job_picker = GpuCircuitProverJobPicker::new(receiver, setup_data_cache);
-    let job_saver = GpuCircuitProverJobSaver::new(connection_pool, object_store, protocol_version);
+    let job_picker = GpuCircuitProverJobPicker::new(
+        connection_pool.clone(),
+        receiver,
+        setup_data_cache,
+        unservable_circuits,
+    );
+    let job_saver = GpuCircuitProverJobSaver::new(connection_pool, object_store, protocol_version)
+        .with_secondary_object_store(secondary_object_store);
     JobRunner::new(executor, job_picker, job_saver, 1, None)
 }
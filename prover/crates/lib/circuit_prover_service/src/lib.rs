@@ -2,8 +2,15 @@
 // Crypto code uses generic const exprs, allocator_api is needed to use global allocators
 #![feature(generic_const_exprs, allocator_api)]
 
+pub use cpu_affinity::WvgCpuAffinity;
+pub use metrics::{setup_cache_lookup_stats, CacheLookupStats, SetupCache, CIRCUIT_PROVER_METRICS};
+pub use unservable_circuits::UnservableCircuits;
+
+mod cpu_affinity;
 pub mod gpu_circuit_prover;
+mod job_fields;
 pub mod job_runner;
 mod metrics;
 pub mod types;
+mod unservable_circuits;
 pub mod witness_vector_generator;
@@ -1,6 +1,26 @@
-use std::time::Duration;
+use std::{collections::HashMap, hash::Hash, sync::Arc, time::Duration};
 
-use vise::{Buckets, Histogram, Metrics};
+use vise::{
+    Buckets, Counter, EncodeLabelSet, EncodeLabelValue, Family, Histogram, LabeledFamily, Metrics,
+};
+use zksync_types::{basic_fri_types::AggregationRound, prover_dal::FriProverJobMetadata};
+
+/// Type of the circuit a witness vector was generated for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue, EncodeLabelSet)]
+#[metrics(label = "circuit_type", rename_all = "snake_case")]
+pub enum CircuitType {
+    Base,
+    Recursive,
+}
+
+impl CircuitType {
+    pub fn from_metadata(metadata: &FriProverJobMetadata) -> Self {
+        match metadata.aggregation_round {
+            AggregationRound::BasicCircuits => Self::Base,
+            _ => Self::Recursive,
+        }
+    }
+}
 
 /// Metrics for witness vector generator execution
 #[derive(Debug, Metrics)]
@@ -14,10 +34,14 @@ pub struct WitnessVectorGeneratorMetrics {
     pub synthesize_time: Histogram<Duration>,
     /// How long does it take to send witness vectors to gpu prover?
     #[metrics(buckets = Buckets::LATENCIES)]
-    pub transfer_time: Histogram<Duration>,
+    pub transfer_time: Family<CircuitType, Histogram<Duration>>,
+    /// Number of witness vectors sent to gpu prover.
+    pub transferred_witness_vectors: Family<CircuitType, Counter>,
     /// How long does it take to save witness vector failure?
     #[metrics(buckets = Buckets::LATENCIES)]
     pub save_time: Histogram<Duration>,
+    /// Number of jobs failed because the produced witness vector exceeded the configured size limit.
+    pub oversized_witness_vectors: Family<CircuitType, Counter>,
 }
 
 #[vise::register]
@@ -28,19 +52,194 @@ pub static WITNESS_VECTOR_GENERATOR_METRICS: vise::Global<WitnessVectorGenerator
 #[derive(Debug, Metrics)]
 #[metrics(prefix = "circuit_prover")]
 pub struct CircuitProverMetrics {
-    /// How long does it take to load prover inputs?
+    /// How long does it take to load prover inputs (including waiting for witness vectors)?
     #[metrics(buckets = Buckets::LATENCIES)]
-    pub load_time: Histogram<Duration>,
+    pub load_time: Family<CircuitType, Histogram<Duration>>,
+    /// Number of witness vectors received from witness vector generators.
+    pub received_witness_vectors: Family<CircuitType, Counter>,
     /// How long does it take to prove & verify?
     #[metrics(buckets = Buckets::LATENCIES)]
     pub prove_and_verify_time: Histogram<Duration>,
+    /// How long does it take to verify a generated proof?
+    #[metrics(buckets = Buckets::LATENCIES)]
+    pub proof_verification_time: Family<CircuitType, Histogram<Duration>>,
+    /// Number of verified proofs, per circuit type & outcome.
+    #[metrics(labels = ["circuit_type", "result"])]
+    pub proof_verifications: LabeledFamily<(CircuitType, VerificationResult), Counter, 2>,
     /// How long does it take to save prover results?
     #[metrics(buckets = Buckets::LATENCIES)]
     pub save_time: Histogram<Duration>,
     /// How long does it take finish a prover job from witness vector to circuit prover?
     #[metrics(buckets = Buckets::LATENCIES)]
     pub full_time: Histogram<Duration>,
+    /// Number of successfully proven jobs.
+    pub completed_jobs: Counter,
+    /// Number of proofs that failed to upload to the secondary object store.
+    pub secondary_upload_failures: Counter,
+    /// Number of lookups in setup caches (finalization hints used by witness vector generators and setup data
+    /// used by the gpu prover), per cache, circuit type & outcome.
+    #[metrics(labels = ["cache", "circuit_type", "result"])]
+    pub setup_cache_lookups:
+        LabeledFamily<(SetupCache, CircuitType, CacheLookupResult), Counter, 3>,
 }
 
 #[vise::register]
 pub static CIRCUIT_PROVER_METRICS: vise::Global<CircuitProverMetrics> = vise::Global::new();
+
+/// In-memory cache holding per-circuit data loaded at prover startup.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub enum SetupCache {
+    FinalizationHints,
+    SetupData,
+}
+
+/// Outcome of a lookup in a `SetupCache`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub enum CacheLookupResult {
+    Hit,
+    Miss,
+}
+
+/// Total number of lookups in a `SetupCache` since the process start.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct CacheLookupStats {
+    pub hits: u64,
+    pub misses: u64,
+}
+
+/// Returns lookup stats for the specified cache, summed over all circuit types.
+pub fn setup_cache_lookup_stats(cache: SetupCache) -> CacheLookupStats {
+    let lookups = |result| {
+        [CircuitType::Base, CircuitType::Recursive]
+            .into_iter()
+            .map(|circuit_type| {
+                CIRCUIT_PROVER_METRICS.setup_cache_lookups[&(cache, circuit_type, result)].get()
+            })
+            .sum()
+    };
+    CacheLookupStats {
+        hits: lookups(CacheLookupResult::Hit),
+        misses: lookups(CacheLookupResult::Miss),
+    }
+}
+
+/// Outcome of verifying a generated proof.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, EncodeLabelValue)]
+#[metrics(rename_all = "snake_case")]
+pub enum VerificationResult {
+    Passed,
+    Failed,
+}
+
+/// Runs proof `verify`ication, reporting its latency and outcome.
+pub(crate) fn observe_verification(
+    circuit_type: CircuitType,
+    verify: impl FnOnce() -> bool,
+) -> bool {
+    let latency = CIRCUIT_PROVER_METRICS.proof_verification_time[&circuit_type].start();
+    let is_valid = verify();
+    latency.observe();
+    let result = if is_valid {
+        VerificationResult::Passed
+    } else {
+        VerificationResult::Failed
+    };
+    CIRCUIT_PROVER_METRICS.proof_verifications[&(circuit_type, result)].inc();
+    is_valid
+}
+
+/// Looks up `key` in a setup `cache`, reporting hit or miss.
+/// A miss is expected if the prover was started with a subset of setup data loaded.
+pub(crate) fn lookup_setup_cache<K: Eq + Hash, V>(
+    cache: &HashMap<K, Arc<V>>,
+    key: &K,
+    cache_kind: SetupCache,
+    circuit_type: CircuitType,
+) -> Option<Arc<V>> {
+    let value = cache.get(key).cloned();
+    let result = if value.is_some() {
+        CacheLookupResult::Hit
+    } else {
+        CacheLookupResult::Miss
+    };
+    CIRCUIT_PROVER_METRICS.setup_cache_lookups[&(cache_kind, circuit_type, result)].inc();
+    value
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use zksync_types::L1BatchId;
+
+    use super::*;
+
+    fn sample_metadata(aggregation_round: AggregationRound) -> FriProverJobMetadata {
+        FriProverJobMetadata {
+            id: 1,
+            batch_id: L1BatchId::from_raw(270, 1),
+            batch_sealed_at: Default::default(),
+            circuit_id: 1,
+            aggregation_round,
+            sequence_number: 0,
+            depth: 0,
+            is_node_final_proof: false,
+            pick_time: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn circuit_type_is_populated_from_job_metadata() {
+        let metadata = sample_metadata(AggregationRound::BasicCircuits);
+        assert_eq!(CircuitType::from_metadata(&metadata), CircuitType::Base);
+        for round in [
+            AggregationRound::LeafAggregation,
+            AggregationRound::NodeAggregation,
+            AggregationRound::RecursionTip,
+            AggregationRound::Scheduler,
+        ] {
+            let metadata = sample_metadata(round);
+            assert_eq!(
+                CircuitType::from_metadata(&metadata),
+                CircuitType::Recursive
+            );
+        }
+    }
+
+    #[test]
+    fn proof_verifications_are_reported() {
+        let counter =
+            |result| &CIRCUIT_PROVER_METRICS.proof_verifications[&(CircuitType::Recursive, result)];
+        let passed = counter(VerificationResult::Passed);
+        let failed = counter(VerificationResult::Failed);
+        let (passed_before, failed_before) = (passed.get(), failed.get());
+
+        assert!(observe_verification(CircuitType::Recursive, || true));
+        assert!(!observe_verification(CircuitType::Recursive, || false));
+        assert_eq!(passed.get(), passed_before + 1);
+        assert_eq!(failed.get(), failed_before + 1);
+    }
+
+    #[test]
+    fn setup_cache_lookups_are_reported() {
+        let cache = HashMap::from([(1_u8, Arc::new("setup"))]);
+        let labels = |result| (SetupCache::SetupData, CircuitType::Base, result);
+        let hits = &CIRCUIT_PROVER_METRICS.setup_cache_lookups[&labels(CacheLookupResult::Hit)];
+        let misses = &CIRCUIT_PROVER_METRICS.setup_cache_lookups[&labels(CacheLookupResult::Miss)];
+        let (hits_before, misses_before) = (hits.get(), misses.get());
+        let stats_before = setup_cache_lookup_stats(SetupCache::SetupData);
+
+        let value = lookup_setup_cache(&cache, &1, SetupCache::SetupData, CircuitType::Base);
+        assert_eq!(value.as_deref(), Some(&"setup"));
+        let value = lookup_setup_cache(&cache, &2, SetupCache::SetupData, CircuitType::Base);
+        assert!(value.is_none());
+
+        assert_eq!(hits.get(), hits_before + 1);
+        assert_eq!(misses.get(), misses_before + 1);
+        let stats = setup_cache_lookup_stats(SetupCache::SetupData);
+        assert!(stats.hits > stats_before.hits, "{stats:?}");
+        assert!(stats.misses > stats_before.misses, "{stats:?}");
+    }
+}
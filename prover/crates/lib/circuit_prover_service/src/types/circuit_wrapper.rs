@@ -31,7 +31,10 @@ use zksync_prover_fri_types::{
     FriProofWrapper,
 };
 
-use crate::types::setup_data::GoldilocksGpuProverSetupData;
+use crate::{
+    metrics::{observe_verification, CircuitType},
+    types::setup_data::GoldilocksGpuProverSetupData,
+};
 
 type Transcript = GoldilocksPoisedon2Transcript;
 type Field = GoldilocksField;
@@ -130,7 +133,10 @@ impl CircuitWrapper {
         .into();
         drop(span);
         let _span = tracing::info_span!("verify_base_circuit").entered();
-        if !verify_base_layer_proof::<NoPow>(circuit, &proof, &setup_data.vk) {
+        let is_valid = observe_verification(CircuitType::Base, || {
+            verify_base_layer_proof::<NoPow>(circuit, &proof, &setup_data.vk)
+        });
+        if !is_valid {
             return Err(anyhow::anyhow!("failed to verify base proof"));
         }
         Ok(proof)
@@ -159,7 +165,10 @@ impl CircuitWrapper {
         .into();
         drop(span);
         let _span = tracing::info_span!("verify_recursive_circuit").entered();
-        if !verify_recursion_layer_proof::<NoPow>(circuit, &proof, &setup_data.vk) {
+        let is_valid = observe_verification(CircuitType::Recursive, || {
+            verify_recursion_layer_proof::<NoPow>(circuit, &proof, &setup_data.vk)
+        });
+        if !is_valid {
             return Err(anyhow::anyhow!("failed to verify recursive proof"));
         }
         Ok(proof)
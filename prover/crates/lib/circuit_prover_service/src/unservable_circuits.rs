@@ -0,0 +1,72 @@
+use std::{
+    collections::HashSet,
+    sync::{Arc, Mutex},
+};
+
+use zksync_types::{basic_fri_types::AggregationRound, prover_dal::FriProverJobMetadata};
+
+/// Circuits (circuit ID & aggregation round pairs) that this prover cannot process because it doesn't have
+/// setup data or finalization hints loaded for them.
+///
+/// Circuits are recorded once a picked job for them is re-queued, and are excluded when picking jobs afterward,
+/// so that a prover with a subset of setup data loaded doesn't repeatedly pick jobs it cannot process.
+/// The set is shared among all job pickers of the prover.
+#[derive(Debug, Clone, Default)]
+pub struct UnservableCircuits(Arc<Mutex<HashSet<(u8, AggregationRound)>>>);
+
+impl UnservableCircuits {
+    /// Records the circuit of the job. Returns `true` if the circuit wasn't recorded before.
+    pub fn insert(&self, metadata: &FriProverJobMetadata) -> bool {
+        let circuit = (metadata.circuit_id, metadata.aggregation_round);
+        self.0.lock().unwrap().insert(circuit)
+    }
+
+    /// Returns all recorded circuits, ordered by circuit ID and aggregation round.
+    pub fn to_vec(&self) -> Vec<(u8, AggregationRound)> {
+        let mut circuits: Vec<_> = self.0.lock().unwrap().iter().copied().collect();
+        circuits.sort_unstable_by_key(|&(circuit_id, round)| (circuit_id, round as u8));
+        circuits
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Instant;
+
+    use zksync_types::L1BatchId;
+
+    use super::*;
+
+    fn metadata(circuit_id: u8, aggregation_round: AggregationRound) -> FriProverJobMetadata {
+        FriProverJobMetadata {
+            id: 1,
+            batch_id: L1BatchId::from_raw(270, 1),
+            batch_sealed_at: Default::default(),
+            circuit_id,
+            aggregation_round,
+            sequence_number: 0,
+            depth: 0,
+            is_node_final_proof: false,
+            pick_time: Instant::now(),
+        }
+    }
+
+    #[test]
+    fn recording_unservable_circuits() {
+        let circuits = UnservableCircuits::default();
+        assert!(circuits.to_vec().is_empty());
+
+        assert!(circuits.insert(&metadata(3, AggregationRound::LeafAggregation)));
+        let shared = circuits.clone();
+        assert!(!shared.insert(&metadata(3, AggregationRound::LeafAggregation)));
+        assert!(shared.insert(&metadata(3, AggregationRound::BasicCircuits)));
+
+        assert_eq!(
+            circuits.to_vec(),
+            [
+                (3, AggregationRound::BasicCircuits),
+                (3, AggregationRound::LeafAggregation)
+            ]
+        );
+    }
+}
@@ -1,11 +1,15 @@
-use std::time::Instant;
+use std::{mem, sync::Arc, time::Instant};
 
 use anyhow::Context;
+use zksync_prover_fri_types::circuit_definitions::boojum::{
+    cs::implementations::witness::WitnessVec, field::goldilocks::GoldilocksField,
+};
 use zksync_prover_job_processor::Executor;
 use zksync_types::prover_dal::FriProverJobMetadata;
 
 use crate::{
-    metrics::WITNESS_VECTOR_GENERATOR_METRICS,
+    cpu_affinity::WvgCpuAffinity,
+    metrics::{CircuitType, WITNESS_VECTOR_GENERATOR_METRICS},
     types::{
         witness_vector_generator_execution_output::WitnessVectorGeneratorExecutionOutput,
         witness_vector_generator_payload::WitnessVectorGeneratorPayload,
@@ -14,8 +18,50 @@ use crate::{
 
 /// WitnessVectorGenerator executor implementation.
 /// Synthesizes witness vectors to be later be used in GPU circuit proving.
-#[derive(Debug)]
-pub struct WitnessVectorGeneratorExecutor;
+#[derive(Debug, Default)]
+pub struct WitnessVectorGeneratorExecutor {
+    cpu_affinity: Option<Arc<WvgCpuAffinity>>,
+    max_witness_vector_bytes: Option<usize>,
+}
+
+impl WitnessVectorGeneratorExecutor {
+    /// Pins threads executing jobs to the specified CPU cores. By default, threads are not pinned.
+    pub fn with_cpu_affinity(mut self, cpu_affinity: Option<Arc<WvgCpuAffinity>>) -> Self {
+        self.cpu_affinity = cpu_affinity;
+        self
+    }
+
+    /// Fails jobs producing witness vectors larger than the specified number of bytes. By default, the size is unlimited.
+    pub fn with_max_witness_vector_bytes(
+        mut self,
+        max_witness_vector_bytes: Option<usize>,
+    ) -> Self {
+        self.max_witness_vector_bytes = max_witness_vector_bytes;
+        self
+    }
+}
+
+/// Returns the approximate in-memory size of a witness vector.
+fn witness_vector_size(witness_vector: &WitnessVec<GoldilocksField>) -> usize {
+    witness_vector.all_values.len() * mem::size_of::<GoldilocksField>()
+        + witness_vector.multiplicities.len() * mem::size_of::<u32>()
+}
+
+/// Checks that a witness vector of `size` bytes fits into the limit, reporting oversized vectors.
+fn check_witness_vector_size(
+    size: usize,
+    max_size: Option<usize>,
+    circuit_type: CircuitType,
+) -> anyhow::Result<()> {
+    let Some(max_size) = max_size else {
+        return Ok(());
+    };
+    if size > max_size {
+        WITNESS_VECTOR_GENERATOR_METRICS.oversized_witness_vectors[&circuit_type].inc();
+        anyhow::bail!("witness vector size ({size} bytes) exceeds the limit ({max_size} bytes)");
+    }
+    Ok(())
+}
 
 impl Executor for WitnessVectorGeneratorExecutor {
     type Input = WitnessVectorGeneratorPayload;
@@ -25,7 +71,12 @@ impl Executor for WitnessVectorGeneratorExecutor {
     #[tracing::instrument(
         name = "witness_vector_generator_executor",
         skip_all,
-        fields(l1_batch = % metadata.batch_id)
+        fields(
+            l1_batch = %metadata.batch_id,
+            job_id = metadata.id,
+            circuit_type = metadata.circuit_id,
+            round = %metadata.aggregation_round
+        )
     )]
     fn execute(
         &self,
@@ -33,6 +84,10 @@ impl Executor for WitnessVectorGeneratorExecutor {
         metadata: Self::Metadata,
     ) -> anyhow::Result<Self::Output> {
         let start_time = Instant::now();
+        let _core_reservation = self
+            .cpu_affinity
+            .as_ref()
+            .and_then(WvgCpuAffinity::pin_current_thread);
         tracing::info!(
             "Started executing witness vector generator job {}, on batch {}, for circuit {}, at round {}",
             metadata.id,
@@ -47,6 +102,12 @@ impl Executor for WitnessVectorGeneratorExecutor {
         let witness_vector = circuit_wrapper
             .synthesize_vector(finalization_hints)
             .context("failed to generate witness vector")?;
+        let circuit_type = CircuitType::from_metadata(&metadata);
+        check_witness_vector_size(
+            witness_vector_size(&witness_vector),
+            self.max_witness_vector_bytes,
+            circuit_type,
+        )?;
         tracing::info!(
             "Finished executing witness vector generator job {}, on batch {}, for circuit {}, at round {} in {:?}",
             metadata.id,
@@ -61,6 +122,29 @@ impl Executor for WitnessVectorGeneratorExecutor {
         Ok(WitnessVectorGeneratorExecutionOutput {
             circuit_wrapper,
             witness_vector,
+            circuit_type,
         })
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn checking_witness_vector_size() {
+        check_witness_vector_size(1 << 30, None, CircuitType::Base).unwrap();
+        check_witness_vector_size(1_024, Some(1_024), CircuitType::Base).unwrap();
+
+        let oversized_vectors = || {
+            WITNESS_VECTOR_GENERATOR_METRICS.oversized_witness_vectors[&CircuitType::Recursive]
+                .get()
+        };
+        let prev_oversized_vectors = oversized_vectors();
+        let err = check_witness_vector_size(1_025, Some(1_024), CircuitType::Recursive)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("exceeds the limit"), "{err}");
+        assert_eq!(oversized_vectors(), prev_oversized_vectors + 1);
+    }
+}
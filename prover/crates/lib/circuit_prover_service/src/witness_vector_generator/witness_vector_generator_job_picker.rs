@@ -3,7 +3,7 @@ use std::{collections::HashMap, sync::Arc, time::Instant};
 use anyhow::Context;
 use async_trait::async_trait;
 use zksync_object_store::ObjectStore;
-use zksync_prover_dal::{ConnectionPool, Prover};
+use zksync_prover_dal::{ConnectionPool, Prover, ProverDal};
 use zksync_prover_fri_types::{
     circuit_definitions::boojum::cs::implementations::setup::FinalizationHintsForProver,
     ProverServiceDataKey,
@@ -12,8 +12,10 @@ use zksync_prover_job_processor::JobPicker;
 use zksync_types::prover_dal::FriProverJobMetadata;
 
 use crate::{
-    metrics::WITNESS_VECTOR_GENERATOR_METRICS,
+    job_fields::record_job_fields,
+    metrics::{lookup_setup_cache, CircuitType, SetupCache, WITNESS_VECTOR_GENERATOR_METRICS},
     types::witness_vector_generator_payload::WitnessVectorGeneratorPayload,
+    unservable_circuits::UnservableCircuits,
     witness_vector_generator::{
         witness_vector_generator_metadata_loader::WitnessVectorMetadataLoader,
         WitnessVectorGeneratorExecutor,
@@ -22,12 +24,15 @@ use crate::{
 
 /// WitnessVectorGenerator job picker implementation.
 /// Picks job from database (via MetadataLoader) and gets data from object store.
+/// Jobs for circuits without loaded finalization hints are re-queued in database and aren't picked afterward.
 #[derive(Debug)]
 pub struct WitnessVectorGeneratorJobPicker<ML: WitnessVectorMetadataLoader> {
     connection_pool: ConnectionPool<Prover>,
     object_store: Arc<dyn ObjectStore>,
     finalization_hints_cache: HashMap<ProverServiceDataKey, Arc<FinalizationHintsForProver>>,
     metadata_loader: ML,
+    read_pool: Option<ConnectionPool<Prover>>,
+    unservable_circuits: UnservableCircuits,
 }
 
 impl<ML: WitnessVectorMetadataLoader> WitnessVectorGeneratorJobPicker<ML> {
@@ -42,44 +47,103 @@ impl<ML: WitnessVectorMetadataLoader> WitnessVectorGeneratorJobPicker<ML> {
             object_store,
             finalization_hints_cache,
             metadata_loader,
+            read_pool: None,
+            unservable_circuits: UnservableCircuits::default(),
         }
     }
+
+    /// Sets a connection pool (e.g., to a DB replica) used to check whether there are jobs available
+    /// before locking one in the main pool.
+    pub fn with_read_pool(mut self, read_pool: Option<ConnectionPool<Prover>>) -> Self {
+        self.read_pool = read_pool;
+        self
+    }
+
+    /// Sets circuits that cannot be processed by the prover, shared with other job pickers.
+    pub fn with_unservable_circuits(mut self, unservable_circuits: UnservableCircuits) -> Self {
+        self.unservable_circuits = unservable_circuits;
+        self
+    }
 }
 
 #[async_trait]
 impl<ML: WitnessVectorMetadataLoader> JobPicker for WitnessVectorGeneratorJobPicker<ML> {
     type ExecutorType = WitnessVectorGeneratorExecutor;
+
+    #[tracing::instrument(
+        name = "witness_vector_generator_job_picker",
+        skip_all,
+        fields(
+            l1_batch = tracing::field::Empty,
+            job_id = tracing::field::Empty,
+            circuit_type = tracing::field::Empty,
+            round = tracing::field::Empty
+        )
+    )]
     async fn pick_job(
         &mut self,
     ) -> anyhow::Result<Option<(WitnessVectorGeneratorPayload, FriProverJobMetadata)>> {
         let start_time = Instant::now();
         tracing::info!("Started picking witness vector generator job");
-        let connection = self
+        if let Some(read_pool) = &self.read_pool {
+            let mut connection = read_pool
+                .connection()
+                .await
+                .context("failed to get read-only db connection")?;
+            if !self.metadata_loader.has_jobs(&mut connection).await? {
+                return Ok(None);
+            }
+        }
+
+        let mut connection = self
             .connection_pool
             .connection()
             .await
             .context("failed to get db connection")?;
-        let metadata = match self.metadata_loader.load_metadata(connection).await {
+        let excluded_circuits = self.unservable_circuits.to_vec();
+        let metadata = match self
+            .metadata_loader
+            .load_metadata(&mut connection, &excluded_circuits)
+            .await
+        {
             None => return Ok(None),
             Some(metadata) => metadata,
         };
-
-        let circuit_wrapper = self
-            .object_store
-            .get(metadata.into())
-            .await
-            .context("failed to get circuit_wrapper from object store")?;
+        record_job_fields(&tracing::Span::current(), &metadata);
 
         let key = ProverServiceDataKey {
             circuit_id: metadata.circuit_id,
             stage: metadata.aggregation_round.into(),
         }
         .crypto_setup_key();
-        let finalization_hints = self
-            .finalization_hints_cache
-            .get(&key)
-            .context("failed to retrieve finalization key from cache")?
-            .clone();
+        let Some(finalization_hints) = lookup_setup_cache(
+            &self.finalization_hints_cache,
+            &key,
+            SetupCache::FinalizationHints,
+            CircuitType::from_metadata(&metadata),
+        ) else {
+            tracing::error!(
+                "Re-queueing witness vector generator job {}, on batch {}: no finalization hints loaded for circuit {} at round {}; \
+                 jobs for this circuit won't be picked anymore",
+                metadata.id,
+                metadata.batch_id,
+                metadata.circuit_id,
+                metadata.aggregation_round
+            );
+            self.unservable_circuits.insert(&metadata);
+            connection
+                .fri_prover_jobs_dal()
+                .requeue_job_without_attempt(metadata.id, metadata.batch_id.chain_id())
+                .await;
+            return Ok(None);
+        };
+        drop(connection);
+
+        let circuit_wrapper = self
+            .object_store
+            .get(metadata.into())
+            .await
+            .context("failed to get circuit_wrapper from object store")?;
 
         let payload = WitnessVectorGeneratorPayload {
             circuit_wrapper,
@@ -99,3 +163,112 @@ impl<ML: WitnessVectorMetadataLoader> JobPicker for WitnessVectorGeneratorJobPic
         Ok(Some((payload, metadata)))
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+    use zksync_object_store::MockObjectStore;
+    use zksync_types::{
+        basic_fri_types::AggregationRound,
+        protocol_version::{L1VerifierConfig, ProtocolSemanticVersion},
+        L1BatchId,
+    };
+
+    use super::*;
+    use crate::{
+        metrics::{CacheLookupResult, CIRCUIT_PROVER_METRICS},
+        witness_vector_generator::SimpleWitnessVectorMetadataLoader,
+    };
+
+    #[tokio::test]
+    #[traced_test]
+    async fn jobs_for_unservable_circuits_are_not_picked_again() {
+        let pool = ConnectionPool::<Prover>::prover_test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let protocol_version = ProtocolSemanticVersion::default();
+        let batch_id = L1BatchId::from_raw(270, 1);
+        conn.fri_protocol_versions_dal()
+            .save_prover_protocol_version(protocol_version, L1VerifierConfig::default())
+            .await
+            .unwrap();
+        conn.fri_basic_witness_generator_dal()
+            .save_witness_inputs(batch_id, "", protocol_version, Default::default())
+            .await
+            .unwrap();
+        conn.fri_prover_jobs_dal()
+            .insert_prover_jobs(
+                batch_id,
+                vec![(3, 0, "circuit3".to_owned())],
+                AggregationRound::LeafAggregation,
+                0,
+                protocol_version,
+                Default::default(),
+            )
+            .await;
+
+        // Only finalization hints for a single circuit are loaded, which doesn't match the queued job.
+        let finalization_hints_cache = HashMap::from([(
+            ProverServiceDataKey::new_basic(1),
+            Arc::new(FinalizationHintsForProver::default()),
+        )]);
+        let unservable_circuits = UnservableCircuits::default();
+        let mut picker = WitnessVectorGeneratorJobPicker::new(
+            pool.clone(),
+            MockObjectStore::arc(),
+            finalization_hints_cache,
+            SimpleWitnessVectorMetadataLoader::new("test".to_owned(), protocol_version),
+        )
+        .with_unservable_circuits(unservable_circuits.clone());
+        let misses = &CIRCUIT_PROVER_METRICS.setup_cache_lookups[&(
+            SetupCache::FinalizationHints,
+            CircuitType::Recursive,
+            CacheLookupResult::Miss,
+        )];
+        let misses_before = misses.get();
+
+        let job = picker.pick_job().await.unwrap();
+        assert!(job.is_none());
+        assert_eq!(
+            unservable_circuits.to_vec(),
+            [(3, AggregationRound::LeafAggregation)]
+        );
+        assert_eq!(misses.get(), misses_before + 1);
+        // Fields of the picked job are recorded on the picker span once the job is known.
+        logs_assert(|lines| {
+            let line = lines
+                .iter()
+                .find(|line| line.contains("Re-queueing witness vector generator job"))
+                .ok_or("no re-queueing log event")?;
+            let expected_fields = [
+                "witness_vector_generator_job_picker{",
+                "circuit_type=3",
+                "round=leaf_aggregation",
+            ];
+            match expected_fields.iter().find(|field| !line.contains(*field)) {
+                None => Ok(()),
+                Some(field) => Err(format!("`{field}` is missing from `{line}`")),
+            }
+        });
+        assert!(conn
+            .fri_prover_jobs_dal()
+            .has_queued_jobs(protocol_version)
+            .await
+            .unwrap());
+
+        // The job must not be picked (and re-queued) again.
+        for _ in 0..3 {
+            let job = picker.pick_job().await.unwrap();
+            assert!(job.is_none());
+        }
+        assert_eq!(misses.get(), misses_before + 1);
+
+        // The job is still available to other provers.
+        let job = conn
+            .fri_prover_jobs_dal()
+            .get_next_job(protocol_version, "other", &[])
+            .await
+            .unwrap();
+        assert_eq!(job.circuit_id, 3);
+        assert_eq!(job.aggregation_round, AggregationRound::LeafAggregation);
+    }
+}
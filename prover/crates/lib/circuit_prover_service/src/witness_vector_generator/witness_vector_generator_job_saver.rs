@@ -44,7 +44,12 @@ impl JobSaver for WitnessVectorGeneratorJobSaver {
     #[tracing::instrument(
         name = "witness_vector_generator_save_job",
         skip_all,
-        fields(l1_batch = % data.1.batch_id)
+        fields(
+            l1_batch = %data.1.batch_id,
+            job_id = data.1.id,
+            circuit_type = data.1.circuit_id,
+            round = %data.1.aggregation_round
+        )
     )]
     async fn save_job_result(
         &self,
@@ -57,6 +62,7 @@ impl JobSaver for WitnessVectorGeneratorJobSaver {
         let (result, metadata) = data;
         match result {
             Ok(payload) => {
+                let circuit_type = payload.circuit_type;
                 tracing::info!(
                     "Started transferring witness vector generator job {}, on batch {}, for circuit {}, at round {}",
                     metadata.id,
@@ -76,9 +82,9 @@ impl JobSaver for WitnessVectorGeneratorJobSaver {
                     metadata.aggregation_round,
                     start_time.elapsed()
                 );
-                WITNESS_VECTOR_GENERATOR_METRICS
-                    .transfer_time
+                WITNESS_VECTOR_GENERATOR_METRICS.transfer_time[&circuit_type]
                     .observe(start_time.elapsed());
+                WITNESS_VECTOR_GENERATOR_METRICS.transferred_witness_vectors[&circuit_type].inc();
             }
             Err(err) => {
                 tracing::error!("Witness vector generation failed: {:?}", err);
@@ -112,3 +118,52 @@ impl JobSaver for WitnessVectorGeneratorJobSaver {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use tracing_test::traced_test;
+    use zksync_types::{basic_fri_types::AggregationRound, L1BatchId};
+
+    use super::*;
+
+    #[tokio::test]
+    #[traced_test]
+    async fn saving_job_result_is_logged_with_job_fields() {
+        let pool = ConnectionPool::<Prover>::prover_test_pool().await;
+        let (sender, _receiver) = tokio::sync::mpsc::channel(1);
+        let saver = WitnessVectorGeneratorJobSaver::new(pool, sender);
+        let metadata = FriProverJobMetadata {
+            id: 42,
+            batch_id: L1BatchId::from_raw(270, 7),
+            batch_sealed_at: Default::default(),
+            circuit_id: 3,
+            aggregation_round: AggregationRound::LeafAggregation,
+            sequence_number: 0,
+            depth: 0,
+            is_node_final_proof: false,
+            pick_time: Instant::now(),
+        };
+
+        saver
+            .save_job_result((Err(anyhow::anyhow!("synthesis failed")), metadata))
+            .await
+            .unwrap();
+
+        logs_assert(|lines| {
+            let line = lines
+                .iter()
+                .find(|line| line.contains("Witness vector generation failed"))
+                .ok_or("no job failure log event")?;
+            let expected_fields = [
+                "witness_vector_generator_save_job{",
+                "job_id=42",
+                "circuit_type=3",
+                "round=leaf_aggregation",
+            ];
+            match expected_fields.iter().find(|field| !line.contains(*field)) {
+                None => Ok(()),
+                Some(field) => Err(format!("`{field}` is missing from `{line}`")),
+            }
+        });
+    }
+}
@@ -1,15 +1,26 @@
+use anyhow::Context as _;
 use async_trait::async_trait;
 use zksync_prover_dal::{Connection, Prover, ProverDal};
-use zksync_types::{protocol_version::ProtocolSemanticVersion, prover_dal::FriProverJobMetadata};
+use zksync_types::{
+    basic_fri_types::AggregationRound, protocol_version::ProtocolSemanticVersion,
+    prover_dal::FriProverJobMetadata,
+};
 
 /// Trait responsible for describing the job loading interface.
 /// This is necessary as multiple strategies are necessary for loading jobs (which require different implementations).
 #[async_trait]
 pub trait WitnessVectorMetadataLoader: Sync + Send + 'static {
+    /// Loads metadata of the next job to process, skipping jobs for `excluded_circuits`
+    /// (circuit ID & aggregation round pairs).
     async fn load_metadata(
         &self,
-        connection: Connection<'_, Prover>,
+        connection: &mut Connection<'_, Prover>,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata>;
+
+    /// Checks whether there are jobs that can be loaded. Only runs read-only queries,
+    /// so `connection` may come from a DB replica.
+    async fn has_jobs(&self, connection: &mut Connection<'_, Prover>) -> anyhow::Result<bool>;
 }
 
 /// Light job MetadataLoader.
@@ -34,12 +45,21 @@ impl LightWitnessVectorMetadataLoader {
 impl WitnessVectorMetadataLoader for LightWitnessVectorMetadataLoader {
     async fn load_metadata(
         &self,
-        mut connection: Connection<'_, Prover>,
+        connection: &mut Connection<'_, Prover>,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
         connection
             .fri_prover_jobs_dal()
-            .get_light_job(self.protocol_version, &self.pod_name)
+            .get_light_job(self.protocol_version, &self.pod_name, excluded_circuits)
+            .await
+    }
+
+    async fn has_jobs(&self, connection: &mut Connection<'_, Prover>) -> anyhow::Result<bool> {
+        connection
+            .fri_prover_jobs_dal()
+            .has_queued_jobs(self.protocol_version)
             .await
+            .context("failed to check queued prover jobs")
     }
 }
 
@@ -66,19 +86,28 @@ impl HeavyWitnessVectorMetadataLoader {
 impl WitnessVectorMetadataLoader for HeavyWitnessVectorMetadataLoader {
     async fn load_metadata(
         &self,
-        mut connection: Connection<'_, Prover>,
+        connection: &mut Connection<'_, Prover>,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
         let metadata = connection
             .fri_prover_jobs_dal()
-            .get_heavy_job(self.protocol_version, &self.pod_name)
+            .get_heavy_job(self.protocol_version, &self.pod_name, excluded_circuits)
             .await;
         if metadata.is_some() {
             return metadata;
         }
         connection
             .fri_prover_jobs_dal()
-            .get_light_job(self.protocol_version, &self.pod_name)
+            .get_light_job(self.protocol_version, &self.pod_name, excluded_circuits)
+            .await
+    }
+
+    async fn has_jobs(&self, connection: &mut Connection<'_, Prover>) -> anyhow::Result<bool> {
+        connection
+            .fri_prover_jobs_dal()
+            .has_queued_jobs(self.protocol_version)
             .await
+            .context("failed to check queued prover jobs")
     }
 }
 
@@ -104,11 +133,20 @@ impl SimpleWitnessVectorMetadataLoader {
 impl WitnessVectorMetadataLoader for SimpleWitnessVectorMetadataLoader {
     async fn load_metadata(
         &self,
-        mut connection: Connection<'_, Prover>,
+        connection: &mut Connection<'_, Prover>,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
         connection
             .fri_prover_jobs_dal()
-            .get_next_job(self.protocol_version, &self.pod_name)
+            .get_next_job(self.protocol_version, &self.pod_name, excluded_circuits)
+            .await
+    }
+
+    async fn has_jobs(&self, connection: &mut Connection<'_, Prover>) -> anyhow::Result<bool> {
+        connection
+            .fri_prover_jobs_dal()
+            .has_queued_jobs(self.protocol_version)
             .await
+            .context("failed to check queued prover jobs")
     }
 }
@@ -1,22 +1,184 @@
-use std::str::FromStr;
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    str::FromStr,
+    thread,
+};
 
 use anyhow::Context as _;
 use hex::ToHex;
+use serde::{Deserialize, Serialize};
+use sha3::{Digest, Keccak256};
 use zkevm_test_harness::witness::recursive_aggregation::{
     compute_leaf_vks_and_params_commitment, compute_node_vk_commitment,
 };
 use zksync_basic_types::H256;
-use zksync_prover_fri_types::circuit_definitions::{
-    boojum::field::goldilocks::GoldilocksField,
-    circuit_definitions::recursion_layer::ZkSyncRecursionLayerStorageType,
+use zksync_prover_fri_types::{
+    circuit_definitions::{
+        boojum::field::goldilocks::GoldilocksField,
+        circuit_definitions::recursion_layer::ZkSyncRecursionLayerStorageType,
+    },
+    ProverServiceDataKey,
 };
 
 use crate::{
-    keystore::Keystore,
-    utils::{calculate_fflonk_snark_vk_hash, calculate_snark_vk_hash, get_leaf_vk_params},
+    keystore::{Keystore, ProverServiceDataType},
+    utils::{
+        calculate_fflonk_snark_vk_hash, calculate_snark_vk_hash, get_leaf_vk_params,
+        try_calculate_fflonk_snark_vk_hash, try_calculate_snark_vk_hash,
+    },
     VkCommitments,
 };
 
+/// Type of the SNARK wrapper which verification key is hashed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SnarkWrapperKind {
+    Plonk,
+    Fflonk,
+}
+
+impl SnarkWrapperKind {
+    const ALL: [Self; 2] = [Self::Plonk, Self::Fflonk];
+
+    fn data_type(self) -> ProverServiceDataType {
+        match self {
+            Self::Plonk => ProverServiceDataType::SnarkVerificationKey,
+            Self::Fflonk => ProverServiceDataType::FflonkSnarkVerificationKey,
+        }
+    }
+
+    fn calculate_vk_hash(self, verification_key: String) -> anyhow::Result<H256> {
+        match self {
+            Self::Plonk => calculate_snark_vk_hash(verification_key),
+            Self::Fflonk => calculate_fflonk_snark_vk_hash(verification_key),
+        }
+    }
+}
+
+/// Precomputed SNARK wrapper VK hashes, allowing to skip recomputing them (e.g., across CI steps).
+/// Created by [`Keystore::write_vk_hash_manifest()`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VkHashManifest {
+    pub entries: Vec<VkHashManifestEntry>,
+}
+
+impl VkHashManifest {
+    /// Returns the VK hash for the specified wrapper, if the manifest contains it.
+    pub fn vk_hash(&self, kind: SnarkWrapperKind) -> Option<H256> {
+        self.entries
+            .iter()
+            .find(|entry| entry.kind == kind)
+            .map(|entry| entry.vk_hash)
+    }
+}
+
+/// VK hash for a single SNARK wrapper verification key.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct VkHashManifestEntry {
+    pub kind: SnarkWrapperKind,
+    /// Keccak-256 hash of the verification key file contents. Used to detect stale manifests.
+    pub source_hash: H256,
+    pub vk_hash: H256,
+}
+
+/// Result of verifying a SNARK wrapper VK hash for a single setup.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VkHashResult {
+    /// Directory containing the setup.
+    pub setup_path: PathBuf,
+    pub kind: SnarkWrapperKind,
+    /// Hash stored in the setup commitments.
+    pub expected: H256,
+    /// Hash calculated from the verification key.
+    pub calculated: H256,
+}
+
+impl VkHashResult {
+    pub fn is_match(&self) -> bool {
+        self.expected == self.calculated
+    }
+}
+
+/// Subset of [`VkCommitments`] used to verify VK hashes. Older setups don't have the FFLONK wrapper commitment.
+#[derive(Debug, Deserialize)]
+struct StoredSnarkCommitments {
+    snark_wrapper: String,
+    fflonk_snark_wrapper: Option<String>,
+}
+
+#[derive(Debug)]
+struct VkHashJob {
+    setup_path: PathBuf,
+    kind: SnarkWrapperKind,
+    expected: H256,
+    verification_key: String,
+}
+
+impl VkHashJob {
+    /// Collects jobs for a single setup directory.
+    fn collect(setup_path: &Path) -> anyhow::Result<Vec<Self>> {
+        let commitments_path = setup_path.join("commitments.json");
+        let commitments = fs::read_to_string(&commitments_path)
+            .with_context(|| format!("failed reading {commitments_path:?}"))?;
+        let commitments: StoredSnarkCommitments = serde_json::from_str(&commitments)
+            .with_context(|| format!("failed parsing {commitments_path:?}"))?;
+
+        // Older setups use a legacy name for the PLONK wrapper key.
+        let snark_key_path = [
+            "verification_snark_key.json",
+            "snark_verification_scheduler_key.json",
+        ]
+        .into_iter()
+        .map(|name| setup_path.join(name))
+        .find(|path| path.exists())
+        .with_context(|| format!("no SNARK verification key in {setup_path:?}"))?;
+        let mut jobs = vec![Self {
+            setup_path: setup_path.to_owned(),
+            kind: SnarkWrapperKind::Plonk,
+            expected: H256::from_str(&commitments.snark_wrapper)
+                .context("invalid SNARK wrapper commitment")?,
+            verification_key: fs::read_to_string(&snark_key_path)
+                .with_context(|| format!("failed reading {snark_key_path:?}"))?,
+        }];
+
+        // FFLONK wrapper key can only be verified if both the commitment and the key are present.
+        let fflonk_key_path = setup_path.join("fflonk_verification_snark_key.json");
+        if let Some(expected) = &commitments.fflonk_snark_wrapper {
+            if fflonk_key_path.exists() {
+                jobs.push(Self {
+                    setup_path: setup_path.to_owned(),
+                    kind: SnarkWrapperKind::Fflonk,
+                    expected: H256::from_str(expected)
+                        .context("invalid FFLONK SNARK wrapper commitment")?,
+                    verification_key: fs::read_to_string(&fflonk_key_path)
+                        .with_context(|| format!("failed reading {fflonk_key_path:?}"))?,
+                });
+            }
+        }
+        Ok(jobs)
+    }
+
+    fn run(self) -> anyhow::Result<VkHashResult> {
+        let calculated = match self.kind {
+            SnarkWrapperKind::Plonk => try_calculate_snark_vk_hash(&self.verification_key),
+            SnarkWrapperKind::Fflonk => try_calculate_fflonk_snark_vk_hash(&self.verification_key),
+        }
+        .with_context(|| {
+            format!(
+                "failed hashing {:?} verification key in {:?}",
+                self.kind, self.setup_path
+            )
+        })?;
+        Ok(VkHashResult {
+            setup_path: self.setup_path,
+            kind: self.kind,
+            expected: self.expected,
+            calculated,
+        })
+    }
+}
+
 impl Keystore {
     pub fn generate_commitments(&self) -> anyhow::Result<VkCommitments> {
         let leaf_vk_params = get_leaf_vk_params(self).context("get_leaf_vk_params()")?;
@@ -80,4 +242,193 @@ impl Keystore {
         anyhow::ensure!(expected_hash == calculated_hash, "Invalid SNARK wrapper VK hash. Calculated locally: {calculated_hash:?}, provided: {expected_hash:?}");
         Ok(())
     }
+
+    /// Computes hashes for all SNARK wrapper verification keys present in the base directory of this keystore
+    /// and writes them as a JSON manifest to the specified path. The manifest can be read back
+    /// with [`Self::load_vk_hash_manifest()`].
+    pub fn write_vk_hash_manifest(&self, path: &Path) -> anyhow::Result<VkHashManifest> {
+        let mut entries = vec![];
+        for kind in SnarkWrapperKind::ALL {
+            let Some(verification_key) = self.read_snark_verification_key(kind)? else {
+                continue;
+            };
+            let source_hash = H256::from_slice(&Keccak256::digest(&verification_key));
+            let vk_hash = kind
+                .calculate_vk_hash(verification_key)
+                .with_context(|| format!("failed hashing {kind:?} verification key"))?;
+            entries.push(VkHashManifestEntry {
+                kind,
+                source_hash,
+                vk_hash,
+            });
+        }
+        anyhow::ensure!(
+            !entries.is_empty(),
+            "no SNARK verification keys in {:?}",
+            self.get_base_path()
+        );
+
+        let manifest = VkHashManifest { entries };
+        let json =
+            serde_json::to_string_pretty(&manifest).context("failed serializing manifest")?;
+        fs::write(path, json).with_context(|| format!("failed writing {path:?}"))?;
+        tracing::info!(
+            "Wrote {} VK hashes to manifest {path:?}",
+            manifest.entries.len()
+        );
+        Ok(manifest)
+    }
+
+    /// Loads a VK hash manifest written by [`Self::write_vk_hash_manifest()`]. Fails if the manifest is stale,
+    /// i.e., if any verification key in this keystore was changed or removed since the manifest was written.
+    pub fn load_vk_hash_manifest(&self, path: &Path) -> anyhow::Result<VkHashManifest> {
+        let json = fs::read_to_string(path).with_context(|| format!("failed reading {path:?}"))?;
+        let manifest: VkHashManifest =
+            serde_json::from_str(&json).with_context(|| format!("failed parsing {path:?}"))?;
+
+        for entry in &manifest.entries {
+            let verification_key =
+                self.read_snark_verification_key(entry.kind)?
+                    .with_context(|| {
+                        format!(
+                            "VK hash manifest {path:?} is stale: {:?} verification key is missing",
+                            entry.kind
+                        )
+                    })?;
+            let source_hash = H256::from_slice(&Keccak256::digest(&verification_key));
+            anyhow::ensure!(
+                source_hash == entry.source_hash,
+                "VK hash manifest {path:?} is stale: {:?} verification key has changed",
+                entry.kind
+            );
+        }
+        Ok(manifest)
+    }
+
+    /// Reads the raw SNARK wrapper verification key, returning `None` if it's not present.
+    fn read_snark_verification_key(
+        &self,
+        kind: SnarkWrapperKind,
+    ) -> anyhow::Result<Option<String>> {
+        let path = self.get_file_path(ProverServiceDataKey::snark(), kind.data_type());
+        if !path.exists() {
+            return Ok(None);
+        }
+        fs::read_to_string(&path)
+            .map(Some)
+            .with_context(|| format!("failed reading {path:?}"))
+    }
+
+    /// Verifies SNARK wrapper VK hashes for all setups in the base directory of this keystore. Each setup
+    /// is a subdirectory containing `commitments.json` and SNARK wrapper verification keys.
+    /// PLONK and FFLONK hashes are computed concurrently, up to the configured load concurrency at a time.
+    ///
+    /// Returns results for all hashes, including mismatched ones, sorted by the setup path.
+    /// Errors are only returned if a setup cannot be read or a key cannot be hashed.
+    pub fn verify_all_vk_hashes(&self) -> anyhow::Result<Vec<VkHashResult>> {
+        let base_path = self.get_base_path();
+        let mut setup_paths = vec![];
+        for entry in
+            fs::read_dir(base_path).with_context(|| format!("failed reading {base_path:?}"))?
+        {
+            let path = entry?.path();
+            if path.is_dir() && path.join("commitments.json").exists() {
+                setup_paths.push(path);
+            }
+        }
+        setup_paths.sort_unstable();
+
+        let mut jobs = vec![];
+        for setup_path in &setup_paths {
+            jobs.extend(VkHashJob::collect(setup_path)?);
+        }
+
+        let mut results = Vec::with_capacity(jobs.len());
+        let mut jobs = jobs.into_iter().peekable();
+        while jobs.peek().is_some() {
+            let chunk: Vec<_> = jobs.by_ref().take(self.load_concurrency()).collect();
+            let chunk_results = thread::scope(|scope| {
+                let handles: Vec<_> = chunk
+                    .into_iter()
+                    .map(|job| scope.spawn(move || job.run()))
+                    .collect();
+                handles
+                    .into_iter()
+                    .map(|handle| handle.join().expect("VK hashing panicked"))
+                    .collect::<anyhow::Result<Vec<_>>>()
+            })?;
+            results.extend(chunk_results);
+        }
+
+        let mismatch_count = results.iter().filter(|res| !res.is_match()).count();
+        tracing::info!(
+            "Verified {} VK hashes for {} setups; {mismatch_count} mismatches",
+            results.len(),
+            setup_paths.len()
+        );
+        Ok(results)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use zksync_utils::env::Workspace;
+
+    use super::*;
+
+    fn copy_keys_fixture() -> tempfile::TempDir {
+        let keys_path = Workspace::locate().prover().join("data/keys");
+        let dir = tempfile::TempDir::new().unwrap();
+        for name in [
+            "commitments.json",
+            "verification_snark_key.json",
+            "fflonk_verification_snark_key.json",
+        ] {
+            fs::copy(keys_path.join(name), dir.path().join(name)).unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn vk_hash_manifest_round_trip() {
+        let dir = copy_keys_fixture();
+        let keystore = Keystore::new(dir.path().to_owned());
+        let manifest_path = dir.path().join("vk_hashes.json");
+        let manifest = keystore.write_vk_hash_manifest(&manifest_path).unwrap();
+        assert_eq!(manifest.entries.len(), 2);
+
+        let loaded = keystore.load_vk_hash_manifest(&manifest_path).unwrap();
+        assert_eq!(loaded, manifest);
+
+        let commitments = keystore.load_commitments().unwrap();
+        let plonk_hash = loaded.vk_hash(SnarkWrapperKind::Plonk).unwrap();
+        assert_eq!(format!("{plonk_hash:?}"), commitments.snark_wrapper);
+        let fflonk_hash = loaded.vk_hash(SnarkWrapperKind::Fflonk).unwrap();
+        assert_eq!(format!("{fflonk_hash:?}"), commitments.fflonk_snark_wrapper);
+    }
+
+    #[test]
+    fn stale_vk_hash_manifest_is_detected() {
+        let dir = copy_keys_fixture();
+        let keystore = Keystore::new(dir.path().to_owned());
+        let manifest_path = dir.path().join("vk_hashes.json");
+        keystore.write_vk_hash_manifest(&manifest_path).unwrap();
+
+        let fflonk_key_path = dir.path().join("fflonk_verification_snark_key.json");
+        let mut fflonk_key = fs::read_to_string(&fflonk_key_path).unwrap();
+        fflonk_key.push('\n');
+        fs::write(&fflonk_key_path, fflonk_key).unwrap();
+        let err = keystore.load_vk_hash_manifest(&manifest_path).unwrap_err();
+        assert!(err.to_string().contains("has changed"), "{err}");
+
+        fs::remove_file(&fflonk_key_path).unwrap();
+        let err = keystore.load_vk_hash_manifest(&manifest_path).unwrap_err();
+        assert!(err.to_string().contains("is missing"), "{err}");
+
+        // A manifest written for the remaining key is valid.
+        let manifest = keystore.write_vk_hash_manifest(&manifest_path).unwrap();
+        assert_eq!(manifest.entries.len(), 1);
+        assert_eq!(manifest.vk_hash(SnarkWrapperKind::Fflonk), None);
+        keystore.load_vk_hash_manifest(&manifest_path).unwrap();
+    }
 }
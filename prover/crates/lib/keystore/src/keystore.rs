@@ -1,5 +1,5 @@
 use std::{
-    collections::HashMap,
+    collections::{HashMap, HashSet},
     fs::{self, File},
     io::Read,
     path::{Path, PathBuf},
@@ -25,6 +25,7 @@ use circuit_definitions::{
 };
 #[cfg(feature = "gpu")]
 use fflonk_gpu::{FflonkSnarkVerifierCircuitDeviceSetup, FflonkSnarkVerifierCircuitVK};
+use futures::StreamExt as _;
 use serde::{de::DeserializeOwned, Deserialize, Serialize};
 #[cfg(any(feature = "gpu", feature = "gpu-light"))]
 use shivini::boojum::field::goldilocks::GoldilocksField;
@@ -41,6 +42,11 @@ use zksync_utils::env::Workspace;
 use crate::compressor::CompressorSetupData;
 use crate::VkCommitments;
 
+/// Default number of keys loaded in parallel by `Keystore::load_all_*_mapping()` functions.
+pub const DEFAULT_LOAD_CONCURRENCY: usize = 8;
+/// Upper bound on the number of keys loaded in parallel.
+pub const MAX_LOAD_CONCURRENCY: usize = 64;
+
 #[derive(Debug, Clone, Copy)]
 pub enum ProverServiceDataType {
     VerificationKey,
@@ -62,6 +68,10 @@ pub struct Keystore {
     basedir: PathBuf,
     /// Directory to store large setup keys.
     setup_data_path: PathBuf,
+    /// Number of keys loaded in parallel when loading key mappings.
+    load_concurrency: usize,
+    /// Whether a corrupted key is re-read once when loading key mappings.
+    reload_on_corruption: bool,
     /// Setup data cache for proof compressor
     #[cfg(feature = "gpu")]
     pub setup_data_cache_proof_compressor: Arc<CompressorSetupData>,
@@ -74,6 +84,8 @@ impl Keystore {
         Keystore {
             basedir: basedir.clone(),
             setup_data_path: basedir,
+            load_concurrency: DEFAULT_LOAD_CONCURRENCY,
+            reload_on_corruption: false,
             #[cfg(feature = "gpu")]
             setup_data_cache_proof_compressor: Arc::new(CompressorSetupData::new()),
         }
@@ -114,6 +126,8 @@ impl Keystore {
         Self {
             basedir: base_path.clone(),
             setup_data_path: base_path,
+            load_concurrency: DEFAULT_LOAD_CONCURRENCY,
+            reload_on_corruption: false,
             #[cfg(feature = "gpu")]
             setup_data_cache_proof_compressor,
         }
@@ -127,10 +141,31 @@ impl Keystore {
         self
     }
 
+    /// Sets how many keys are loaded in parallel, bounded by `MAX_LOAD_CONCURRENCY`.
+    pub fn with_load_concurrency(mut self, load_concurrency: usize) -> Self {
+        self.load_concurrency = load_concurrency.clamp(1, MAX_LOAD_CONCURRENCY);
+        self
+    }
+
+    /// If set, a key that fails to deserialize when loading key mappings is re-read once before giving up.
+    /// Handles artifacts that were torn when read (e.g., because they were being re-uploaded).
+    pub fn with_reload_on_corruption(mut self, reload_on_corruption: bool) -> Self {
+        self.reload_on_corruption = reload_on_corruption;
+        self
+    }
+
     pub fn get_base_path(&self) -> &PathBuf {
         &self.basedir
     }
 
+    pub(crate) fn load_concurrency(&self) -> usize {
+        self.load_concurrency
+    }
+
+    pub fn get_setup_data_path(&self) -> &PathBuf {
+        &self.setup_data_path
+    }
+
     pub(crate) fn get_file_path(
         &self,
         key: ProverServiceDataKey,
@@ -182,14 +217,48 @@ impl Keystore {
     fn load_bincode_from_file<T: for<'a> Deserialize<'a>>(
         filepath: impl AsRef<Path> + std::fmt::Debug,
     ) -> anyhow::Result<T> {
+        let buffer = Self::read_file(&filepath)?;
+        bincode::deserialize::<T>(&buffer)
+            .with_context(|| format!("Failed deserializing setup-data at path: {filepath:?}"))
+    }
+
+    fn read_file(filepath: impl AsRef<Path> + std::fmt::Debug) -> anyhow::Result<Vec<u8>> {
         let mut file = File::open(&filepath)
             .with_context(|| format!("Failed reading setup-data from path: {filepath:?}"))?;
         let mut buffer = Vec::new();
         file.read_to_end(&mut buffer).with_context(|| {
             format!("Failed reading setup-data to buffer from path: {filepath:?}")
         })?;
-        bincode::deserialize::<T>(&buffer)
-            .with_context(|| format!("Failed deserializing setup-data at path: {filepath:?}"))
+        Ok(buffer)
+    }
+
+    /// Deserializes bincode data obtained from `read`. If deserialization fails and `reload_on_corruption` is set,
+    /// reads and deserializes the data one more time.
+    fn load_bincode_with_reload<T: for<'a> Deserialize<'a>>(
+        filepath: &Path,
+        reload_on_corruption: bool,
+        mut read: impl FnMut() -> anyhow::Result<Vec<u8>>,
+    ) -> anyhow::Result<T> {
+        let buffer = read()?;
+        let err = match bincode::deserialize::<T>(&buffer) {
+            Ok(data) => return Ok(data),
+            Err(err) if reload_on_corruption => err,
+            Err(err) => {
+                return Err(err).with_context(|| {
+                    format!("Failed deserializing setup-data at path: {filepath:?}")
+                })
+            }
+        };
+
+        tracing::warn!(
+            "Setup-data at path {filepath:?} is corrupted ({err}); reloading it (attempt 2 of 2)"
+        );
+        let buffer = read()?;
+        let data = bincode::deserialize::<T>(&buffer).with_context(|| {
+            format!("Failed deserializing setup-data at path {filepath:?} after reloading")
+        })?;
+        tracing::info!("Setup-data at path {filepath:?} was successfully reloaded");
+        Ok(data)
     }
 
     //
@@ -522,10 +591,45 @@ impl Keystore {
             .context("Failed reading FFLONK SNARK setup data from a file")
     }
 
+    /// Returns the path to the setup data file for the specified circuit. The file may not exist.
+    pub fn setup_data_file_path(&self, key: ProverServiceDataKey) -> PathBuf {
+        self.get_file_path(key, ProverServiceDataType::SetupData)
+    }
+
     pub fn is_setup_data_present(&self, key: &ProverServiceDataKey) -> bool {
         Path::new(&self.get_file_path(*key, ProverServiceDataType::SetupData)).exists()
     }
 
+    /// Lists boojum circuits that have setup data present in the setup data directory.
+    /// Setup data is not loaded (and thus not checked for corruption); only the presence of files is checked.
+    pub fn available_setup_circuits(&self) -> anyhow::Result<Vec<ProverServiceDataKey>> {
+        let entries = fs::read_dir(&self.setup_data_path).with_context(|| {
+            format!(
+                "Failed listing setup data directory: {:?}",
+                self.setup_data_path
+            )
+        })?;
+        let mut file_names = HashSet::new();
+        for entry in entries {
+            let entry = entry.with_context(|| {
+                format!(
+                    "Failed listing setup data directory: {:?}",
+                    self.setup_data_path
+                )
+            })?;
+            file_names.insert(entry.file_name());
+        }
+
+        Ok(ProverServiceDataKey::all_boojum()
+            .into_iter()
+            .filter(|&key| {
+                let path = self.get_file_path(key, ProverServiceDataType::SetupData);
+                path.file_name()
+                    .is_some_and(|name| file_names.contains(name))
+            })
+            .collect())
+    }
+
     pub fn save_setup_data_for_circuit_type(
         &self,
         key: ProverServiceDataKey,
@@ -741,21 +845,23 @@ impl Keystore {
     ) -> anyhow::Result<HashMap<ProverServiceDataKey, Arc<T>>> {
         let mut mapping: HashMap<ProverServiceDataKey, Arc<T>> = HashMap::new();
 
-        // Load each file in parallel. Note that FS access is not necessarily parallel, but
-        // deserialization is. For larger files, it makes a big difference.
-        // Note: `collect` is important, because iterators are lazy, and otherwise we won't actually
-        // spawn threads.
-        let handles: Vec<_> = ProverServiceDataKey::all_boojum()
-            .into_iter()
+        // Load files in parallel, up to `load_concurrency` at a time. Note that FS access is not
+        // necessarily parallel, but deserialization is. For larger files, it makes a big difference.
+        // Note: blocking tasks are spawned lazily, as the stream is polled.
+        let mut handles = futures::stream::iter(ProverServiceDataKey::all_boojum())
             .map(|key| {
                 let filepath = self.get_file_path(key, data_type);
+                let reload_on_corruption = self.reload_on_corruption;
                 tokio::task::spawn_blocking(move || {
-                    let data = Self::load_bincode_from_file(filepath)?;
+                    let data =
+                        Self::load_bincode_with_reload(&filepath, reload_on_corruption, || {
+                            Self::read_file(&filepath)
+                        })?;
                     anyhow::Ok((key, Arc::new(data)))
                 })
             })
-            .collect();
-        for handle in futures::future::join_all(handles).await {
+            .buffer_unordered(self.load_concurrency);
+        while let Some(handle) = handles.next().await {
             let (key, setup_data) = handle.context("future loading key panicked")??;
             mapping.insert(key, setup_data);
         }
@@ -773,3 +879,95 @@ impl Keystore {
         Ok(data)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use zksync_prover_fri_types::ProvingStage;
+
+    use super::*;
+
+    #[test]
+    fn listing_available_setup_circuits() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let keystore = Keystore::new(dir.path().to_owned());
+        assert!(keystore.available_setup_circuits().unwrap().is_empty());
+
+        let present_keys = [
+            ProverServiceDataKey::new_basic(1),
+            ProverServiceDataKey::new_basic(255),
+            ProverServiceDataKey::new(2, ProvingStage::NodeAggregation),
+            ProverServiceDataKey::new(1, ProvingStage::Scheduler),
+        ];
+        for key in present_keys {
+            keystore
+                .save_setup_data_for_circuit_type(key, &vec![0])
+                .unwrap();
+        }
+        // Unrelated files must be ignored.
+        fs::write(dir.path().join("finalization_hints_basic_2.bin"), [0]).unwrap();
+        fs::write(dir.path().join("setup_basic_1_data.bin.tmp"), [0]).unwrap();
+
+        let mut available = keystore.available_setup_circuits().unwrap();
+        available.sort_by_key(|key| (key.stage as u8, key.circuit_id));
+        assert_eq!(available, present_keys);
+        for key in present_keys {
+            assert!(keystore.is_setup_data_present(&key));
+        }
+    }
+
+    #[test]
+    fn listing_setup_circuits_in_missing_directory() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let keystore = Keystore::new(dir.path().join("missing"));
+        let err = keystore.available_setup_circuits().unwrap_err();
+        assert!(
+            format!("{err:#}").contains("setup data directory"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn corrupted_data_is_reloaded_once() {
+        let filepath = Path::new("setup_basic_1_data.bin");
+        let data = vec![1_u64, 2, 3];
+        let good_bytes = bincode::serialize(&data).unwrap();
+        let reads = Cell::new(0);
+        let read = || {
+            reads.set(reads.get() + 1);
+            // Only the first read returns torn data.
+            if reads.get() == 1 {
+                Ok(good_bytes[..3].to_vec())
+            } else {
+                Ok(good_bytes.clone())
+            }
+        };
+
+        let loaded: Vec<u64> = Keystore::load_bincode_with_reload(filepath, true, read).unwrap();
+        assert_eq!(loaded, data);
+        assert_eq!(reads.get(), 2);
+
+        reads.set(0);
+        let err =
+            Keystore::load_bincode_with_reload::<Vec<u64>>(filepath, false, read).unwrap_err();
+        assert!(
+            format!("{err:#}").contains("Failed deserializing"),
+            "{err:#}"
+        );
+        assert_eq!(reads.get(), 1);
+    }
+
+    #[test]
+    fn reload_gives_up_after_second_corrupted_read() {
+        let filepath = Path::new("setup_basic_1_data.bin");
+        let mut reads = 0;
+        let err = Keystore::load_bincode_with_reload::<Vec<u64>>(filepath, true, || {
+            reads += 1;
+            Ok(vec![1, 2, 3])
+        })
+        .unwrap_err();
+        assert!(format!("{err:#}").contains("after reloading"), "{err:#}");
+        assert_eq!(reads, 2);
+    }
+}
@@ -26,6 +26,11 @@ use zksync_prover_fri_types::circuit_definitions::{
 
 use crate::keystore::Keystore;
 
+/// Computes leaf recursion parameters for all base layer circuit types.
+///
+/// The returned vector contains exactly one entry per base layer circuit type, sorted by the circuit type
+/// in ascending order. Callers (e.g., recursion witness generators) rely on this ordering when matching
+/// parameters by position, so it must not depend on the iteration order of `BaseLayerCircuitType`.
 pub fn get_leaf_vk_params(
     keystore: &Keystore,
 ) -> anyhow::Result<Vec<(u8, RecursionLeafParametersWitness<GoldilocksField>)>> {
@@ -46,67 +51,125 @@ pub fn get_leaf_vk_params(
         let params = compute_leaf_params(circuit_type, base_vk, leaf_vk);
         leaf_vk_commits.push((circuit_type, params));
     }
+    leaf_vk_commits.sort_unstable_by_key(|(circuit_type, _)| *circuit_type);
     Ok(leaf_vk_commits)
 }
 
+/// Errors that can occur while calculating verification key hashes.
+#[derive(Debug, thiserror::Error)]
+pub enum VkHashError {
+    #[error("failed to parse verification key: {0}")]
+    ParseJson(#[from] serde_json::Error),
+    #[error("unexpected number of {commitment} commitments: expected {expected}, got {actual}")]
+    UnexpectedCommitmentCount {
+        commitment: &'static str,
+        expected: usize,
+        actual: usize,
+    },
+    #[error("missing {0} commitment")]
+    MissingCommitment(&'static str),
+    #[error("failed to serialize verification key field: {0}")]
+    Io(#[from] std::io::Error),
+}
+
+fn ensure_commitment_count(
+    commitment: &'static str,
+    expected: usize,
+    actual: usize,
+) -> Result<(), VkHashError> {
+    if expected != actual {
+        return Err(VkHashError::UnexpectedCommitmentCount {
+            commitment,
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
 /// Calculates the hash of a snark verification key.
-// This function corresponds 1:1 with the following solidity code: https://github.com/matter-labs/era-contracts/blob/3e2bee96e412bac7c0a58c4b919837b59e9af36e/ethereum/contracts/zksync/Verifier.sol#L260
 pub fn calculate_snark_vk_hash(verification_key: String) -> anyhow::Result<H256> {
+    Ok(try_calculate_snark_vk_hash(&verification_key)?)
+}
+
+/// Calculates the hash of a snark verification key, returning a structured error on failure.
+// This function corresponds 1:1 with the following solidity code: https://github.com/matter-labs/era-contracts/blob/3e2bee96e412bac7c0a58c4b919837b59e9af36e/ethereum/contracts/zksync/Verifier.sol#L260
+pub fn try_calculate_snark_vk_hash(verification_key: &str) -> Result<H256, VkHashError> {
     let verification_key: SnarkVK<Bn256, ZkSyncSnarkWrapperCircuit> =
-        serde_json::from_str(&verification_key)?;
+        serde_json::from_str(verification_key)?;
 
     let mut res = vec![];
 
     // gate setup commitments
-    assert_eq!(8, verification_key.gate_setup_commitments.len());
+    ensure_commitment_count(
+        "gate setup",
+        8,
+        verification_key.gate_setup_commitments.len(),
+    )?;
 
     for gate_setup in verification_key.gate_setup_commitments {
         let (x, y) = gate_setup.as_xy();
-        x.into_repr().write_be(&mut res).unwrap();
-        y.into_repr().write_be(&mut res).unwrap();
+        x.into_repr().write_be(&mut res)?;
+        y.into_repr().write_be(&mut res)?;
     }
 
     // gate selectors commitments
-    assert_eq!(2, verification_key.gate_selectors_commitments.len());
+    ensure_commitment_count(
+        "gate selectors",
+        2,
+        verification_key.gate_selectors_commitments.len(),
+    )?;
 
     for gate_selector in verification_key.gate_selectors_commitments {
         let (x, y) = gate_selector.as_xy();
-        x.into_repr().write_be(&mut res).unwrap();
-        y.into_repr().write_be(&mut res).unwrap();
+        x.into_repr().write_be(&mut res)?;
+        y.into_repr().write_be(&mut res)?;
     }
 
     // permutation commitments
-    assert_eq!(4, verification_key.permutation_commitments.len());
+    ensure_commitment_count(
+        "permutation",
+        4,
+        verification_key.permutation_commitments.len(),
+    )?;
 
     for permutation in verification_key.permutation_commitments {
         let (x, y) = permutation.as_xy();
-        x.into_repr().write_be(&mut res).unwrap();
-        y.into_repr().write_be(&mut res).unwrap();
+        x.into_repr().write_be(&mut res)?;
+        y.into_repr().write_be(&mut res)?;
     }
 
     // lookup selector commitment
-    let lookup_selector = verification_key.lookup_selector_commitment.unwrap();
+    let lookup_selector = verification_key
+        .lookup_selector_commitment
+        .ok_or(VkHashError::MissingCommitment("lookup selector"))?;
     let (x, y) = lookup_selector.as_xy();
-    x.into_repr().write_be(&mut res).unwrap();
-    y.into_repr().write_be(&mut res).unwrap();
+    x.into_repr().write_be(&mut res)?;
+    y.into_repr().write_be(&mut res)?;
 
     // lookup tables commitments
-    assert_eq!(4, verification_key.lookup_tables_commitments.len());
+    ensure_commitment_count(
+        "lookup tables",
+        4,
+        verification_key.lookup_tables_commitments.len(),
+    )?;
 
     for table_commit in verification_key.lookup_tables_commitments {
         let (x, y) = table_commit.as_xy();
-        x.into_repr().write_be(&mut res).unwrap();
-        y.into_repr().write_be(&mut res).unwrap();
+        x.into_repr().write_be(&mut res)?;
+        y.into_repr().write_be(&mut res)?;
     }
 
     // table type commitment
-    let lookup_table = verification_key.lookup_table_type_commitment.unwrap();
+    let lookup_table = verification_key
+        .lookup_table_type_commitment
+        .ok_or(VkHashError::MissingCommitment("lookup table type"))?;
     let (x, y) = lookup_table.as_xy();
-    x.into_repr().write_be(&mut res).unwrap();
-    y.into_repr().write_be(&mut res).unwrap();
+    x.into_repr().write_be(&mut res)?;
+    y.into_repr().write_be(&mut res)?;
 
     // flag for using recursive part
-    Fq::default().into_repr().write_be(&mut res).unwrap();
+    Fq::default().into_repr().write_be(&mut res)?;
 
     let mut hasher = sha3::Keccak256::new();
     hasher.update(&res);
@@ -116,10 +179,15 @@ pub fn calculate_snark_vk_hash(verification_key: String) -> anyhow::Result<H256>
 }
 
 pub fn calculate_fflonk_snark_vk_hash(verification_key: String) -> anyhow::Result<H256> {
+    Ok(try_calculate_fflonk_snark_vk_hash(&verification_key)?)
+}
+
+/// Calculates the hash of a FFLONK snark verification key, returning a structured error on failure.
+pub fn try_calculate_fflonk_snark_vk_hash(verification_key: &str) -> Result<H256, VkHashError> {
     let verification_key: FflonkVerificationKey<
         Bn256,
         ZkSyncSnarkWrapperCircuitNoLookupCustomGate,
-    > = serde_json::from_str(&verification_key)?;
+    > = serde_json::from_str(verification_key)?;
 
     let mut res = vec![0u8; 32];
 
@@ -208,4 +276,115 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn verifying_all_vk_hashes() {
+        let path_to_input = Workspace::locate().prover().join("data/historical_data");
+        let setup_count = std::fs::read_dir(&path_to_input)
+            .unwrap()
+            .flatten()
+            .filter(|entry| entry.metadata().unwrap().is_dir())
+            .count();
+
+        let results = Keystore::new(path_to_input).verify_all_vk_hashes().unwrap();
+        assert!(results.len() >= setup_count, "{results:?}");
+        for result in &results {
+            assert!(result.is_match(), "{result:?}");
+        }
+    }
+
+    #[test]
+    fn all_vk_hash_mismatches_are_collected() {
+        let path_to_input = Workspace::locate().prover().join("data/historical_data");
+        let dir = tempfile::TempDir::new().unwrap();
+        for setup in ["0.24.0", "0.27.0", "0.28.0"] {
+            let setup_dir = dir.path().join(setup);
+            std::fs::create_dir(&setup_dir).unwrap();
+            for entry in std::fs::read_dir(path_to_input.join(setup)).unwrap() {
+                let entry = entry.unwrap();
+                std::fs::copy(entry.path(), setup_dir.join(entry.file_name())).unwrap();
+            }
+        }
+        // Corrupt commitments for 2 setups.
+        for setup in ["0.24.0", "0.28.0"] {
+            let path = dir.path().join(setup).join("commitments.json");
+            let mut commitments: serde_json::Value =
+                serde_json::from_str(&std::fs::read_to_string(&path).unwrap()).unwrap();
+            commitments["snark_wrapper"] = format!("{:?}", H256::repeat_byte(1)).into();
+            std::fs::write(&path, commitments.to_string()).unwrap();
+        }
+
+        let results = Keystore::new(dir.path().to_owned())
+            .with_load_concurrency(2)
+            .verify_all_vk_hashes()
+            .unwrap();
+        let mismatched_setups: Vec<_> = results
+            .iter()
+            .filter(|res| !res.is_match())
+            .map(|res| res.setup_path.file_name().unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(mismatched_setups, ["0.24.0", "0.28.0"]);
+        assert_eq!(results.len(), 3);
+    }
+
+    fn snark_vk_json() -> serde_json::Value {
+        let path = Workspace::locate()
+            .prover()
+            .join("data/keys/verification_snark_key.json");
+        let text = std::fs::read_to_string(path).unwrap();
+        serde_json::from_str(&text).unwrap()
+    }
+
+    #[test]
+    fn leaf_vk_params_are_sorted_by_circuit_type() {
+        let leaf_vk_params = get_leaf_vk_params(&Keystore::locate()).unwrap();
+        let circuit_types: Vec<_> = leaf_vk_params
+            .iter()
+            .map(|(circuit_type, _)| *circuit_type)
+            .collect();
+
+        let mut expected_circuit_types: Vec<_> = BaseLayerCircuitType::as_iter_u8().collect();
+        expected_circuit_types.sort_unstable();
+        expected_circuit_types.dedup();
+        assert_eq!(circuit_types, expected_circuit_types);
+    }
+
+    #[test]
+    fn vk_hash_error_on_malformed_json() {
+        let err = try_calculate_snark_vk_hash("not a key").unwrap_err();
+        assert!(matches!(err, VkHashError::ParseJson(_)), "{err:?}");
+        let err = try_calculate_fflonk_snark_vk_hash("{}").unwrap_err();
+        assert!(matches!(err, VkHashError::ParseJson(_)), "{err:?}");
+    }
+
+    #[test]
+    fn vk_hash_error_on_unexpected_commitment_count() {
+        let mut key = snark_vk_json();
+        key["gate_setup_commitments"].as_array_mut().unwrap().pop();
+
+        let err = try_calculate_snark_vk_hash(&key.to_string()).unwrap_err();
+        assert!(
+            matches!(
+                err,
+                VkHashError::UnexpectedCommitmentCount {
+                    commitment: "gate setup",
+                    expected: 8,
+                    actual: 7,
+                }
+            ),
+            "{err:?}"
+        );
+    }
+
+    #[test]
+    fn vk_hash_error_on_missing_commitment() {
+        let mut key = snark_vk_json();
+        key["lookup_selector_commitment"] = serde_json::Value::Null;
+
+        let err = try_calculate_snark_vk_hash(&key.to_string()).unwrap_err();
+        assert!(
+            matches!(err, VkHashError::MissingCommitment("lookup selector")),
+            "{err:?}"
+        );
+    }
 }
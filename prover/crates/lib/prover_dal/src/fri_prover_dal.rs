@@ -152,11 +152,16 @@ impl FriProverDal<'_, '_> {
     /// The 2 differ in the type of jobs they will load. Some Basic jobs are heavy in resource utilization.
     ///
     /// NOTE: This function retrieves only HEAVY_BASIC_CIRCUIT_IDS jobs.
+    ///
+    /// Jobs for `excluded_circuits` (circuit ID & aggregation round pairs) are never retrieved; this allows provers
+    /// that can only process a subset of circuits to not pick jobs they cannot process.
     pub async fn get_heavy_job(
         &mut self,
         protocol_version: ProtocolSemanticVersion,
         picked_by: &str,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
+        let (excluded_circuit_ids, excluded_rounds) = Self::unzip_circuits(excluded_circuits);
         sqlx::query!(
             r#"
             UPDATE prover_jobs_fri
@@ -179,6 +184,12 @@ impl FriProverDal<'_, '_> {
                         AND protocol_version_patch = $2
                         AND aggregation_round = $4
                         AND circuit_id = ANY($5)
+                        AND (circuit_id, aggregation_round) NOT IN (
+                            SELECT
+                                *
+                            FROM
+                                UNNEST($6::SMALLINT [], $7::SMALLINT [])
+                        )
                     ORDER BY
                         priority DESC,
                         batch_sealed_at ASC,
@@ -205,6 +216,8 @@ impl FriProverDal<'_, '_> {
             picked_by,
             AggregationRound::BasicCircuits as i64,
             &HEAVY_BASIC_CIRCUIT_IDS[..],
+            &excluded_circuit_ids,
+            &excluded_rounds,
         )
         .fetch_optional(self.storage.conn())
         .await
@@ -235,11 +248,15 @@ impl FriProverDal<'_, '_> {
     /// Most of this function is similar to `get_heavy_job()`.
     ///
     /// NOTE: This function retrieves all job but HEAVY_BASIC_CIRCUIT_IDS.
+    ///
+    /// Jobs for `excluded_circuits` are never retrieved, same as in `get_heavy_job()`.
     pub async fn get_light_job(
         &mut self,
         protocol_version: ProtocolSemanticVersion,
         picked_by: &str,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
+        let (excluded_circuit_ids, excluded_rounds) = Self::unzip_circuits(excluded_circuits);
         sqlx::query!(
             r#"
             UPDATE prover_jobs_fri
@@ -261,6 +278,12 @@ impl FriProverDal<'_, '_> {
                         AND protocol_version = $1
                         AND protocol_version_patch = $2
                         AND NOT (aggregation_round = $4 AND circuit_id = ANY($5))
+                        AND (circuit_id, aggregation_round) NOT IN (
+                            SELECT
+                                *
+                            FROM
+                                UNNEST($6::SMALLINT [], $7::SMALLINT [])
+                        )
                     ORDER BY
                         priority DESC,
                         batch_sealed_at ASC,
@@ -288,6 +311,8 @@ impl FriProverDal<'_, '_> {
             picked_by,
             AggregationRound::BasicCircuits as i64,
             &HEAVY_BASIC_CIRCUIT_IDS[..],
+            &excluded_circuit_ids,
+            &excluded_rounds,
         )
         .fetch_optional(self.storage.conn())
         .await
@@ -316,11 +341,15 @@ impl FriProverDal<'_, '_> {
     /// - pick the same type of circuit for as long as possible, this maximizes GPU cache reuse
     ///
     /// NOTE: We don't differentiate between heavy and light jobs in this function.
+    ///
+    /// Jobs for `excluded_circuits` are never retrieved, same as in `get_heavy_job()`.
     pub async fn get_next_job(
         &mut self,
         protocol_version: ProtocolSemanticVersion,
         picked_by: &str,
+        excluded_circuits: &[(u8, AggregationRound)],
     ) -> Option<FriProverJobMetadata> {
+        let (excluded_circuit_ids, excluded_rounds) = Self::unzip_circuits(excluded_circuits);
         sqlx::query!(
             r#"
             UPDATE prover_jobs_fri
@@ -341,6 +370,12 @@ impl FriProverDal<'_, '_> {
                         status = 'queued'
                         AND protocol_version = $1
                         AND protocol_version_patch = $2
+                        AND (circuit_id, aggregation_round) NOT IN (
+                            SELECT
+                                *
+                            FROM
+                                UNNEST($4::SMALLINT [], $5::SMALLINT [])
+                        )
                     ORDER BY
                         priority DESC,
                         batch_sealed_at ASC,
@@ -366,6 +401,8 @@ impl FriProverDal<'_, '_> {
             protocol_version.minor as i32,
             protocol_version.patch.0 as i32,
             picked_by,
+            &excluded_circuit_ids,
+            &excluded_rounds,
         )
         .fetch_optional(self.storage.conn())
         .await
@@ -384,6 +421,42 @@ impl FriProverDal<'_, '_> {
         })
     }
 
+    /// Splits circuit ID & aggregation round pairs into arrays that can be bound to a query.
+    fn unzip_circuits(circuits: &[(u8, AggregationRound)]) -> (Vec<i16>, Vec<i16>) {
+        circuits
+            .iter()
+            .map(|&(circuit_id, round)| (i16::from(circuit_id), round as i16))
+            .unzip()
+    }
+
+    /// Checks whether there are queued prover jobs for the specified protocol version.
+    /// Read-only, so it can be run against a DB replica to avoid contending for job locks on the primary.
+    pub async fn has_queued_jobs(
+        &mut self,
+        protocol_version: ProtocolSemanticVersion,
+    ) -> sqlx::Result<bool> {
+        let exists = sqlx::query_scalar!(
+            r#"
+            SELECT
+                EXISTS (
+                    SELECT
+                        1
+                    FROM
+                        prover_jobs_fri
+                    WHERE
+                        status = 'queued'
+                        AND protocol_version = $1
+                        AND protocol_version_patch = $2
+                )
+            "#,
+            protocol_version.minor as i32,
+            protocol_version.patch.0 as i32
+        )
+        .fetch_one(self.storage.conn())
+        .await?;
+        Ok(exists.unwrap_or(false))
+    }
+
     pub async fn save_proof_error(&mut self, id: u32, error: String) {
         {
             sqlx::query!(
@@ -739,6 +812,30 @@ impl FriProverDal<'_, '_> {
         .unwrap();
     }
 
+    /// Sets an in-progress job back to `queued`, without counting the current pick towards its attempts.
+    /// Used if the picked job cannot be processed by the prover that has picked it (e.g., because the prover
+    /// doesn't have setup data for the job circuit loaded), so that the job isn't failed after exhausting attempts.
+    pub async fn requeue_job_without_attempt(&mut self, id: u32, chain_id: L2ChainId) {
+        sqlx::query!(
+            r#"
+            UPDATE prover_jobs_fri
+            SET
+                status = 'queued',
+                attempts = GREATEST(attempts - 1, 0),
+                updated_at = NOW()
+            WHERE
+                id = $1
+                AND chain_id = $2
+                AND status = 'in_progress'
+            "#,
+            i64::from(id),
+            chain_id.inner() as i64
+        )
+        .execute(self.storage.conn())
+        .await
+        .unwrap();
+    }
+
     pub async fn get_scheduler_proof_job_id(&mut self, batch_id: L1BatchId) -> Option<u32> {
         sqlx::query!(
             r#"
@@ -1092,4 +1189,74 @@ mod tests {
 
         transaction.commit().await.unwrap();
     }
+
+    #[tokio::test]
+    async fn excluded_circuits_are_not_picked() {
+        let pool = ConnectionPool::<Prover>::prover_test_pool().await;
+        let mut conn = pool.connection().await.unwrap();
+        let batch_id = L1BatchId::from_raw(1, 1);
+
+        conn.fri_protocol_versions_dal()
+            .save_prover_protocol_version(
+                ProtocolSemanticVersion::default(),
+                L1VerifierConfig::default(),
+            )
+            .await
+            .unwrap();
+        conn.fri_basic_witness_generator_dal()
+            .save_witness_inputs(
+                batch_id,
+                "",
+                ProtocolSemanticVersion::default(),
+                DateTime::<Utc>::default(),
+            )
+            .await
+            .unwrap();
+        conn.fri_prover_jobs_dal()
+            .insert_prover_jobs(
+                batch_id,
+                vec![(1, 0, "circuit1".to_owned()), (3, 1, "circuit3".to_owned())],
+                AggregationRound::LeafAggregation,
+                0,
+                ProtocolSemanticVersion::default(),
+                DateTime::<Utc>::default(),
+            )
+            .await;
+
+        let excluded_circuits = [(1, AggregationRound::LeafAggregation)];
+        let job = conn
+            .fri_prover_jobs_dal()
+            .get_next_job(
+                ProtocolSemanticVersion::default(),
+                "test",
+                &excluded_circuits,
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.circuit_id, 3);
+        assert_eq!(job.aggregation_round, AggregationRound::LeafAggregation);
+
+        let job = conn
+            .fri_prover_jobs_dal()
+            .get_light_job(
+                ProtocolSemanticVersion::default(),
+                "test",
+                &excluded_circuits,
+            )
+            .await;
+        assert!(job.is_none(), "{job:?}");
+
+        // The same circuit at another round is not excluded.
+        let excluded_circuits = [(1, AggregationRound::BasicCircuits)];
+        let job = conn
+            .fri_prover_jobs_dal()
+            .get_light_job(
+                ProtocolSemanticVersion::default(),
+                "test",
+                &excluded_circuits,
+            )
+            .await
+            .unwrap();
+        assert_eq!(job.circuit_id, 1);
+    }
 }
@@ -2,7 +2,7 @@ use tokio::task::JoinHandle;
 
 use crate::{
     task_wiring::{JobPickerTask, JobSaverTask, Task, WorkerPool},
-    BackoffAndCancellable, Executor, JobPicker, JobSaver,
+    BackoffAndCancellable, Executor, JobPicker, JobSaver, WorkerScaler,
 };
 
 /// It's preferred to have a minimal amount of jobs in flight at any given time.
@@ -24,6 +24,7 @@ where
     saver: S,
     num_workers: usize,
     picker_backoff_and_cancellable: Option<BackoffAndCancellable>,
+    worker_scaler: Option<WorkerScaler>,
 }
 
 impl<E, P, S> JobRunner<E, P, S>
@@ -45,11 +46,29 @@ where
             saver,
             num_workers,
             picker_backoff_and_cancellable,
+            worker_scaler: None,
         }
     }
 
+    /// Allows scaling the number of workers at runtime, between scaler's bounds.
+    /// Maximum number of workers is taken from the scaler, overriding `num_workers`.
+    pub fn with_worker_scaler(mut self, worker_scaler: WorkerScaler) -> Self {
+        self.num_workers = worker_scaler.max();
+        self.worker_scaler = Some(worker_scaler);
+        self
+    }
+
     /// Runs job runner tasks.
     pub fn run(self) -> Vec<JoinHandle<anyhow::Result<()>>> {
+        let JobRunnerTasks { picker, processing } = self.spawn_tasks();
+        let mut tasks = vec![picker];
+        tasks.extend(processing);
+        tasks
+    }
+
+    /// Runs job runner tasks, keeping the job picker task separate from the tasks processing picked jobs.
+    /// Useful for graceful shutdown, where intake of new jobs should be stopped before in-flight jobs are drained.
+    pub fn spawn_tasks(self) -> JobRunnerTasks {
         let (input_tx, input_rx) =
             tokio::sync::mpsc::channel::<(E::Input, E::Metadata)>(CHANNEL_SIZE);
         let (result_tx, result_rx) =
@@ -57,13 +76,30 @@ where
 
         let picker_task =
             JobPickerTask::new(self.picker, input_tx, self.picker_backoff_and_cancellable);
-        let worker_pool = WorkerPool::new(self.executor, self.num_workers, input_rx, result_tx);
+        let worker_pool = WorkerPool::new(
+            self.executor,
+            self.num_workers,
+            input_rx,
+            result_tx,
+            self.worker_scaler,
+        );
         let saver_task = JobSaverTask::new(self.saver, result_rx);
 
-        vec![
-            tokio::spawn(picker_task.run()),
-            tokio::spawn(worker_pool.run()),
-            tokio::spawn(saver_task.run()),
-        ]
+        JobRunnerTasks {
+            picker: tokio::spawn(picker_task.run()),
+            processing: vec![
+                tokio::spawn(worker_pool.run()),
+                tokio::spawn(saver_task.run()),
+            ],
+        }
     }
 }
+
+/// Tasks spawned by [`JobRunner::spawn_tasks()`].
+#[derive(Debug)]
+pub struct JobRunnerTasks {
+    /// Job picker task, the only task pulling new jobs.
+    pub picker: JoinHandle<anyhow::Result<()>>,
+    /// Tasks executing & saving jobs that were already picked.
+    pub processing: Vec<JoinHandle<anyhow::Result<()>>>,
+}
@@ -4,12 +4,14 @@ mod job_picker;
 mod job_runner;
 mod job_saver;
 mod task_wiring;
+mod worker_scaler;
 
 pub use backoff_and_cancellable::{Backoff, BackoffAndCancellable};
 pub use executor::Executor;
 pub use job_picker::JobPicker;
-pub use job_runner::JobRunner;
+pub use job_runner::{JobRunner, JobRunnerTasks};
 pub use job_saver::JobSaver;
+pub use worker_scaler::WorkerScaler;
 
 // convenience aliases to simplify declarations
 type Input<P> = <<P as JobPicker>::ExecutorType as Executor>::Input;
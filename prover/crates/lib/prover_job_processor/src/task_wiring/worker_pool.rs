@@ -2,12 +2,12 @@ use std::sync::Arc;
 
 use async_trait::async_trait;
 use futures::stream::StreamExt;
-use tokio_stream::wrappers::ReceiverStream;
 
-use crate::{executor::Executor, task_wiring::Task};
+use crate::{executor::Executor, task_wiring::Task, WorkerScaler};
 
 /// Wrapper over Executor. Makes it a continuous task, picking tasks until picker channel is closed.
 /// It can execute multiple concurrent executors, up to specified limit.
+/// If a `WorkerScaler` is provided, the limit can be adjusted at runtime (up to `num_workers`).
 #[derive(Debug)]
 pub struct WorkerPool<E>
 where
@@ -17,6 +17,7 @@ where
     num_workers: usize,
     input_rx: tokio::sync::mpsc::Receiver<(E::Input, E::Metadata)>,
     result_tx: tokio::sync::mpsc::Sender<(anyhow::Result<E::Output>, E::Metadata)>,
+    scaler: Option<WorkerScaler>,
 }
 
 impl<E: Executor> WorkerPool<E> {
@@ -25,12 +26,14 @@ impl<E: Executor> WorkerPool<E> {
         num_workers: usize,
         input_rx: tokio::sync::mpsc::Receiver<(E::Input, E::Metadata)>,
         result_tx: tokio::sync::mpsc::Sender<(anyhow::Result<E::Output>, E::Metadata)>,
+        scaler: Option<WorkerScaler>,
     ) -> Self {
         Self {
             executor,
             num_workers,
             input_rx,
             result_tx,
+            scaler,
         }
     }
 }
@@ -40,10 +43,22 @@ impl<E: Executor> Task for WorkerPool<E> {
     async fn run(mut self) -> anyhow::Result<()> {
         let executor = Arc::new(self.executor);
         let num_workers = self.num_workers;
-        let stream = ReceiverStream::new(self.input_rx);
+        // A worker slot is acquired before receiving the next job,
+        // so that jobs are not pulled from the picker whilst the pool is scaled down.
+        let stream = futures::stream::unfold(
+            (self.input_rx, self.scaler),
+            |(mut input_rx, scaler)| async move {
+                let slot = match &scaler {
+                    Some(scaler) => Some(scaler.acquire().await),
+                    None => None,
+                };
+                let (input, metadata) = input_rx.recv().await?;
+                Some(((input, metadata, slot), (input_rx, scaler)))
+            },
+        );
 
         stream
-            .for_each_concurrent(num_workers, move |(input, metadata)| {
+            .for_each_concurrent(num_workers, move |(input, metadata, slot)| {
                 let executor = executor.clone();
                 let result_tx = self.result_tx.clone();
                 let exec_metadata = metadata.clone();
@@ -52,6 +67,7 @@ impl<E: Executor> Task for WorkerPool<E> {
                         tokio::task::spawn_blocking(move || executor.execute(input, exec_metadata))
                             .await
                             .expect("failed executing");
+                    drop(slot);
                     result_tx
                         .send((payload, metadata))
                         .await
@@ -62,3 +78,70 @@ impl<E: Executor> Task for WorkerPool<E> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{
+        sync::atomic::{AtomicUsize, Ordering},
+        time::Duration,
+    };
+
+    use super::*;
+
+    /// Executor recording the max number of concurrently executed jobs.
+    #[derive(Debug, Default)]
+    struct ConcurrencyTrackingExecutor {
+        running: AtomicUsize,
+        max_running: Arc<AtomicUsize>,
+    }
+
+    impl Executor for ConcurrencyTrackingExecutor {
+        type Input = ();
+        type Output = ();
+        type Metadata = usize;
+
+        fn execute(&self, (): (), _metadata: usize) -> anyhow::Result<()> {
+            let running = self.running.fetch_add(1, Ordering::SeqCst) + 1;
+            self.max_running.fetch_max(running, Ordering::SeqCst);
+            std::thread::sleep(Duration::from_millis(50));
+            self.running.fetch_sub(1, Ordering::SeqCst);
+            Ok(())
+        }
+    }
+
+    async fn run_jobs(job_count: usize, scaler: WorkerScaler) -> usize {
+        let executor = ConcurrencyTrackingExecutor::default();
+        let max_running = executor.max_running.clone();
+        let (input_tx, input_rx) = tokio::sync::mpsc::channel(job_count);
+        let (result_tx, mut result_rx) = tokio::sync::mpsc::channel(job_count);
+        for i in 0..job_count {
+            input_tx.send(((), i)).await.unwrap();
+        }
+        drop(input_tx);
+
+        WorkerPool::new(executor, 3, input_rx, result_tx, Some(scaler))
+            .run()
+            .await
+            .unwrap();
+        let mut completed = 0;
+        while let Some((result, _)) = result_rx.recv().await {
+            result.unwrap();
+            completed += 1;
+        }
+        assert_eq!(completed, job_count);
+        max_running.load(Ordering::SeqCst)
+    }
+
+    #[tokio::test]
+    async fn pool_is_limited_by_scaler() {
+        let scaler = WorkerScaler::new(1, 1, 3);
+        assert_eq!(run_jobs(6, scaler).await, 1);
+    }
+
+    #[tokio::test]
+    async fn pool_can_be_scaled_up() {
+        let scaler = WorkerScaler::new(1, 1, 3);
+        scaler.scale_to(3);
+        assert_eq!(run_jobs(6, scaler).await, 3);
+    }
+}
@@ -0,0 +1,171 @@
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+use tokio::sync::Notify;
+
+/// Handle that allows scaling the number of active workers of a `JobRunner` at runtime.
+/// The amount of workers is always kept between `min` and `max` (inclusive).
+///
+/// NOTE: Scaling down does not interrupt jobs that are already executing.
+/// The new limit applies once they finish and a worker is about to pick the next job.
+#[derive(Debug, Clone)]
+pub struct WorkerScaler {
+    inner: Arc<WorkerScalerInner>,
+}
+
+#[derive(Debug)]
+struct WorkerScalerInner {
+    min: usize,
+    max: usize,
+    target: AtomicUsize,
+    active: AtomicUsize,
+    notify: Notify,
+}
+
+impl WorkerScaler {
+    /// Creates a scaler with `initial` workers, clamped between `min` and `max`.
+    pub fn new(initial: usize, min: usize, max: usize) -> Self {
+        assert!(
+            min <= max,
+            "min workers ({min}) must not exceed max workers ({max})"
+        );
+        Self {
+            inner: Arc::new(WorkerScalerInner {
+                min,
+                max,
+                target: AtomicUsize::new(initial.clamp(min, max)),
+                active: AtomicUsize::new(0),
+                notify: Notify::new(),
+            }),
+        }
+    }
+
+    pub fn min(&self) -> usize {
+        self.inner.min
+    }
+
+    pub fn max(&self) -> usize {
+        self.inner.max
+    }
+
+    /// Number of workers currently allowed to execute jobs.
+    pub fn target(&self) -> usize {
+        self.inner.target.load(Ordering::Acquire)
+    }
+
+    /// Sets the number of workers allowed to execute jobs. Returns the effective (clamped) value.
+    pub fn scale_to(&self, target: usize) -> usize {
+        let target = target.clamp(self.inner.min, self.inner.max);
+        self.inner.target.store(target, Ordering::Release);
+        self.inner.notify.notify_waiters();
+        target
+    }
+
+    /// Waits until a worker slot is free. The slot is released once returned value is dropped.
+    pub(crate) async fn acquire(&self) -> WorkerSlot {
+        loop {
+            let notified = self.inner.notify.notified();
+            let active = self.inner.active.load(Ordering::Acquire);
+            if active < self.target()
+                && self
+                    .inner
+                    .active
+                    .compare_exchange(active, active + 1, Ordering::AcqRel, Ordering::Acquire)
+                    .is_ok()
+            {
+                return WorkerSlot {
+                    inner: self.inner.clone(),
+                };
+            }
+            if active < self.target() {
+                // Lost the race with another worker, retry immediately.
+                continue;
+            }
+            notified.await;
+        }
+    }
+}
+
+/// Guard representing a busy worker slot, see `WorkerScaler::acquire()`.
+#[derive(Debug)]
+pub(crate) struct WorkerSlot {
+    inner: Arc<WorkerScalerInner>,
+}
+
+impl Drop for WorkerSlot {
+    fn drop(&mut self) {
+        self.inner.active.fetch_sub(1, Ordering::AcqRel);
+        self.inner.notify.notify_waiters();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    const WAIT: Duration = Duration::from_millis(50);
+
+    async fn try_acquire(scaler: &WorkerScaler) -> Option<WorkerSlot> {
+        tokio::time::timeout(WAIT, scaler.acquire()).await.ok()
+    }
+
+    #[test]
+    fn clamping_target() {
+        let scaler = WorkerScaler::new(5, 1, 3);
+        assert_eq!(scaler.target(), 3);
+        assert_eq!(scaler.scale_to(0), 1);
+        assert_eq!(scaler.target(), 1);
+        assert_eq!(scaler.scale_to(10), 3);
+        assert_eq!(scaler.target(), 3);
+    }
+
+    #[tokio::test]
+    async fn scaling_up() {
+        let scaler = WorkerScaler::new(1, 1, 3);
+        let _first = try_acquire(&scaler).await.unwrap();
+        assert!(try_acquire(&scaler).await.is_none());
+
+        let waiting = tokio::spawn({
+            let scaler = scaler.clone();
+            async move { scaler.acquire().await }
+        });
+        tokio::time::sleep(WAIT).await;
+        assert!(!waiting.is_finished());
+        scaler.scale_to(3);
+        let _second = tokio::time::timeout(WAIT, waiting).await.unwrap().unwrap();
+        let _third = try_acquire(&scaler).await.unwrap();
+        assert!(try_acquire(&scaler).await.is_none());
+    }
+
+    #[tokio::test]
+    async fn scaling_down() {
+        let scaler = WorkerScaler::new(3, 1, 3);
+        scaler.scale_to(1);
+        let first = try_acquire(&scaler).await.unwrap();
+        assert!(try_acquire(&scaler).await.is_none());
+        drop(first);
+        let _first = try_acquire(&scaler).await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn scaling_down_with_jobs_in_flight() {
+        let scaler = WorkerScaler::new(3, 1, 3);
+        let first = try_acquire(&scaler).await.unwrap();
+        let second = try_acquire(&scaler).await.unwrap();
+        let third = try_acquire(&scaler).await.unwrap();
+
+        // In-flight jobs are not interrupted, but no new slots are available until enough of them finish.
+        scaler.scale_to(1);
+        drop(first);
+        assert!(try_acquire(&scaler).await.is_none());
+        drop(second);
+        assert!(try_acquire(&scaler).await.is_none());
+        drop(third);
+        let _slot = try_acquire(&scaler).await.unwrap();
+        assert!(try_acquire(&scaler).await.is_none());
+    }
+}
@@ -71,6 +71,8 @@ pub trait VerificationKeyManager: 'static + Send + Sync {
     ) -> anyhow::Result<ZkSyncRecursionLayerVerificationKey>;
     fn verify_scheduler_vk_hash(&self, expected_hash: H256) -> anyhow::Result<()>;
 
+    /// Returns leaf recursion parameters with exactly one entry per base layer circuit type,
+    /// sorted by the circuit type in ascending order.
     fn get_leaf_vk_params(
         &self,
     ) -> anyhow::Result<Vec<(u8, RecursionLeafParametersWitness<GoldilocksField>)>>;
@@ -159,3 +159,25 @@ pub async fn wait_for_db(db_url: &Url, tries: u32) -> anyhow::Result<()> {
     }
     anyhow::bail!("Unable to connect to Postgres, connection cannot be established");
 }
+
+/// Checks whether genesis was already performed for the server database,
+/// i.e. whether the genesis L1 batch is present.
+pub async fn is_genesis_performed(db_url: &Url) -> anyhow::Result<bool> {
+    let mut connection = PgConnection::connect(db_url.as_str()).await?;
+    // The table may be missing if migrations were not applied yet.
+    let (table_exists,): (bool,) =
+        sqlx::query_as("SELECT to_regclass('public.l1_batches') IS NOT NULL")
+            .fetch_one(&mut connection)
+            .await?;
+    let performed = if table_exists {
+        let (performed,): (bool,) =
+            sqlx::query_as("SELECT EXISTS (SELECT 1 FROM l1_batches WHERE number = 0)")
+                .fetch_one(&mut connection)
+                .await?;
+        performed
+    } else {
+        false
+    };
+    let _ = connection.close().await;
+    Ok(performed)
+}
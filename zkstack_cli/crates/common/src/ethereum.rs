@@ -1,13 +1,13 @@
-use std::{num::NonZeroUsize, ops::Add, str::FromStr, sync::Arc, time::Duration};
+use std::{future::Future, num::NonZeroUsize, ops::Add, str::FromStr, sync::Arc, time::Duration};
 
 use anyhow::Context;
 use ethers::{
-    contract::abigen,
+    contract::{abigen, ContractCall, ContractError},
     core::k256::ecdsa::SigningKey,
     middleware::MiddlewareBuilder,
     prelude::{BlockId, BlockNumber, Http, LocalWallet, Provider, Signer, SignerMiddleware},
     providers::Middleware,
-    types::{Address, TransactionRequest},
+    types::{Address, TransactionReceipt, TransactionRequest, U256},
 };
 use zkstack_cli_types::TokenInfo;
 use zksync_types::{url::SensitiveUrl, L2ChainId};
@@ -97,16 +97,99 @@ abigen!(
     function symbol() external view returns (string)
     function decimals() external view returns (uint8)
     function mint(address to, uint256 amount)
+    function approve(address spender, uint256 amount) returns (bool)
+    function transfer(address to, uint256 amount) returns (bool)
     ]"
 );
 
+/// Policy for retrying read-only RPC calls that have failed because of a transport / node error.
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    /// Maximum number of attempts for a single call, including the first one.
+    pub max_attempts: usize,
+    /// Delay before the first retry; doubled after each subsequent failed attempt.
+    pub backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            backoff: Duration::from_millis(500),
+        }
+    }
+}
+
+impl RetryPolicy {
+    async fn call<T, E, Fut>(
+        &self,
+        call_name: &str,
+        is_transient: impl Fn(&E) -> bool,
+        mut call: impl FnMut() -> Fut,
+    ) -> anyhow::Result<T>
+    where
+        Fut: Future<Output = Result<T, E>>,
+        E: std::error::Error + Send + Sync + 'static,
+    {
+        let mut backoff = self.backoff;
+        let mut attempt = 1;
+        loop {
+            match call().await {
+                Ok(value) => return Ok(value),
+                Err(err) if attempt < self.max_attempts && is_transient(&err) => {
+                    logger::warn(format!(
+                        "Call `{call_name}` failed (attempt {attempt}/{}): {err}; retrying in {backoff:?}",
+                        self.max_attempts
+                    ));
+                    tokio::time::sleep(backoff).await;
+                    backoff *= 2;
+                    attempt += 1;
+                }
+                Err(err) => return Err(err).with_context(|| format!("call `{call_name}` failed")),
+            }
+        }
+    }
+}
+
+/// Checks whether a contract call has failed because of the transport or the RPC node, rather than because of
+/// the contract itself (e.g., a revert or unexpected return data). Only the former errors are worth retrying.
+fn is_transient_call_error<M: Middleware>(err: &ContractError<M>) -> bool {
+    matches!(
+        err,
+        ContractError::MiddlewareError { .. } | ContractError::ProviderError { .. }
+    )
+}
+
+/// Fetches token metadata, retrying calls that fail transiently according to the default [`RetryPolicy`].
 pub async fn get_token_info(token_address: Address, rpc_url: String) -> anyhow::Result<TokenInfo> {
+    get_token_info_with_retries(token_address, rpc_url, RetryPolicy::default()).await
+}
+
+/// Fetches token metadata, retrying calls that fail transiently according to the provided `retry_policy`.
+/// Reverts are not retried.
+pub async fn get_token_info_with_retries(
+    token_address: Address,
+    rpc_url: String,
+    retry_policy: RetryPolicy,
+) -> anyhow::Result<TokenInfo> {
     let provider = Provider::<Http>::try_from(rpc_url)?;
-    let contract = TokenContract::new(token_address, Arc::new(provider));
+    let contract = &TokenContract::new(token_address, Arc::new(provider));
 
-    let name = contract.name().call().await?;
-    let symbol = contract.symbol().call().await?;
-    let decimals = contract.decimals().call().await?;
+    let name = retry_policy
+        .call("name()", is_transient_call_error, || async move {
+            contract.name().call().await
+        })
+        .await?;
+    let symbol = retry_policy
+        .call("symbol()", is_transient_call_error, || async move {
+            contract.symbol().call().await
+        })
+        .await?;
+    let decimals = retry_policy
+        .call("decimals()", is_transient_call_error, || async move {
+            contract.decimals().call().await
+        })
+        .await?;
 
     Ok(TokenInfo {
         name,
@@ -115,6 +198,48 @@ pub async fn get_token_info(token_address: Address, rpc_url: String) -> anyhow::
     })
 }
 
+/// Converts a human-readable decimal amount (e.g. `"1.5"`) into base token units,
+/// given the token `decimals` (see [`get_token_info()`]).
+pub fn to_token_units(human_amount: &str, decimals: u8) -> anyhow::Result<u128> {
+    let human_amount = human_amount.trim();
+    let (whole, fraction) = human_amount.split_once('.').unwrap_or((human_amount, ""));
+    anyhow::ensure!(
+        !(whole.is_empty() && fraction.is_empty()),
+        "empty token amount: {human_amount:?}"
+    );
+    anyhow::ensure!(
+        whole
+            .bytes()
+            .chain(fraction.bytes())
+            .all(|b| b.is_ascii_digit()),
+        "invalid token amount: {human_amount:?}"
+    );
+    anyhow::ensure!(
+        fraction.len() <= usize::from(decimals),
+        "token amount {human_amount} has more fractional digits than token decimals ({decimals})"
+    );
+
+    let overflow = || anyhow::anyhow!("token amount {human_amount} is too large");
+    let scale = 10_u128.checked_pow(decimals.into()).ok_or_else(overflow)?;
+    let whole = if whole.is_empty() {
+        0
+    } else {
+        whole.parse::<u128>().map_err(|_| overflow())?
+    };
+    let fraction = if fraction.is_empty() {
+        0
+    } else {
+        // Can't overflow: `fraction` has at most `decimals` digits, and `10^decimals` fits into `u128`.
+        fraction.parse::<u128>()? * 10_u128.pow(u32::from(decimals) - fraction.len() as u32)
+    };
+    whole
+        .checked_mul(scale)
+        .and_then(|whole| whole.checked_add(fraction))
+        .ok_or_else(overflow)
+}
+
+/// Mints `amount` of the token to each of `addresses`. If `max_in_flight` is specified, at most this many
+/// mint transactions are sent and awaiting confirmation at a time; otherwise, all transactions are sent at once.
 pub async fn mint_token(
     main_wallet: Wallet,
     token_address: Address,
@@ -122,32 +247,370 @@ pub async fn mint_token(
     l1_rpc: String,
     chain_id: u64,
     amount: u128,
+    max_in_flight: Option<NonZeroUsize>,
 ) -> anyhow::Result<()> {
     let client = Arc::new(
         create_ethers_client(main_wallet.private_key.unwrap(), l1_rpc, Some(chain_id))?
             .nonce_manager(main_wallet.address),
     );
 
-    let contract = TokenContract::new(token_address, client);
+    let contract = &TokenContract::new(token_address, client);
+    for_each_bounded(addresses, max_in_flight, |address| async move {
+        let call = contract.mint(address, amount.into());
+        match call.send().await {
+            // It's safe to set such low number of confirmations and low interval for localhost
+            Ok(pending_tx) => {
+                if let Err(err) = pending_tx
+                    .confirmations(5)
+                    .interval(Duration::from_millis(300))
+                    .await
+                {
+                    logger::error(format!("Minting is not confirmed {err}"));
+                }
+            }
+            Err(e) => logger::error(format!("Minting is not successful {e}")),
+        }
+    })
+    .await;
 
-    let mut pending_calls = vec![];
-    for address in addresses {
-        pending_calls.push(contract.mint(address, amount.into()));
+    Ok(())
+}
+
+/// Runs `task` for each of `items` concurrently, with at most `max_in_flight` tasks running at a time
+/// (unbounded if `None`).
+async fn for_each_bounded<T, Fut>(
+    items: impl IntoIterator<Item = T>,
+    max_in_flight: Option<NonZeroUsize>,
+    task: impl Fn(T) -> Fut,
+) where
+    Fut: Future<Output = ()>,
+{
+    let semaphore = max_in_flight.map(|limit| tokio::sync::Semaphore::new(limit.get()));
+    let semaphore = semaphore.as_ref();
+    let tasks = items.into_iter().map(|item| {
+        let task = task(item);
+        async move {
+            let _permit = match semaphore {
+                Some(semaphore) => Some(
+                    semaphore
+                        .acquire()
+                        .await
+                        .expect("semaphore is never closed"),
+                ),
+                None => None,
+            };
+            task.await;
+        }
+    });
+    futures::future::join_all(tasks).await;
+}
+
+/// Policy for waiting until a sent transaction is confirmed.
+#[derive(Debug, Clone, Copy)]
+pub struct ConfirmationPolicy {
+    /// Number of confirmations to wait for; 1 means that the transaction is included into a block.
+    pub confirmations: usize,
+    /// Interval between polls of the transaction receipt.
+    pub poll_interval: Duration,
+}
+
+impl ConfirmationPolicy {
+    /// Policy suitable for a local L1 node, which produces blocks quickly and cannot reorg.
+    pub const LOCALHOST: Self = Self {
+        confirmations: 1,
+        poll_interval: Duration::from_millis(30),
+    };
+}
+
+impl Default for ConfirmationPolicy {
+    fn default() -> Self {
+        Self {
+            confirmations: 3,
+            poll_interval: Duration::from_secs(5),
+        }
     }
+}
 
-    let mut pending_txs = vec![];
-    for call in &pending_calls {
-        let call = call.send().await;
-        match call {
-            // It's safe to set such low number of confirmations and low interval for localhost
-            Ok(call) => {
-                pending_txs.push(call.confirmations(5).interval(Duration::from_millis(300)))
+/// Approves `spender` to spend `amount` of the token on behalf of `wallet`. Returns the receipt
+/// of the approval transaction once it is confirmed according to `confirmation_policy`.
+pub async fn approve_token(
+    wallet: &Wallet,
+    token_address: Address,
+    spender: Address,
+    amount: U256,
+    l1_rpc: String,
+    chain_id: u64,
+    confirmation_policy: ConfirmationPolicy,
+) -> anyhow::Result<TransactionReceipt> {
+    let contract = token_contract(wallet, token_address, l1_rpc, chain_id)?;
+    send_token_call(
+        "approve()",
+        contract.approve(spender, amount),
+        confirmation_policy,
+    )
+    .await
+}
+
+/// Transfers `amount` of the token from `wallet` to `to`. Returns the receipt of the transfer transaction
+/// once it is confirmed according to `confirmation_policy`.
+pub async fn transfer_token(
+    wallet: &Wallet,
+    token_address: Address,
+    to: Address,
+    amount: U256,
+    l1_rpc: String,
+    chain_id: u64,
+    confirmation_policy: ConfirmationPolicy,
+) -> anyhow::Result<TransactionReceipt> {
+    let contract = token_contract(wallet, token_address, l1_rpc, chain_id)?;
+    send_token_call(
+        "transfer()",
+        contract.transfer(to, amount),
+        confirmation_policy,
+    )
+    .await
+}
+
+type SignerClient = SignerMiddleware<Provider<Http>, ethers::prelude::Wallet<SigningKey>>;
+
+fn token_contract(
+    wallet: &Wallet,
+    token_address: Address,
+    l1_rpc: String,
+    chain_id: u64,
+) -> anyhow::Result<TokenContract<SignerClient>> {
+    let private_key = wallet
+        .private_key
+        .clone()
+        .with_context(|| format!("wallet {:?} has no private key", wallet.address))?;
+    let client = create_ethers_client(private_key, l1_rpc, Some(chain_id))?;
+    Ok(TokenContract::new(token_address, Arc::new(client)))
+}
+
+async fn send_token_call(
+    call_name: &str,
+    call: ContractCall<SignerClient, bool>,
+    confirmation_policy: ConfirmationPolicy,
+) -> anyhow::Result<TransactionReceipt> {
+    let pending_tx = call
+        .send()
+        .await
+        .map_err(|err| contract_call_error(call_name, err))?;
+    let tx_hash = pending_tx.tx_hash();
+    logger::info(format!("Sent `{call_name}` transaction {tx_hash:?}"));
+
+    let receipt = pending_tx
+        .confirmations(confirmation_policy.confirmations)
+        .interval(confirmation_policy.poll_interval)
+        .await
+        .with_context(|| format!("failed waiting for `{call_name}` transaction {tx_hash:?}"))?;
+    let receipt =
+        receipt.with_context(|| format!("`{call_name}` transaction {tx_hash:?} was dropped"))?;
+    check_receipt(call_name, receipt)
+}
+
+/// Converts an error sending a contract call into a user-facing error, surfacing the revert reason if the call
+/// has reverted (e.g., during gas estimation).
+fn contract_call_error<M: Middleware>(call_name: &str, err: ContractError<M>) -> anyhow::Error {
+    if let Some(reason) = err.decode_revert::<String>() {
+        anyhow::anyhow!("`{call_name}` reverted: {reason}")
+    } else if let Some(data) = err.as_revert() {
+        anyhow::anyhow!("`{call_name}` reverted with data {data}")
+    } else {
+        anyhow::Error::new(err).context(format!("failed sending `{call_name}` transaction"))
+    }
+}
+
+fn check_receipt(
+    call_name: &str,
+    receipt: TransactionReceipt,
+) -> anyhow::Result<TransactionReceipt> {
+    anyhow::ensure!(
+        receipt.status != Some(0.into()),
+        "`{call_name}` transaction {:?} reverted",
+        receipt.transaction_hash
+    );
+    Ok(receipt)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::Cell;
+
+    use ethers::{
+        abi::{self, Token},
+        providers::ProviderError,
+        types::{Bytes, H256},
+    };
+
+    use super::*;
+
+    const TEST_RETRY_POLICY: RetryPolicy = RetryPolicy {
+        max_attempts: 3,
+        backoff: Duration::ZERO,
+    };
+
+    type TestCallError = ContractError<Provider<Http>>;
+
+    fn transport_error() -> TestCallError {
+        ContractError::MiddlewareError {
+            e: ProviderError::CustomError("connection reset".to_owned()),
+        }
+    }
+
+    #[tokio::test]
+    async fn transient_call_error_is_retried() {
+        let attempts = Cell::new(0);
+        let value = TEST_RETRY_POLICY
+            .call("decimals()", is_transient_call_error, || {
+                attempts.set(attempts.get() + 1);
+                let attempt = attempts.get();
+                async move {
+                    if attempt == 1 {
+                        Err(transport_error())
+                    } else {
+                        Ok(18_u8)
+                    }
+                }
+            })
+            .await
+            .unwrap();
+        assert_eq!(value, 18);
+        assert_eq!(attempts.get(), 2);
+    }
+
+    #[tokio::test]
+    async fn revert_is_not_retried() {
+        let attempts = Cell::new(0);
+        let err = TEST_RETRY_POLICY
+            .call("name()", is_transient_call_error, || {
+                attempts.set(attempts.get() + 1);
+                async { Err::<String, _>(TestCallError::Revert(Bytes::from_static(b"oops"))) }
+            })
+            .await
+            .unwrap_err();
+        assert!(
+            format!("{err:#}").contains("call `name()` failed"),
+            "{err:#}"
+        );
+        assert_eq!(attempts.get(), 1);
+    }
+
+    #[tokio::test]
+    async fn retries_are_bounded() {
+        let attempts = Cell::new(0);
+        TEST_RETRY_POLICY
+            .call("symbol()", is_transient_call_error, || {
+                attempts.set(attempts.get() + 1);
+                async { Err::<String, _>(transport_error()) }
+            })
+            .await
+            .unwrap_err();
+        assert_eq!(attempts.get(), TEST_RETRY_POLICY.max_attempts);
+    }
+
+    async fn max_outstanding_tasks(
+        task_count: usize,
+        max_in_flight: Option<NonZeroUsize>,
+    ) -> usize {
+        let outstanding = &Cell::new(0_usize);
+        let max_outstanding = &Cell::new(0_usize);
+        for_each_bounded(0..task_count, max_in_flight, |_| async move {
+            outstanding.set(outstanding.get() + 1);
+            max_outstanding.set(max_outstanding.get().max(outstanding.get()));
+            // Emulate sending a transaction and waiting for its confirmation.
+            for _ in 0..3 {
+                tokio::task::yield_now().await;
             }
-            Err(e) => logger::error(format!("Minting is not successful {e}")),
+            outstanding.set(outstanding.get() - 1);
+        })
+        .await;
+        assert_eq!(outstanding.get(), 0);
+        max_outstanding.get()
+    }
+
+    #[tokio::test]
+    async fn in_flight_tasks_are_bounded() {
+        for limit in [1, 3, 10] {
+            let max_in_flight = NonZeroUsize::new(limit);
+            assert_eq!(max_outstanding_tasks(10, max_in_flight).await, limit);
         }
+        assert_eq!(max_outstanding_tasks(10, None).await, 10);
     }
 
-    futures::future::join_all(pending_txs).await;
+    #[test]
+    fn receipt_is_returned_on_success() {
+        let receipt = TransactionReceipt {
+            transaction_hash: H256::repeat_byte(1),
+            status: Some(1.into()),
+            ..TransactionReceipt::default()
+        };
+        let checked = check_receipt("transfer()", receipt.clone()).unwrap();
+        assert_eq!(checked, receipt);
 
-    Ok(())
+        let reverted = TransactionReceipt {
+            status: Some(0.into()),
+            ..receipt
+        };
+        let err = check_receipt("transfer()", reverted)
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("`transfer()` transaction"), "{err}");
+        assert!(err.contains("reverted"), "{err}");
+    }
+
+    #[test]
+    fn revert_reason_is_surfaced() {
+        // ABI encoding of `Error(string)` used by Solidity for revert reasons.
+        let mut revert_data = vec![0x08, 0xc3, 0x79, 0xa0];
+        revert_data.extend(abi::encode(&[Token::String(
+            "ERC20: insufficient allowance".to_owned(),
+        )]));
+        let err = contract_call_error("approve()", TestCallError::Revert(revert_data.into()));
+        let err = err.to_string();
+        assert_eq!(err, "`approve()` reverted: ERC20: insufficient allowance");
+
+        let err = contract_call_error(
+            "approve()",
+            TestCallError::Revert(Bytes::from_static(b"\x01\x02")),
+        );
+        assert!(err.to_string().contains("reverted with data"), "{err}");
+
+        let err = contract_call_error("approve()", transport_error());
+        assert!(
+            format!("{err:#}").contains("failed sending `approve()` transaction"),
+            "{err:#}"
+        );
+    }
+
+    #[test]
+    fn converting_whole_token_amounts() {
+        assert_eq!(to_token_units("0", 18).unwrap(), 0);
+        assert_eq!(to_token_units("1", 18).unwrap(), 1_000_000_000_000_000_000);
+        assert_eq!(to_token_units("42", 6).unwrap(), 42_000_000);
+        assert_eq!(to_token_units("7", 0).unwrap(), 7);
+    }
+
+    #[test]
+    fn converting_fractional_token_amounts() {
+        assert_eq!(
+            to_token_units("1.5", 18).unwrap(),
+            1_500_000_000_000_000_000
+        );
+        assert_eq!(to_token_units("0.000001", 6).unwrap(), 1);
+        assert_eq!(to_token_units(".25", 2).unwrap(), 25);
+        assert_eq!(to_token_units("3.", 2).unwrap(), 300);
+    }
+
+    #[test]
+    fn rejecting_invalid_token_amounts() {
+        let err = to_token_units("1.0000001", 6).unwrap_err().to_string();
+        assert!(err.contains("more fractional digits"), "{err}");
+        to_token_units("1.5", 0).unwrap_err();
+        to_token_units("", 18).unwrap_err();
+        to_token_units(".", 18).unwrap_err();
+        to_token_units("-1", 18).unwrap_err();
+        to_token_units("1e18", 18).unwrap_err();
+        to_token_units("1000000000000000000000", 18).unwrap_err();
+    }
 }
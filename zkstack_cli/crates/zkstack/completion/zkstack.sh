@@ -6162,12 +6162,20 @@ _zkstack() {
             return 0
             ;;
         zkstack__dev__test__revert)
-            opts="-n -v -h --enable-consensus --no-deps --no-kill --verbose --chain --ignore-prerequisites --help"
+            opts="-n -v -h --enable-consensus --no-deps --strict-deps --no-kill --target-batch --tail-batches --verbose --chain --ignore-prerequisites --help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 4 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
             fi
             case "${prev}" in
+                --target-batch)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --tail-batches)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --chain)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -9384,7 +9392,7 @@ _zkstack() {
             return 0
             ;;
         zkstack__server)
-            opts="-v -h --components --genesis --uring --server-command --verbose --chain --ignore-prerequisites --help [ADDITIONAL_ARGS]... build run wait help"
+            opts="-v -h --components --components-file --components-exclude --genesis --reuse-genesis --skip-genesis-check --uring --expand-env --server-command --verbose --chain --ignore-prerequisites --help [ADDITIONAL_ARGS]... build run wait help"
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 2 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -9394,6 +9402,14 @@ _zkstack() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --components-file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --components-exclude)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --server-command)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
@@ -9498,7 +9514,7 @@ _zkstack() {
             return 0
             ;;
         zkstack__server__run)
-            opts="-v -h --components --genesis --uring --server-command --verbose --chain --ignore-prerequisites --help [ADDITIONAL_ARGS]..."
+            opts="-v -h --components --components-file --components-exclude --genesis --reuse-genesis --skip-genesis-check --uring --expand-env --server-command --verbose --chain --ignore-prerequisites --help [ADDITIONAL_ARGS]..."
             if [[ ${cur} == -* || ${COMP_CWORD} -eq 3 ]] ; then
                 COMPREPLY=( $(compgen -W "${opts}" -- "${cur}") )
                 return 0
@@ -9508,6 +9524,14 @@ _zkstack() {
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
                     ;;
+                --components-file)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
+                --components-exclude)
+                    COMPREPLY=($(compgen -f "${cur}"))
+                    return 0
+                    ;;
                 --server-command)
                     COMPREPLY=($(compgen -f "${cur}"))
                     return 0
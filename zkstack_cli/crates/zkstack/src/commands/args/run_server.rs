@@ -1,14 +1,73 @@
+use std::path::PathBuf;
+
+use anyhow::Context;
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
+use zkstack_cli_common::server::ServerMode;
 
 use crate::{
     commands::args::WaitArgs,
     messages::{
-        MSG_SERVER_ADDITIONAL_ARGS_HELP, MSG_SERVER_COMMAND_HELP, MSG_SERVER_COMPONENTS_HELP,
-        MSG_SERVER_GENESIS_HELP, MSG_SERVER_URING_HELP,
+        msg_failed_to_read_components_file, msg_undefined_env_var_in_server_arg,
+        msg_unknown_server_component, msg_unterminated_env_var_in_server_arg,
+        MSG_ALL_SERVER_COMPONENTS_EXCLUDED_ERR, MSG_SERVER_ADDITIONAL_ARGS_HELP,
+        MSG_SERVER_COMMAND_HELP, MSG_SERVER_COMPONENTS_EXCLUDE_HELP,
+        MSG_SERVER_COMPONENTS_FILE_HELP, MSG_SERVER_COMPONENTS_HELP, MSG_SERVER_EXPAND_ENV_HELP,
+        MSG_SERVER_GENESIS_HELP, MSG_SERVER_REUSE_GENESIS_HELP, MSG_SERVER_SKIP_GENESIS_CHECK_HELP,
+        MSG_SERVER_URING_HELP,
     },
 };
 
+/// Component names accepted by `zksync_server --components` (see `core/bin/zksync_server/src/components.rs`).
+const SERVER_COMPONENTS: &[&str] = &[
+    "api",
+    "http_api",
+    "ws_api",
+    "contract_verification_api",
+    "tree",
+    "tree_api",
+    "state_keeper",
+    "housekeeper",
+    "eth_proof_manager",
+    "eth",
+    "eth_watcher",
+    "eth_tx_aggregator",
+    "eth_tx_manager",
+    "proof_data_handler",
+    "tee_proof_data_handler",
+    "consensus",
+    "commitment_generator",
+    "da_dispatcher",
+    "vm_runner_protective_reads",
+    "base_token_ratio_persister",
+    "vm_runner_bwip",
+    "vm_playground",
+    "external_proof_integration_api",
+];
+
+/// Components run by `zksync_server` if `--components` is not specified (see `core/bin/zksync_server/src/main.rs`).
+/// `--components-exclude` removes components from this list.
+const DEFAULT_SERVER_COMPONENTS: &[&str] = &[
+    "api",
+    "tree",
+    "eth",
+    "state_keeper",
+    "housekeeper",
+    "commitment_generator",
+    "da_dispatcher",
+    "vm_runner_protective_reads",
+    "consensus",
+];
+
+/// Components from [`SERVER_COMPONENTS`] that are shorthands for several other components.
+const AGGREGATE_SERVER_COMPONENTS: &[(&str, &[&str])] = &[
+    ("api", &["http_api", "ws_api", "contract_verification_api"]),
+    (
+        "eth",
+        &["eth_watcher", "eth_tx_aggregator", "eth_tx_manager"],
+    ),
+];
+
 #[derive(Debug, Parser)]
 #[command(args_conflicts_with_subcommands = true, flatten_help = true)]
 pub struct ServerArgs {
@@ -38,12 +97,347 @@ impl From<ServerArgs> for ServerCommand {
 pub struct RunServerArgs {
     #[arg(long, help = MSG_SERVER_COMPONENTS_HELP)]
     pub components: Option<Vec<String>>,
+    #[arg(long, help = MSG_SERVER_COMPONENTS_FILE_HELP)]
+    pub components_file: Option<PathBuf>,
+    #[arg(
+        long,
+        conflicts_with_all = ["components", "components_file"],
+        help = MSG_SERVER_COMPONENTS_EXCLUDE_HELP
+    )]
+    pub components_exclude: Option<Vec<String>>,
     #[arg(long, help = MSG_SERVER_GENESIS_HELP)]
     pub genesis: bool,
+    #[arg(long, requires = "genesis", help = MSG_SERVER_REUSE_GENESIS_HELP)]
+    pub reuse_genesis: bool,
+    #[arg(long, conflicts_with = "genesis", help = MSG_SERVER_SKIP_GENESIS_CHECK_HELP)]
+    pub skip_genesis_check: bool,
     #[clap(help = MSG_SERVER_URING_HELP, long, default_missing_value = "true")]
     pub uring: bool,
+    #[arg(long, help = MSG_SERVER_EXPAND_ENV_HELP)]
+    pub expand_env: bool,
     #[clap(long, help = MSG_SERVER_COMMAND_HELP)]
     pub server_command: Option<String>,
     #[arg(last = true, help = MSG_SERVER_ADDITIONAL_ARGS_HELP)]
     pub additional_args: Vec<String>,
 }
+
+impl RunServerArgs {
+    /// Returns the server mode if it can be determined without probing the server database.
+    /// Returns `None` if the database should be probed (i.e., `--reuse-genesis` is specified).
+    pub fn server_mode(&self) -> Option<ServerMode> {
+        if self.skip_genesis_check || !self.genesis {
+            Some(ServerMode::Normal)
+        } else if self.reuse_genesis {
+            None
+        } else {
+            Some(ServerMode::Genesis)
+        }
+    }
+
+    /// Returns components from `--components` merged with the ones from `--components-file`,
+    /// or the default server components except for `--components-exclude`.
+    /// Returns `None` if none of these args is specified, so that the server runs its default components.
+    pub fn resolve_components(&self) -> anyhow::Result<Option<Vec<String>>> {
+        if let Some(excluded) = &self.components_exclude {
+            return exclude_components(excluded).map(Some);
+        }
+        let from_file = self
+            .components_file
+            .as_ref()
+            .map(|path| {
+                std::fs::read_to_string(path)
+                    .with_context(|| msg_failed_to_read_components_file(path))
+            })
+            .transpose()?;
+        merge_components(self.components.as_deref(), from_file.as_deref())
+    }
+
+    /// Returns additional args to pass to the server. If `--expand-env` is specified,
+    /// `${VAR}` and `${VAR:-default}` references in the args are substituted from the environment.
+    pub fn resolve_additional_args(&self) -> anyhow::Result<Vec<String>> {
+        if !self.expand_env {
+            return Ok(self.additional_args.clone());
+        }
+        self.additional_args
+            .iter()
+            .map(|arg| expand_env_vars(arg, |name| std::env::var(name).ok()))
+            .collect()
+    }
+}
+
+/// Substitutes `${VAR}` and `${VAR:-default}` references in `arg` using `lookup`.
+/// Undefined variables without a default are an error.
+fn expand_env_vars(arg: &str, lookup: impl Fn(&str) -> Option<String>) -> anyhow::Result<String> {
+    let mut expanded = String::with_capacity(arg.len());
+    let mut rest = arg;
+    while let Some(start) = rest.find("${") {
+        expanded.push_str(&rest[..start]);
+        let reference = &rest[start + 2..];
+        let end = reference
+            .find('}')
+            .with_context(|| msg_unterminated_env_var_in_server_arg(arg))?;
+        let (name, default) = match reference[..end].split_once(":-") {
+            Some((name, default)) => (name, Some(default)),
+            None => (&reference[..end], None),
+        };
+        let value = lookup(name)
+            .or_else(|| default.map(str::to_owned))
+            .with_context(|| msg_undefined_env_var_in_server_arg(name, arg))?;
+        expanded.push_str(&value);
+        rest = &reference[end + 1..];
+    }
+    expanded.push_str(rest);
+    Ok(expanded)
+}
+
+/// Merges components from the CLI and the components file, removing duplicates.
+/// Fails if any of the components is not in [`SERVER_COMPONENTS`].
+fn merge_components(
+    from_cli: Option<&[String]>,
+    file_contents: Option<&str>,
+) -> anyhow::Result<Option<Vec<String>>> {
+    if from_cli.is_none() && file_contents.is_none() {
+        return Ok(None);
+    }
+
+    let from_cli = from_cli
+        .into_iter()
+        .flatten()
+        .flat_map(|arg| arg.split(','))
+        .map(str::trim);
+    let from_file = file_contents.into_iter().flat_map(parse_components_file);
+    let mut components: Vec<String> = vec![];
+    for component in from_cli.chain(from_file) {
+        anyhow::ensure!(
+            SERVER_COMPONENTS.contains(&component),
+            msg_unknown_server_component(component)
+        );
+        if !components.iter().any(|existing| existing == component) {
+            components.push(component.to_owned());
+        }
+    }
+    Ok(Some(components))
+}
+
+/// Returns the default server components except for the excluded ones. Aggregate components (e.g., `api`)
+/// are not included in the output; instead, the components they expand to are, so that parts of an aggregate
+/// can be excluded.
+fn exclude_components(excluded: &[String]) -> anyhow::Result<Vec<String>> {
+    let mut excluded_set = vec![];
+    for component in excluded
+        .iter()
+        .flat_map(|arg| arg.split(','))
+        .map(str::trim)
+    {
+        anyhow::ensure!(
+            SERVER_COMPONENTS.contains(&component),
+            msg_unknown_server_component(component)
+        );
+        match AGGREGATE_SERVER_COMPONENTS
+            .iter()
+            .find(|(aggregate, _)| *aggregate == component)
+        {
+            Some((_, parts)) => excluded_set.extend_from_slice(parts),
+            None => excluded_set.push(component),
+        }
+    }
+
+    let components: Vec<_> = DEFAULT_SERVER_COMPONENTS
+        .iter()
+        .flat_map(|component| {
+            AGGREGATE_SERVER_COMPONENTS
+                .iter()
+                .find(|(aggregate, _)| aggregate == component)
+                .map_or(std::slice::from_ref(component), |(_, parts)| *parts)
+        })
+        .filter(|component| !excluded_set.contains(*component))
+        .map(|component| (*component).to_owned())
+        .collect();
+    anyhow::ensure!(
+        !components.is_empty(),
+        MSG_ALL_SERVER_COMPONENTS_EXCLUDED_ERR
+    );
+    Ok(components)
+}
+
+/// Parses newline- or comma-separated component names. Blank lines and `#` comments are ignored.
+fn parse_components_file(contents: &str) -> Vec<&str> {
+    contents
+        .lines()
+        .map(|line| line.split_once('#').map_or(line, |(before, _)| before))
+        .flat_map(|line| line.split(','))
+        .map(str::trim)
+        .filter(|component| !component.is_empty())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parsing_components_file() {
+        let contents = "\
+            # Core components\n\
+            api, tree\n\
+            \n\
+            state_keeper # sequencer\n\
+            \t\n\
+            eth,\n\
+            # da_dispatcher\n";
+        assert_eq!(
+            parse_components_file(contents),
+            ["api", "tree", "state_keeper", "eth"]
+        );
+    }
+
+    #[test]
+    fn merging_components_from_cli_and_file() {
+        assert_eq!(merge_components(None, None).unwrap(), None);
+
+        let from_cli = ["api,tree".to_owned()];
+        let merged = merge_components(Some(&from_cli), Some("tree\nstate_keeper\n")).unwrap();
+        assert_eq!(merged.unwrap(), ["api", "tree", "state_keeper"]);
+
+        let err = merge_components(Some(&from_cli), Some("tree\nunknown\n")).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("`unknown`"), "{err}");
+
+        let from_cli = ["api,unknown".to_owned()];
+        let err = merge_components(Some(&from_cli), None).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("`unknown`"), "{err}");
+    }
+
+    #[test]
+    fn excluding_components() {
+        let default_set = exclude_components(&[]).unwrap();
+        assert!(default_set.iter().all(|component| component != "api"));
+        assert!(default_set.iter().any(|component| component == "http_api"));
+        // Components not run by default are not added.
+        assert!(default_set.iter().all(|component| component != "tree_api"));
+
+        let components = exclude_components(&["consensus".to_owned()]).unwrap();
+        assert_eq!(
+            components,
+            [
+                "http_api",
+                "ws_api",
+                "contract_verification_api",
+                "tree",
+                "eth_watcher",
+                "eth_tx_aggregator",
+                "eth_tx_manager",
+                "state_keeper",
+                "housekeeper",
+                "commitment_generator",
+                "da_dispatcher",
+                "vm_runner_protective_reads",
+            ]
+        );
+
+        let excluded = ["tree,tree_api".to_owned(), "api".to_owned()];
+        let components = exclude_components(&excluded).unwrap();
+        let expected: Vec<_> = default_set
+            .iter()
+            .filter(|component| {
+                !["tree", "http_api", "ws_api", "contract_verification_api"]
+                    .contains(&component.as_str())
+            })
+            .cloned()
+            .collect();
+        assert_eq!(components, expected);
+        assert_eq!(components.len(), default_set.len() - 4);
+
+        let err = exclude_components(&["tree,unknown".to_owned()]).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("`unknown`"), "{err}");
+    }
+
+    fn parse_run_args(args: &[&str]) -> clap::error::Result<RunServerArgs> {
+        RunServerArgs::try_parse_from(std::iter::once("server").chain(args.iter().copied()))
+    }
+
+    #[test]
+    fn selecting_server_mode() {
+        let args = parse_run_args(&[]).unwrap();
+        assert!(matches!(args.server_mode(), Some(ServerMode::Normal)));
+        let args = parse_run_args(&["--genesis"]).unwrap();
+        assert!(matches!(args.server_mode(), Some(ServerMode::Genesis)));
+        let args = parse_run_args(&["--genesis", "--reuse-genesis"]).unwrap();
+        assert!(matches!(args.server_mode(), None));
+    }
+
+    #[test]
+    fn skipping_genesis_check() {
+        let args = parse_run_args(&["--skip-genesis-check"]).unwrap();
+        assert!(args.skip_genesis_check);
+        assert!(matches!(args.server_mode(), Some(ServerMode::Normal)));
+
+        let err = parse_run_args(&["--skip-genesis-check", "--genesis"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+        let err =
+            parse_run_args(&["--skip-genesis-check", "--genesis", "--reuse-genesis"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn components_exclude_conflicts_with_components() {
+        let args = parse_run_args(&["--components-exclude", "tree"]).unwrap();
+        let components = args.resolve_components().unwrap().unwrap();
+        assert!(!components.iter().any(|component| component == "tree"));
+        assert!(components
+            .iter()
+            .any(|component| component == "state_keeper"));
+
+        let err =
+            parse_run_args(&["--components-exclude", "tree", "--components", "api"]).unwrap_err();
+        assert_eq!(err.kind(), clap::error::ErrorKind::ArgumentConflict);
+    }
+
+    fn lookup(name: &str) -> Option<String> {
+        match name {
+            "HOME" => Some("/home/test".to_owned()),
+            "EMPTY" => Some(String::new()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn expanding_defined_env_vars() {
+        assert_eq!(
+            expand_env_vars("--config-path=${HOME}/general.yaml", lookup).unwrap(),
+            "--config-path=/home/test/general.yaml"
+        );
+        assert_eq!(
+            expand_env_vars("${HOME}:${HOME}", lookup).unwrap(),
+            "/home/test:/home/test"
+        );
+        assert_eq!(expand_env_vars("x${EMPTY}y", lookup).unwrap(), "xy");
+        // Defaults are ignored for defined vars, and refs without braces are left as is.
+        assert_eq!(
+            expand_env_vars("${HOME:-/tmp} $HOME", lookup).unwrap(),
+            "/home/test $HOME"
+        );
+    }
+
+    #[test]
+    fn expanding_defaulted_env_vars() {
+        assert_eq!(
+            expand_env_vars("--port=${PORT:-3050}", lookup).unwrap(),
+            "--port=3050"
+        );
+        assert_eq!(expand_env_vars("${PORT:-}", lookup).unwrap(), "");
+    }
+
+    #[test]
+    fn expanding_undefined_env_vars() {
+        let err = expand_env_vars("--port=${PORT}", lookup).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("`PORT`"), "{err}");
+
+        let err = expand_env_vars("--port=${PORT", lookup).unwrap_err();
+        let err = err.to_string();
+        assert!(err.contains("Unterminated"), "{err}");
+    }
+}
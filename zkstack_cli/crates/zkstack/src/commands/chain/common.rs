@@ -66,6 +66,7 @@ pub async fn mint_base_token(
             l1_rpc_url,
             ecosystem_config.l1_network.chain_id(),
             amount,
+            None,
         )
         .await?;
         spinner.finish();
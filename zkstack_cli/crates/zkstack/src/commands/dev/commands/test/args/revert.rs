@@ -2,6 +2,7 @@ use clap::Parser;
 
 use crate::commands::dev::messages::{
     MSG_NO_DEPS_HELP, MSG_NO_KILL_HELP, MSG_REVERT_TEST_ENABLE_CONSENSUS_HELP,
+    MSG_REVERT_TEST_TAIL_BATCHES_HELP, MSG_REVERT_TEST_TARGET_BATCH_HELP, MSG_STRICT_DEPS_HELP,
 };
 
 #[derive(Debug, Parser)]
@@ -10,6 +11,12 @@ pub struct RevertArgs {
     pub enable_consensus: bool,
     #[clap(short, long, help = MSG_NO_DEPS_HELP)]
     pub no_deps: bool,
+    #[clap(long, requires = "no_deps", help = MSG_STRICT_DEPS_HELP)]
+    pub strict_deps: bool,
     #[clap(long, help = MSG_NO_KILL_HELP)]
     pub no_kill: bool,
+    #[clap(long, help = MSG_REVERT_TEST_TARGET_BATCH_HELP)]
+    pub target_batch: Option<u32>,
+    #[clap(long, help = MSG_REVERT_TEST_TAIL_BATCHES_HELP)]
+    pub tail_batches: Option<u32>,
 }
@@ -2,18 +2,32 @@ use xshell::{cmd, Shell};
 use zkstack_cli_common::{cmd::Cmd, logger};
 use zkstack_cli_config::{EcosystemConfig, ZkStackConfig, ZkStackConfigTrait};
 
-use super::{args::revert::RevertArgs, utils::install_and_build_dependencies};
-use crate::commands::dev::messages::{MSG_REVERT_TEST_RUN_INFO, MSG_REVERT_TEST_RUN_SUCCESS};
+use super::{
+    args::revert::RevertArgs,
+    utils::{check_dependencies_staleness, install_and_build_dependencies},
+};
+use crate::commands::dev::messages::{
+    MSG_REVERT_TEST_INVALID_TARGET_BATCH_ERR, MSG_REVERT_TEST_RUN_INFO, MSG_REVERT_TEST_RUN_SUCCESS,
+};
 
-const REVERT_TESTS_PATH: &str = "core/tests/revert-test";
+/// Revert test in the `highlevel-test-tools` workspace; used as a `vitest` file filter.
+const REVERT_TEST_FILTER: &str = "tests/revert-test.test.ts";
 
 pub async fn run(shell: &Shell, args: RevertArgs) -> anyhow::Result<()> {
+    // Batch #0 is genesis, so it's always executed; the remaining checks require L1 and are performed by the test itself.
+    anyhow::ensure!(
+        args.target_batch != Some(0),
+        MSG_REVERT_TEST_INVALID_TARGET_BATCH_ERR
+    );
+
     let ecosystem_config = ZkStackConfig::ecosystem(shell)?;
-    shell.change_dir(ecosystem_config.link_to_code().join(REVERT_TESTS_PATH));
+    shell.change_dir(ecosystem_config.link_to_code());
 
     logger::info(MSG_REVERT_TEST_RUN_INFO);
 
-    if !args.no_deps {
+    if args.no_deps {
+        check_dependencies_staleness(&ecosystem_config.link_to_code(), args.strict_deps)?;
+    } else {
         install_and_build_dependencies(shell, &ecosystem_config.link_to_code())?;
     }
 
@@ -28,10 +42,17 @@ async fn run_test(
     args: &RevertArgs,
     ecosystem_config: &EcosystemConfig,
 ) -> anyhow::Result<()> {
-    let cmd = cmd!(shell, "yarn mocha tests/revert-and-restart-en.test.ts");
-    let cmd = Cmd::new(cmd)
+    // Run the test the same way as CI does, i.e. via the `highlevel-test-tools` workspace from the repo root.
+    let cmd = cmd!(shell, "yarn highlevel-test-tools test {REVERT_TEST_FILTER}");
+    let mut cmd = Cmd::new(cmd)
         .env("CHAIN_NAME", ecosystem_config.current_chain())
         .env("NO_KILL", args.no_kill.to_string());
+    if let Some(target_batch) = args.target_batch {
+        cmd = cmd.env("REVERT_TARGET_BATCH", target_batch.to_string());
+    }
+    if let Some(tail_batches) = args.tail_batches {
+        cmd = cmd.env("REVERT_TAIL_BATCHES", tail_batches.to_string());
+    }
     cmd.with_force_run().run()?;
 
     Ok(())
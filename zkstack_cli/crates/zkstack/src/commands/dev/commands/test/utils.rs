@@ -1,18 +1,26 @@
-use std::{collections::HashMap, path::Path};
+use std::{collections::HashMap, fs, path::Path, time::SystemTime};
 
 use serde::Deserialize;
 use xshell::{cmd, Shell};
-use zkstack_cli_common::{cmd::Cmd, spinner::Spinner, wallets::Wallet};
+use zkstack_cli_common::{cmd::Cmd, logger, spinner::Spinner, wallets::Wallet};
 use zkstack_cli_config::{ChainConfig, EcosystemConfig};
 
 use crate::commands::dev::messages::{
-    MSG_INTEGRATION_TESTS_BUILDING_CONTRACTS, MSG_INTEGRATION_TESTS_BUILDING_DEPENDENCIES,
+    msg_stale_dependencies, MSG_INTEGRATION_TESTS_BUILDING_CONTRACTS,
+    MSG_INTEGRATION_TESTS_BUILDING_DEPENDENCIES,
 };
 
 pub const TEST_WALLETS_PATH: &str = "etc/test_config/constant/eth.json";
 const AMOUNT_FOR_DISTRIBUTION_TO_TEST_WALLETS: u128 = 10_000u128 * 1_000_000_000_000_000_000u128; // 10k ETH
 pub const TS_INTEGRATION_PATH: &str = "core/tests/ts-integration";
 
+/// Pairs of (sources, artifact) paths relative to the repo root for dependencies built
+/// by [`install_and_build_dependencies()`].
+const DEPENDENCY_ARTIFACTS: &[(&str, &str)] = &[
+    ("yarn.lock", "node_modules/.yarn-integrity"),
+    ("etc/utils/src", "etc/utils/build"),
+];
+
 #[derive(Deserialize)]
 pub struct TestWallets {
     base_path: String,
@@ -79,3 +87,71 @@ pub fn install_and_build_dependencies(shell: &Shell, link_to_code: &Path) -> any
     spinner.finish();
     Ok(())
 }
+
+/// Returns whether an artifact is out of date w.r.t. its sources, given the latest modification
+/// times of both. A missing artifact is always stale; missing sources never make an artifact stale.
+fn is_stale(sources_modified: Option<SystemTime>, artifact_modified: Option<SystemTime>) -> bool {
+    match (sources_modified, artifact_modified) {
+        (_, None) => true,
+        (None, Some(_)) => false,
+        (Some(sources), Some(artifact)) => sources > artifact,
+    }
+}
+
+/// Returns the latest modification time among the file at `path` or files in the directory at `path` (recursively).
+fn latest_modification(path: &Path) -> Option<SystemTime> {
+    let metadata = fs::metadata(path).ok()?;
+    if !metadata.is_dir() {
+        return metadata.modified().ok();
+    }
+    fs::read_dir(path)
+        .ok()?
+        .filter_map(|entry| latest_modification(&entry.ok()?.path()))
+        .max()
+}
+
+/// Cheaply checks whether dependencies built by [`install_and_build_dependencies()`] are out of date
+/// by comparing modification times of their sources and artifacts. Warns about stale dependencies,
+/// or returns an error if `strict` is set.
+pub fn check_dependencies_staleness(link_to_code: &Path, strict: bool) -> anyhow::Result<()> {
+    let stale: Vec<_> = DEPENDENCY_ARTIFACTS
+        .iter()
+        .filter(|(sources, artifact)| {
+            is_stale(
+                latest_modification(&link_to_code.join(sources)),
+                latest_modification(&link_to_code.join(artifact)),
+            )
+        })
+        .map(|(_, artifact)| *artifact)
+        .collect();
+    if stale.is_empty() {
+        return Ok(());
+    }
+
+    let msg = msg_stale_dependencies(&stale);
+    if strict {
+        anyhow::bail!(msg);
+    }
+    logger::warn(msg);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    #[test]
+    fn staleness_comparison() {
+        let earlier = SystemTime::UNIX_EPOCH + Duration::from_secs(1_000);
+        let later = earlier + Duration::from_secs(1);
+
+        assert!(!is_stale(Some(earlier), Some(later)));
+        assert!(!is_stale(Some(earlier), Some(earlier)));
+        assert!(is_stale(Some(later), Some(earlier)));
+        assert!(is_stale(Some(earlier), None));
+        assert!(is_stale(None, None));
+        assert!(!is_stale(None, Some(earlier)));
+    }
+}
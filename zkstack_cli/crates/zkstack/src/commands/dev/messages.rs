@@ -105,6 +105,8 @@ pub(super) const MSG_TEST_RUST_OPTIONS_HELP: &str = "Cargo test flags";
 pub(super) const MSG_BUILD_ABOUT: &str = "Build all test dependencies";
 pub(super) const MSG_TESTS_EXTERNAL_NODE_HELP: &str = "Run tests for external node";
 pub(super) const MSG_NO_DEPS_HELP: &str = "Do not install or build dependencies";
+pub(super) const MSG_STRICT_DEPS_HELP: &str =
+    "Fail instead of warning if dependencies are out of date when not building them";
 pub(super) const MSG_EVM_TESTS_HELP: &str =
     "Expect EVM contracts to be enabled for the chain; fail EVM tests if they are not";
 pub(super) const MSG_TEST_SUITES_HELP: &str = "Test suite(s) to run, e.g. 'contracts' or 'erc20'";
@@ -113,6 +115,13 @@ pub(super) const MSG_TEST_PATTERN_HELP: &str =
 pub(super) const MSG_TEST_TIMEOUT_HELP: &str = "Timeout for tests in milliseconds";
 pub(super) const MSG_TEST_SECOND_CHAIN_HELP: &str =
     "Second chain to run tests on, used for interop tests. If not specified, interop tests will be run on the same chain";
+pub(super) fn msg_stale_dependencies(artifacts: &[&str]) -> String {
+    format!(
+        "Dependencies appear to be out of date ({}), tests may run against old code. \
+         Rerun without --no-deps to rebuild them",
+        artifacts.join(", ")
+    )
+}
 pub(super) const MSG_NO_KILL_HELP: &str = "The test will not kill all the nodes during execution";
 pub(super) const MSG_TESTS_RECOVERY_SNAPSHOT_HELP: &str =
     "Run recovery from a snapshot instead of genesis";
@@ -155,6 +164,11 @@ pub(super) const MSG_INTEGRATION_TESTS_BUILDING_CONTRACTS: &str = "Building test
 // Revert tests related messages
 pub(super) const MSG_REVERT_TEST_ENABLE_CONSENSUS_HELP: &str = "Enable consensus";
 pub(super) const MSG_REVERT_TEST_RUN_INFO: &str = "Running revert and restart test";
+pub(super) const MSG_REVERT_TEST_TARGET_BATCH_HELP: &str = "L1 batch number to revert to. Must be below the last committed batch and not executed yet. If not specified, the last executed batch is used";
+pub(super) const MSG_REVERT_TEST_TAIL_BATCHES_HELP: &str =
+    "Number of recent L1 batches to print from the main and external nodes if the test fails";
+pub(super) const MSG_REVERT_TEST_INVALID_TARGET_BATCH_ERR: &str =
+    "Target batch for revert must be positive";
 
 pub(super) const MSG_REVERT_TEST_RUN_SUCCESS: &str = "Revert and restart test ran successfully";
 
@@ -3,6 +3,7 @@ use xshell::{cmd, Shell};
 use zkstack_cli_common::{
     cmd::Cmd,
     config::global_config,
+    db::is_genesis_performed,
     logger,
     server::{Server, ServerMode},
 };
@@ -14,8 +15,10 @@ use zkstack_cli_config::{
 use crate::{
     commands::args::{RunServerArgs, ServerArgs, ServerCommand, WaitArgs},
     messages::{
-        msg_waiting_for_server_success, MSG_BUILDING_SERVER, MSG_FAILED_TO_BUILD_SERVER_ERR,
-        MSG_FAILED_TO_RUN_SERVER_ERR, MSG_STARTING_SERVER, MSG_WAITING_FOR_SERVER,
+        msg_genesis_probe_failed, msg_waiting_for_server_success, MSG_BUILDING_SERVER,
+        MSG_FAILED_TO_BUILD_SERVER_ERR, MSG_FAILED_TO_RUN_SERVER_ERR,
+        MSG_GENESIS_ALREADY_PERFORMED, MSG_GENESIS_CHECK_SKIPPED, MSG_GENESIS_NOT_PERFORMED,
+        MSG_STARTING_SERVER, MSG_WAITING_FOR_SERVER,
     },
 };
 
@@ -44,18 +47,22 @@ async fn run_server(
     chain_config: &ChainConfig,
     shell: &Shell,
 ) -> anyhow::Result<()> {
+    let components = args.resolve_components()?;
+    let additional_args = args.resolve_additional_args()?;
     logger::info(MSG_STARTING_SERVER);
     let server = Server::new(
         args.server_command,
-        args.components.clone(),
+        components,
         chain_config.link_to_code(),
         args.uring,
     );
 
-    let mode = if args.genesis {
-        ServerMode::Genesis
-    } else {
-        ServerMode::Normal
+    if args.skip_genesis_check {
+        logger::warn(MSG_GENESIS_CHECK_SKIPPED);
+    }
+    let mode = match args.server_mode() {
+        Some(mode) => mode,
+        None => select_genesis_mode(chain_config).await,
     };
 
     server
@@ -67,11 +74,37 @@ async fn run_server(
             chain_config.configs.join(GENERAL_FILE),
             chain_config.configs.join(SECRETS_FILE),
             ContractsConfig::get_path_with_base_path(&chain_config.configs),
-            args.additional_args,
+            additional_args,
         )
         .context(MSG_FAILED_TO_RUN_SERVER_ERR)
 }
 
+/// Probes the server database to decide whether genesis needs to be run.
+async fn select_genesis_mode(chain_config: &ChainConfig) -> ServerMode {
+    let probe = async {
+        let db_url = chain_config
+            .get_secrets_config()
+            .await?
+            .core_database_url()?
+            .context("missing core database URL")?;
+        is_genesis_performed(&db_url).await
+    };
+    match probe.await {
+        Ok(true) => {
+            logger::info(MSG_GENESIS_ALREADY_PERFORMED);
+            ServerMode::Normal
+        }
+        Ok(false) => {
+            logger::info(MSG_GENESIS_NOT_PERFORMED);
+            ServerMode::Genesis
+        }
+        Err(err) => {
+            logger::warn(msg_genesis_probe_failed(&err));
+            ServerMode::Genesis
+        }
+    }
+}
+
 async fn wait_for_server(args: WaitArgs, chain_config: &ChainConfig) -> anyhow::Result<()> {
     let verbose = global_config().verbose;
 
@@ -300,11 +300,39 @@ pub(super) const MSG_CHAIN_TRANSACTIONS_BUILT: &str = "Chain transactions succes
 
 /// Run server related messages
 pub(super) const MSG_SERVER_COMPONENTS_HELP: &str = "Components of server to run";
+pub(super) const MSG_SERVER_COMPONENTS_FILE_HELP: &str =
+    "File with components of server to run, one per line or comma-separated";
+pub(super) const MSG_SERVER_COMPONENTS_EXCLUDE_HELP: &str =
+    "Components of server to exclude from the default set of components";
+pub(super) const MSG_ALL_SERVER_COMPONENTS_EXCLUDED_ERR: &str =
+    "All server components are excluded";
 pub(super) const MSG_ENABLE_CONSENSUS_HELP: &str = "Enable consensus";
 pub(super) const MSG_SERVER_GENESIS_HELP: &str = "Run server in genesis mode";
+pub(super) const MSG_SERVER_REUSE_GENESIS_HELP: &str =
+    "Skip genesis if it was already performed and run server in normal mode";
+pub(super) const MSG_SERVER_SKIP_GENESIS_CHECK_HELP: &str =
+    "Skip checking whether genesis was performed and run server in normal mode";
 pub(super) const MSG_SERVER_ADDITIONAL_ARGS_HELP: &str =
     "Additional arguments that can be passed through the CLI";
 pub(super) const MSG_SERVER_URING_HELP: &str = "Enables uring support for RocksDB";
+pub(super) const MSG_SERVER_EXPAND_ENV_HELP: &str =
+    "Expand ${VAR} and ${VAR:-default} references to env variables in additional arguments";
+
+pub(super) fn msg_failed_to_read_components_file(path: &Path) -> String {
+    format!("Failed to read server components from {}", path.display())
+}
+
+pub(super) fn msg_unknown_server_component(component: &str) -> String {
+    format!("Unknown server component `{component}`")
+}
+
+pub(super) fn msg_undefined_env_var_in_server_arg(name: &str, arg: &str) -> String {
+    format!("Env variable `{name}` referenced in server argument `{arg}` is not defined")
+}
+
+pub(super) fn msg_unterminated_env_var_in_server_arg(arg: &str) -> String {
+    format!("Unterminated env variable reference in server argument `{arg}`")
+}
 
 /// Accept ownership related messages
 pub(super) const MSG_ACCEPTING_GOVERNANCE_SPINNER: &str = "Accepting governance...";
@@ -325,6 +353,17 @@ pub(super) const MSG_OBSERVABILITY_RUN_PROMPT: &str = "Do you want to run observ
 /// Server related messages
 pub(super) const MSG_STARTING_SERVER: &str = "Starting server";
 pub(super) const MSG_FAILED_TO_RUN_SERVER_ERR: &str = "Failed to start server";
+pub(super) const MSG_GENESIS_ALREADY_PERFORMED: &str =
+    "Genesis was already performed, starting server in normal mode";
+pub(super) const MSG_GENESIS_NOT_PERFORMED: &str =
+    "Genesis was not performed yet, starting server in genesis mode";
+pub(super) const MSG_GENESIS_CHECK_SKIPPED: &str =
+    "Genesis check is skipped, starting server in normal mode. Make sure that genesis was already performed!";
+pub(super) fn msg_genesis_probe_failed(err: &anyhow::Error) -> String {
+    format!(
+        "Failed to check whether genesis was performed ({err:#}), starting server in genesis mode"
+    )
+}
 pub(super) const MSG_PREPARING_EN_CONFIGS: &str = "Preparing External Node config";
 pub(super) const MSG_BUILDING_SERVER: &str = "Building server";
 pub(super) const MSG_FAILED_TO_BUILD_SERVER_ERR: &str = "Failed to build server";